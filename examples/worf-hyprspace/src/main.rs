@@ -2,7 +2,7 @@ use std::{
     env,
     fmt::{Display, Formatter},
     str::FromStr,
-    sync::{Arc, LazyLock, Mutex, RwLock},
+    sync::{Arc, LazyLock, Mutex},
     thread::sleep,
     time::{Duration, Instant},
 };
@@ -10,7 +10,10 @@ use std::{
 use clap::Parser;
 use hyprland::{
     data::{Client, Workspace, Workspaces},
-    dispatch::{Dispatch, DispatchType, WindowIdentifier, WorkspaceIdentifierWithSpecial},
+    dispatch::{
+        Dispatch, DispatchType, MonitorIdentifier, WindowIdentifier, WorkspaceIdentifier,
+        WorkspaceIdentifierWithSpecial,
+    },
     prelude::HyprData,
     shared::HyprDataActive,
 };
@@ -19,9 +22,12 @@ use regex::Regex;
 use serde::Deserialize;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use worf::gui::{
-    self, ArcFactory, ArcProvider, ExpandMode, ItemFactory, ItemProvider, MenuItem, ProviderData,
-    Selection,
+use worf::{
+    config::Key,
+    gui::{
+        self, ArcFactory, ArcProvider, CustomKeys, ExpandMode, ItemFactory, ItemProvider,
+        KeyBinding, MenuItem, Modifier, ProviderData, Selection, SubmitDisposition,
+    },
 };
 
 #[derive(Clone)]
@@ -35,6 +41,29 @@ struct HyprspaceProvider {
     cfg: HyprSpaceConfig,
     search_ignored_words: Vec<Regex>,
     detected_mode: Option<Mode>,
+    /// Only list workspaces of the focused monitor, toggled with `key_toggle_monitor`
+    current_monitor_only: bool,
+}
+
+/// What happens when switching to a workspace which is shown on another monitor
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+enum MonitorBehavior {
+    /// Focus the monitor showing the workspace
+    Focus,
+    /// Move the workspace to the focused monitor
+    Pull,
+}
+
+impl FromStr for MonitorBehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "focus" => Ok(MonitorBehavior::Focus),
+            "pull" => Ok(MonitorBehavior::Pull),
+            _ => Err(format!("Invalid monitor behavior: {s}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, EnumIter, PartialEq, Eq)]
@@ -90,6 +119,14 @@ struct HyprSpaceConfig {
 
     #[arg(long)]
     max_workspace_id: Option<i32>,
+
+    /// Focus or Pull, see `MonitorBehavior`
+    #[arg(long)]
+    monitor_behavior: Option<MonitorBehavior>,
+
+    /// Start with only the workspaces of the focused monitor listed
+    #[arg(long)]
+    current_monitor_only: Option<bool>,
 }
 
 impl HyprSpaceConfig {
@@ -104,6 +141,16 @@ impl HyprSpaceConfig {
     fn max_workspace_id(&self) -> i32 {
         self.max_workspace_id.unwrap_or(10)
     }
+
+    fn monitor_behavior(&self) -> MonitorBehavior {
+        self.monitor_behavior
+            .clone()
+            .unwrap_or(MonitorBehavior::Focus)
+    }
+
+    fn current_monitor_only(&self) -> bool {
+        self.current_monitor_only.unwrap_or(false)
+    }
 }
 
 impl HyprspaceProvider {
@@ -112,8 +159,24 @@ impl HyprspaceProvider {
             cfg: cfg.clone(),
             search_ignored_words,
             detected_mode: None,
+            current_monitor_only: cfg.current_monitor_only(),
         })
     }
+
+    fn current_mode(&self) -> Mode {
+        self.detected_mode
+            .clone()
+            .unwrap_or(self.cfg.hypr_space_mode())
+    }
+}
+
+fn key_toggle_monitor() -> KeyBinding {
+    KeyBinding {
+        key: Key::M,
+        modifiers: vec![Modifier::Alt].into_iter().collect(),
+        label: "<b>Alt+m</b> Toggle current monitor only".to_string(),
+        visible: true,
+    }
 }
 
 impl ItemProvider<Action> for HyprspaceProvider {
@@ -124,26 +187,39 @@ impl ItemProvider<Action> for HyprspaceProvider {
                     .find(|m| m.to_string().to_lowercase().trim() == q.to_lowercase())
                     .map(|m| {
                         self.detected_mode = Some(m.clone());
-                        ProviderData {
-                            items: Some(get_modes_actions(
-                                &m,
-                                query,
-                                self.search_ignored_words.as_ref(),
-                            )),
-                        }
+                        ProviderData::Items(get_modes_actions(
+                            &m,
+                            query,
+                            self.search_ignored_words.as_ref(),
+                            self.current_monitor_only,
+                        ))
                     })
             })
         } else {
             self.detected_mode = None;
             None
         };
-        auto.unwrap_or(ProviderData {
-            items: Some(get_modes_actions(
-                &self.cfg.hypr_space_mode(),
-                query,
+        auto.unwrap_or(ProviderData::Items(get_modes_actions(
+            &self.cfg.hypr_space_mode(),
+            query,
+            self.search_ignored_words.as_ref(),
+            self.current_monitor_only,
+        )))
+    }
+
+    /// The monitor filter is toggled without closing the window.
+    fn on_submit(&mut self, selection: &Selection<Action>) -> SubmitDisposition<Action> {
+        if selection.custom_key.as_ref() == Some(&key_toggle_monitor()) {
+            self.current_monitor_only = !self.current_monitor_only;
+            SubmitDisposition::Replace(get_modes_actions(
+                &self.current_mode(),
+                None,
                 self.search_ignored_words.as_ref(),
-            )),
-        })
+                self.current_monitor_only,
+            ))
+        } else {
+            SubmitDisposition::Accept
+        }
     }
 
     fn get_sub_elements(&mut self, item: &MenuItem<Action>) -> ProviderData<Action> {
@@ -156,18 +232,17 @@ impl ItemProvider<Action> for HyprspaceProvider {
             })
             .map(|m| {
                 self.detected_mode = Some(m.clone());
-                ProviderData {
-                    items: Some(get_modes_actions(
-                        &m,
-                        Some(&item.label),
-                        self.search_ignored_words.as_ref(),
-                    )),
-                }
+                ProviderData::Items(get_modes_actions(
+                    &m,
+                    Some(&item.label),
+                    self.search_ignored_words.as_ref(),
+                    self.current_monitor_only,
+                ))
             })
         {
             mode
         } else {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         }
     }
 }
@@ -199,6 +274,7 @@ fn build_menu_items<'a, F>(
     workspaces: &'a Workspaces,
     query: Option<&'a str>,
     search_ignored_words: &Vec<Regex>,
+    current_monitor_only: bool,
     filter_fn: F,
 ) -> Vec<MenuItem<Action>>
 where
@@ -207,6 +283,7 @@ where
     workspaces
         .iter()
         .filter(|ws| filter_fn(ws))
+        .filter(|ws| !current_monitor_only || ws.monitor == aws.monitor)
         .map(|ws| workspace_to_menu_item(mode, aws, ws))
         .chain(query.map(|q| {
             MenuItem::new(
@@ -229,6 +306,7 @@ fn get_modes_actions(
     mode: &Mode,
     query: Option<&str>,
     search_ignored_words: &Vec<Regex>,
+    current_monitor_only: bool,
 ) -> Vec<MenuItem<Action>> {
     let workspaces = match hyprland::data::Workspaces::get() {
         Ok(ws) => ws,
@@ -264,24 +342,37 @@ fn get_modes_actions(
             })
             .collect(),
 
-        Mode::Rename | Mode::DeleteWorkspace => {
-            build_menu_items(mode, &aws, &workspaces, query, search_ignored_words, |_| {
-                true
-            })
-        }
+        Mode::Rename | Mode::DeleteWorkspace => build_menu_items(
+            mode,
+            &aws,
+            &workspaces,
+            query,
+            search_ignored_words,
+            current_monitor_only,
+            |_| true,
+        ),
 
         Mode::SwitchToWorkspace
         | Mode::MoveAllWindowsToOtherWorkSpace
         | Mode::MoveCurrentWindowToOtherWorkspace
-        | Mode::MoveCurrentWindowToOtherWorkspaceSilent => {
-            build_menu_items(mode, &aws, &workspaces, query, search_ignored_words, |ws| {
-                ws.id != aws.id
-            })
-        }
+        | Mode::MoveCurrentWindowToOtherWorkspaceSilent => build_menu_items(
+            mode,
+            &aws,
+            &workspaces,
+            query,
+            search_ignored_words,
+            current_monitor_only,
+            |ws| ws.id != aws.id,
+        ),
     }
 }
 
 fn workspace_to_menu_item(mode: &Mode, aws: &Workspace, ws: &Workspace) -> MenuItem<Action> {
+    let badge = if ws.monitor == aws.monitor {
+        ws.windows.to_string()
+    } else {
+        format!("{} · {}", ws.windows, ws.monitor)
+    };
     MenuItem::new(
         ws.name.clone(),
         None,
@@ -294,6 +385,7 @@ fn workspace_to_menu_item(mode: &Mode, aws: &Workspace, ws: &Workspace) -> MenuI
             mode: mode.clone(),
         }),
     )
+    .with_badge(badge)
 }
 
 fn handle_sub_selection(
@@ -307,13 +399,11 @@ fn handle_sub_selection(
                 .to_lowercase()
                 .contains(&item.label.to_lowercase())
         })
-        .map(|m| ProviderData {
-            items: Some(get_modes_actions(&m, query, search_ignored_words)),
-        })
+        .map(|m| ProviderData::Items(get_modes_actions(&m, query, search_ignored_words, false)))
     {
         mode
     } else {
-        ProviderData { items: None }
+        ProviderData::Unchanged
     }
 }
 
@@ -322,24 +412,22 @@ struct EmptyProvider {}
 
 impl ItemProvider<Action> for EmptyProvider {
     fn get_elements(&mut self, search: Option<&str>) -> ProviderData<Action> {
-        ProviderData {
-            items: Some(vec![MenuItem::new(
-                search.unwrap_or_default().to_owned(),
-                None,
-                None,
-                Vec::new(),
-                None,
-                0.0,
-                Some(Action {
-                    workspace: None,
-                    mode: Mode::Auto,
-                }),
-            )]),
-        }
+        ProviderData::Items(vec![MenuItem::new(
+            search.unwrap_or_default().to_owned(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            0.0,
+            Some(Action {
+                workspace: None,
+                mode: Mode::Auto,
+            }),
+        )])
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<Action>) -> ProviderData<Action> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
     }
 }
 
@@ -369,14 +457,15 @@ fn show_gui<T: ItemProvider<Action> + ItemFactory<Action> + Send + Clone + 'stat
     cfg: &HyprSpaceConfig,
     pattern: &Regex,
     provider: Arc<Mutex<T>>,
+    custom_keys: Option<CustomKeys>,
 ) -> Result<Selection<Action>, String> {
     gui::show(
-        &Arc::new(RwLock::new(cfg.worf.clone())),
+        &cfg.worf,
         Arc::clone(&provider) as ArcProvider<Action>,
         Some(provider as ArcFactory<Action>),
         Some(vec![pattern.clone()]),
         ExpandMode::WithSpace,
-        None,
+        custom_keys,
     )
     .map_err(|e| e.to_string())
 }
@@ -464,6 +553,29 @@ where
     Ok(())
 }
 
+fn workspace_keys() -> CustomKeys {
+    CustomKeys {
+        bindings: vec![key_toggle_monitor()],
+        hint: None,
+    }
+}
+
+/// Moves the workspace to the focused monitor, if it is shown on another one.
+fn pull_workspace(action: Option<&Action>) -> Result<(), String> {
+    let Some(ws) = action.and_then(|action| action.workspace.as_ref()) else {
+        return Ok(());
+    };
+    let aws = Workspace::get_active().map_err(|e| format!("failed to get active workspace {e}"))?;
+    if ws.monitor != aws.monitor {
+        Dispatch::call(DispatchType::MoveWorkspaceToMonitor(
+            WorkspaceIdentifier::Id(ws.id),
+            MonitorIdentifier::Current,
+        ))
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), String> {
     env_logger::Builder::new()
         .parse_filters(&env::var("RUST_LOG").unwrap_or_else(|_| "error".to_owned()))
@@ -500,10 +612,10 @@ fn process_inputs(
     pattern: &Regex,
     provider: Arc<Mutex<HyprspaceProvider>>,
 ) -> Result<(), String> {
-    let result = show_gui(cfg, pattern, Arc::clone(&provider))?;
+    let result = show_gui(cfg, pattern, Arc::clone(&provider), Some(workspace_keys()))?;
 
     let result_items = handle_sub_selection(&result.menu, None, vec![pattern.clone()].as_ref());
-    let result = if result_items.items.is_some() {
+    let result = if matches!(result_items, ProviderData::Items(_)) {
         if let Some(menu) = result.menu.data {
             cfg.hypr_space_mode = Some(menu.mode.clone());
             cfg.worf.set_prompt(cfg.hypr_space_mode().to_string());
@@ -512,7 +624,7 @@ fn process_inputs(
                 &cfg.clone(),
                 vec![pattern.clone()],
             )?));
-            show_gui(cfg, pattern, provider)?
+            show_gui(cfg, pattern, provider, Some(workspace_keys()))?
         } else {
             result
         }
@@ -534,7 +646,7 @@ fn process_inputs(
                 cfg.worf
                     .set_prompt(format!("Rename {} to  ", result.menu.label));
                 let provider = Arc::new(Mutex::new(EmptyProvider {}));
-                let rename_result = show_gui(cfg, pattern, provider)?;
+                let rename_result = show_gui(cfg, pattern, provider, None)?;
 
                 let new_name = if cfg.add_id_prefix() {
                     let ws_id = action
@@ -560,6 +672,9 @@ fn process_inputs(
             // Clippy suggests removing this closure as redundant,
             // but doing so causes lifetime inference issues with `DispatchType::Workspace`.
             // Keeping the closure avoids `'static` lifetime assumptions.
+            if cfg.monitor_behavior() == MonitorBehavior::Pull {
+                pull_workspace(action.as_ref())?;
+            }
             #[allow(clippy::redundant_closure)]
             handle_workspace_action(cfg, &result.menu.label, action, |ws| {
                 DispatchType::Workspace(ws)
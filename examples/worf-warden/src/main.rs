@@ -2,7 +2,7 @@ use std::{
     collections::HashMap,
     env,
     process::Command,
-    sync::{Arc, Mutex, RwLock},
+    sync::{Arc, Mutex},
     thread::sleep,
     time::Duration,
 };
@@ -11,60 +11,137 @@ use clap::Parser;
 use serde::{Deserialize, Serialize};
 use worf::{
     config::{self, Config, CustomKeyHintLocation, Key, TextOutputMode},
-    desktop::{copy_to_clipboard, spawn_fork},
+    desktop::{copy_to_clipboard, spawn_fork, type_text},
     gui::{
         self, CustomKeyHint, CustomKeys, ExpandMode, ItemProvider, KeyBinding, MenuItem, Modifier,
-        ProviderData,
+        ProviderData, Selection, SubmitDisposition,
     },
 };
 
 #[derive(Clone)]
 struct MenuItemMetaData {
     ids: Vec<String>,
+    /// Shown in the details panel
+    details: Vec<(String, String)>,
 }
 
 #[derive(Clone)]
 struct PasswordProvider {
     items: Vec<MenuItem<MenuItemMetaData>>,
+    /// Copies the user name without closing the window
+    copy_user_key: KeyBinding,
 }
 
-fn split_at_tab(input: &str) -> Option<(&str, &str)> {
-    let mut parts = input.splitn(2, '\t');
-    Some((parts.next()?, parts.next()?))
+/// A single vault entry as listed by rbw
+#[derive(Debug)]
+struct VaultEntry {
+    id: String,
+    name: String,
+    user: String,
+    folder: String,
+    uri: String,
+}
+
+fn parse_vault_entries(output: &str) -> Vec<VaultEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(ToOwned::to_owned);
+            Some(VaultEntry {
+                id: fields.next()?,
+                name: fields.next()?,
+                user: fields.next().unwrap_or_default(),
+                folder: fields.next().unwrap_or_default(),
+                uri: fields.next().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// User, url and folder of the entries sharing a name, empty fields are left out.
+fn entry_details(entries: &[VaultEntry]) -> Vec<(String, String)> {
+    let mut details = Vec::new();
+    for entry in entries {
+        if !entry.user.is_empty() {
+            details.push(("User".to_owned(), entry.user.clone()));
+        }
+        if !entry.uri.is_empty() {
+            details.push(("URL".to_owned(), entry.uri.clone()));
+        }
+    }
+    if let Some(entry) = entries.first()
+        && !entry.folder.is_empty()
+    {
+        details.push(("Folder".to_owned(), entry.folder.clone()));
+    }
+    details
+}
+
+fn rbw_list() -> Result<Vec<VaultEntry>, String> {
+    // uris can only be listed by newer rbw versions
+    let output = rbw("list", Some(vec!["--fields", "id,name,user,folder,uri"])).or_else(|e| {
+        log::warn!("Failed to list uris, searching by url is not possible: {e}");
+        rbw("list", Some(vec!["--fields", "id,name,user,folder"]))
+    })?;
+    Ok(parse_vault_entries(&output))
 }
 
 impl PasswordProvider {
-    fn new(config: &Config) -> Result<Self, String> {
-        let output = rbw("list", Some(vec!["--fields", "id,name"]))?;
-        let mut items = output
-            .lines()
-            .filter_map(|s| split_at_tab(s))
-            .fold(
-                HashMap::new(),
-                |mut acc: HashMap<String, Vec<String>>, (id, name)| {
-                    acc.entry(name.to_owned()).or_default().push(id.to_owned());
-                    acc
-                },
-            )
-            .iter()
-            .map(|(key, value)| {
-                MenuItem::new(
-                    key.clone(),
+    fn new(config: &Config, warden_config: &WardenConfig) -> Result<Self, String> {
+        // entries sharing a name are merged, the user is asked which one to use on submit.
+        let mut entries: HashMap<(String, String), Vec<VaultEntry>> = HashMap::new();
+        for entry in rbw_list()? {
+            entries
+                .entry((entry.folder.clone(), entry.name.clone()))
+                .or_default()
+                .push(entry);
+        }
+
+        let mut items = entries
+            .into_iter()
+            .map(|((folder, name), entries)| {
+                let mut search_terms: Vec<_> = entries
+                    .iter()
+                    .flat_map(|entry| [entry.user.clone(), entry.uri.clone()])
+                    .filter(|term| !term.is_empty())
+                    .collect();
+                if !folder.is_empty() {
+                    search_terms.push(folder.clone());
+                }
+
+                let item = MenuItem::new(
+                    name,
                     None,
                     None,
-                    vec![].into_iter().collect(),
+                    vec![],
                     None,
                     0.0,
-                    Some(MenuItemMetaData { ids: value.clone() }),
+                    Some(MenuItemMetaData {
+                        details: entry_details(&entries),
+                        ids: entries.into_iter().map(|entry| entry.id).collect(),
+                    }),
                 )
+                .with_search_terms(search_terms);
+                if folder.is_empty() {
+                    item
+                } else {
+                    item.with_badge(folder)
+                }
             })
             .collect::<Vec<_>>();
+
+        if warden_config.folders() {
+            items = group_by_folder(items);
+        }
         gui::apply_sort(&mut items, &config.sort_order());
 
-        Ok(Self { items })
+        Ok(Self {
+            items,
+            copy_user_key: key_copy_user(warden_config),
+        })
     }
 
-    fn sub_provider(ids: Vec<String>) -> Result<Self, String> {
+    fn sub_provider(&self, ids: Vec<String>) -> Result<Self, String> {
         let items = ids
             .iter()
             .map(|id| {
@@ -77,23 +154,25 @@ impl PasswordProvider {
                     0.0,
                     Some(MenuItemMetaData {
                         ids: vec![id.clone()].into_iter().collect(),
+                        details: Vec::new(),
                     }),
                 ))
             })
             .collect::<Result<Vec<_>, String>>()?;
 
-        Ok(Self { items })
+        Ok(Self {
+            items,
+            copy_user_key: self.copy_user_key.clone(),
+        })
     }
 }
 
 impl ItemProvider<MenuItemMetaData> for PasswordProvider {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<MenuItemMetaData> {
         if query.is_some() {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
-            ProviderData {
-                items: Some(self.items.clone()),
-            }
+            ProviderData::Items(self.items.clone())
         }
     }
 
@@ -101,8 +180,75 @@ impl ItemProvider<MenuItemMetaData> for PasswordProvider {
         &mut self,
         _: &MenuItem<MenuItemMetaData>,
     ) -> ProviderData<MenuItemMetaData> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
+    }
+
+    fn details(&self, item: &MenuItem<MenuItemMetaData>) -> Option<Vec<(String, String)>> {
+        item.data.as_ref().map(|meta| meta.details.clone())
+    }
+
+    /// Entries sharing a name ask which user to use before the window closes.
+    /// Copying the user name keeps the window open.
+    fn on_submit(
+        &mut self,
+        selection: &Selection<MenuItemMetaData>,
+    ) -> SubmitDisposition<MenuItemMetaData> {
+        match &selection.menu.data {
+            None if !selection.menu.sub_elements.is_empty() => {
+                SubmitDisposition::Reject("Expand the folder to select an entry".to_owned())
+            }
+            Some(meta) if meta.ids.len() > 1 => match self.sub_provider(meta.ids.clone()) {
+                Ok(provider) => SubmitDisposition::Replace(provider.items),
+                Err(e) => SubmitDisposition::Reject(e),
+            },
+            Some(meta) if selection.custom_key.as_ref() == Some(&self.copy_user_key) => {
+                let id = meta.ids.first().unwrap_or(&selection.menu.label);
+                match rbw_get_user(id, true) {
+                    Ok(_) => SubmitDisposition::Reject(format!(
+                        "Copied user of {}",
+                        selection.menu.label
+                    )),
+                    Err(e) => SubmitDisposition::Reject(e),
+                }
+            }
+            _ => SubmitDisposition::Accept,
+        }
+    }
+}
+
+/// Moves entries into an expandable item per folder.
+/// The folder items can be found by the names and search terms of their entries.
+fn group_by_folder(items: Vec<MenuItem<MenuItemMetaData>>) -> Vec<MenuItem<MenuItemMetaData>> {
+    let mut folders: HashMap<String, Vec<MenuItem<MenuItemMetaData>>> = HashMap::new();
+    let mut grouped = Vec::new();
+    for item in items {
+        if let Some(folder) = item.badge.clone() {
+            folders.entry(folder).or_default().push(item);
+        } else {
+            grouped.push(item);
+        }
     }
+
+    grouped.extend(folders.into_iter().map(|(folder, mut entries)| {
+        entries.sort_by(|l, r| l.label.cmp(&r.label));
+        let search_terms = entries
+            .iter()
+            .flat_map(|entry| {
+                std::iter::once(entry.label.clone()).chain(entry.search_terms.iter().cloned())
+            })
+            .collect();
+        MenuItem::new(
+            folder,
+            Some("folder".to_owned()),
+            None,
+            entries,
+            None,
+            0.0,
+            None,
+        )
+        .with_search_terms(search_terms)
+    }));
+    grouped
 }
 
 fn groups() -> String {
@@ -115,14 +261,21 @@ fn groups() -> String {
 }
 
 fn keyboard_type(text: &str, cfg: &WardenConfig) {
-    let mut cmd = Command::new(cfg.typing_cmd());
+    let Some(typing_cmd) = &cfg.typing_cmd else {
+        if let Err(e) = type_text(text) {
+            log::error!("Failed to type text: {e}");
+        }
+        return;
+    };
+
+    let mut cmd = Command::new(typing_cmd);
     for arg in cfg.typing_cmd_args() {
         cmd.arg(arg);
     }
     cmd.arg(text);
 
     cmd.output()
-        .unwrap_or_else(|_| panic!("Failed to execute {}", cfg.typing_cmd()));
+        .unwrap_or_else(|_| panic!("Failed to execute {typing_cmd}"));
 }
 
 fn keyboard_return(config: &WardenConfig) {
@@ -244,6 +397,26 @@ fn rbw_get_totp(id: &str, copy: bool) -> Result<String, String> {
     rbw_get(id, "totp", copy)
 }
 
+/// First url stored for the entry
+fn rbw_get_uri(id: &str) -> Result<String, String> {
+    rbw_get(id, "uris", false)?
+        .lines()
+        .next()
+        .map(|uri| uri.trim().to_owned())
+        .filter(|uri| !uri.is_empty())
+        .ok_or_else(|| "Entry has no url".to_owned())
+}
+
+/// Custom auto type configured for the entry, by id or label, or user tab password
+fn auto_type_of(id: &str, label: &str, config: &WardenConfig) -> String {
+    config
+        .custom_auto_types
+        .get(id)
+        .or(config.custom_auto_types.get(label))
+        .cloned()
+        .unwrap_or_else(|| "$U\t$P".to_owned())
+}
+
 fn key_type_all() -> KeyBinding {
     KeyBinding {
         key: Key::Num1,
@@ -335,6 +508,31 @@ fn key_totp_to_clipboard() -> KeyBinding {
     }
 }
 
+fn key_copy_user(config: &WardenConfig) -> KeyBinding {
+    let key = config.key_copy_user();
+    KeyBinding {
+        key,
+        modifiers: vec![Modifier::Alt].into_iter().collect(),
+        label: format!("<b>Alt+{}</b> Copy User", key_name(key)),
+        visible: true,
+    }
+}
+
+/// Opens the url of the entry and types user and password into it afterwards
+fn key_open_url(config: &WardenConfig) -> KeyBinding {
+    let key = config.key_open_url();
+    KeyBinding {
+        key,
+        modifiers: vec![Modifier::Alt].into_iter().collect(),
+        label: format!("<b>Alt+{}</b> Open Url", key_name(key)),
+        visible: true,
+    }
+}
+
+fn key_name(key: Key) -> String {
+    format!("{key:?}").to_lowercase()
+}
+
 fn key_lock() -> KeyBinding {
     KeyBinding {
         key: Key::L,
@@ -345,12 +543,12 @@ fn key_lock() -> KeyBinding {
 }
 
 fn show(
-    config: Arc<RwLock<Config>>,
+    config: &Config,
     provider: Arc<Mutex<PasswordProvider>>,
     warden_config: WardenConfig,
 ) -> Result<(), String> {
     match gui::show(
-        &config,
+        config,
         provider,
         None,
         None,
@@ -367,6 +565,8 @@ fn show(
                 key_type_totp_and_enter(),
                 key_sync(),
                 key_totp_to_clipboard(),
+                key_copy_user(&warden_config),
+                key_open_url(&warden_config),
                 key_lock(),
             ],
             hint: Some(CustomKeyHint {
@@ -377,26 +577,20 @@ fn show(
     ) {
         Ok(selection) => {
             if let Some(meta) = selection.menu.data {
-                if meta.ids.len() > 1 {
-                    return show(
-                        config,
-                        Arc::new(Mutex::new(PasswordProvider::sub_provider(meta.ids)?)),
-                        warden_config.clone(),
-                    );
-                }
-
                 let id = meta.ids.first().unwrap_or(&selection.menu.label);
 
                 sleep(Duration::from_millis(500));
                 if let Some(key) = selection.custom_key {
                     if key == key_type_all() || key == key_type_all_and_enter() {
-                        let default = "$U\t$P".to_owned();
-                        let typing = warden_config
-                            .custom_auto_types
-                            .get(id)
-                            .or(warden_config.custom_auto_types.get(&selection.menu.label))
-                            .unwrap_or(&default);
-                        keyboard_auto_type(typing, id, &warden_config)?;
+                        let typing = auto_type_of(id, &selection.menu.label, &warden_config);
+                        keyboard_auto_type(&typing, id, &warden_config)?;
+                    } else if key == key_open_url(&warden_config) {
+                        let uri = rbw_get_uri(id)?;
+                        spawn_fork(&format!("xdg-open {uri}"), None, &[])
+                            .map_err(|e| format!("Failed to open {uri}: {e}"))?;
+                        sleep(Duration::from_millis(warden_config.open_url_delay()));
+                        let typing = auto_type_of(id, &selection.menu.label, &warden_config);
+                        keyboard_auto_type(&typing, id, &warden_config)?;
                     } else if key == key_type_user() || key == key_type_user_and_enter() {
                         keyboard_type(&rbw_get_user(id, false)?, &warden_config);
                     } else if key == key_type_password() || key == key_type_password_and_enter() {
@@ -416,7 +610,7 @@ fn show(
                     }
                 } else {
                     let pw = rbw_get_password(id, true)?;
-                    match config.read().unwrap().text_output_mode() {
+                    match config.text_output_mode() {
                         TextOutputMode::Clipboard => {
                             if let Err(e) = copy_to_clipboard(pw, None) {
                                 log::error!("failed to copy to clipboard: {e}");
@@ -425,6 +619,11 @@ fn show(
                         TextOutputMode::StandardOutput => {
                             println!("{pw}");
                         }
+                        TextOutputMode::Type => {
+                            if let Err(e) = type_text(&pw) {
+                                log::error!("failed to type password: {e}");
+                            }
+                        }
                         TextOutputMode::None => {}
                     }
                 }
@@ -441,12 +640,33 @@ fn show(
 struct WardenConfig {
     typing_cmd: Option<String>,
     typing_cmd_args: Option<Vec<String>>,
+    /// Show folders as expandable entries instead of a badge next to the entries.
+    folders: Option<bool>,
+    /// Key which copies the user name when pressed with Alt
+    key_copy_user: Option<Key>,
+    /// Key which opens the url and auto types into it when pressed with Alt
+    key_open_url: Option<Key>,
+    /// Milliseconds to wait for the browser before auto typing
+    open_url_delay: Option<u64>,
+    #[serde(default)]
     custom_auto_types: HashMap<String, String>,
 }
 
 impl WardenConfig {
-    fn typing_cmd(&self) -> String {
-        self.typing_cmd.clone().unwrap_or("ydotool".to_owned())
+    fn folders(&self) -> bool {
+        self.folders.unwrap_or(false)
+    }
+
+    fn key_copy_user(&self) -> Key {
+        self.key_copy_user.unwrap_or(Key::U)
+    }
+
+    fn key_open_url(&self) -> Key {
+        self.key_open_url.unwrap_or(Key::O)
+    }
+
+    fn open_url_delay(&self) -> u64 {
+        self.open_url_delay.unwrap_or(2000)
     }
 
     fn typing_cmd_args(&self) -> Vec<String> {
@@ -483,28 +703,27 @@ fn main() -> Result<(), String> {
             WardenConfig::default()
         });
 
-    if !groups().contains("input") {
-        log::error!(
-            "User must be in input group. 'sudo usermod -aG input $USER', then login again"
-        );
-        std::process::exit(1)
-    }
-
-    // ydotool is our special default value, give it some love and start the daemon
+    // ydotool needs some love, check the permissions and start the daemon.
     // if other tools need this it must be run beforehand (or can be added here)
-    // in case another tool is added it might make sense to make it configurable
-    if warden_config.typing_cmd() == "ydotool" {
+    // without a configured tool worf types on its own and handles ydotool as fallback.
+    if warden_config.typing_cmd.as_deref() == Some("ydotool") {
+        if !groups().contains("input") {
+            log::error!(
+                "User must be in input group. 'sudo usermod -aG input $USER', then login again"
+            );
+            std::process::exit(1)
+        }
+
         // will exit if there is a daemon running already, so it's fine to call this everytime.
-        if let Err(e) = spawn_fork("ydotoold", None) {
+        if let Err(e) = spawn_fork("ydotoold", None, &[]) {
             log::error!("Failed to start ydotool daemon: {e}");
         }
     }
 
-    let worf_config = Arc::new(RwLock::new(cfg.worf.clone()));
-
     // todo eventually use a propper rust client for this, for now rbw is good enough
     let provider = Arc::new(Mutex::new(PasswordProvider::new(
-        &worf_config.read().unwrap(),
+        &cfg.worf,
+        &warden_config,
     )?));
-    show(worf_config, provider, warden_config)
+    show(&cfg.worf, provider, warden_config)
 }
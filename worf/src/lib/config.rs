@@ -1,10 +1,15 @@
-use std::{env, fs, path::PathBuf, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fmt, fs,
+    path::PathBuf,
+    str::FromStr,
+};
 
 use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 
-use crate::Error;
+use crate::{Error, gui::Modifier};
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Anchor {
@@ -46,6 +51,12 @@ pub enum WrapMode {
 pub enum SortOrder {
     Default,
     Alphabetical,
+    /// Most often used entries first, others alphabetical
+    MostUsed,
+    /// Last used entries first, others alphabetical
+    RecentlyUsed,
+    /// Entries used often and recently first, others alphabetical
+    Frecency,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -70,11 +81,252 @@ pub enum Layer {
     Overlay,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ScrollbarMode {
+    /// Classic scrollbar, shown if the content does not fit
+    Auto,
+    /// Classic scrollbar, always shown along the entries
+    Always,
+    /// No scrollbar, same as `hide_scroll`
+    Never,
+    /// Scrollbar is drawn above the content while scrolling (GTK default)
+    Overlay,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum IconFallback {
+    /// Show `icon_placeholder` instead
+    Placeholder,
+    /// Keep the space of the icon empty, so labels stay aligned
+    Blank,
+    /// Leave out the image cell
+    Collapse,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Focus {
+    /// Typing edits the search, arrow keys move the selection
+    Search,
+    /// All keys act on the list, letters select the next entry starting with them
+    List,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum DetailsPosition {
+    /// Next to the entries
+    Right,
+    /// Below the entries
+    Bottom,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum StylePreset {
+    /// Defaults as documented for each option
+    Default,
+    /// Horizontal strip of icons sized to its entries, the search is hidden
+    /// and typed text is shown as overlay
+    Dock,
+    /// Single row of entries along the top edge with the search in front,
+    /// as many entries are shown as fit the width
+    Bar,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum HiddenSearchInput {
+    /// Typed text filters the entries, same as with a visible search
+    Filter,
+    /// A letter selects the next entry starting with it
+    QuickJump,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ListDirection {
+    /// Search on top, best match below it
+    Down,
+    /// Search at the bottom, best match above it
+    Up,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ExpanderPosition {
+    /// Arrow in front of the entry
+    Start,
+    /// Arrow after the entry
+    End,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TextOutputMode {
     None,
     Clipboard,
     StandardOutput,
+    /// Types the text into the focused window, see `desktop::type_text`
+    Type,
+}
+
+/// External command of auto mode, its output is shown as single row
+/// when the search matches `pattern`, i.e. to look up the weather.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct InfoCommand {
+    /// Shown while the command runs and in front of its errors
+    pub name: String,
+    /// Regex matched against the search, the capture groups are passed
+    /// to the command as `$1`, `$2`..., the whole match if there are none
+    pub pattern: String,
+    /// Run with `sh -c`, the first line of its output is shown
+    pub command: String,
+    /// Icon of the row
+    pub icon: Option<String>,
+}
+
+/// Weights of the sources blended by `auto_suggestions`,
+/// the score of an entry in its source is multiplied by them.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AutoSuggestionWeights {
+    /// Applications, ranked by their usage
+    pub drun: f64,
+    /// The row searching the web for the query
+    pub websearch: f64,
+    /// Recently used emoji
+    pub emoji: f64,
+}
+
+impl Default for AutoSuggestionWeights {
+    fn default() -> Self {
+        Self {
+            drun: 1.0,
+            websearch: 0.5,
+            emoji: 0.5,
+        }
+    }
+}
+
+/// Options of a single mode, see `modes`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModeConfig {
+    /// Placeholder text of the search, i.e. `Calculate…`
+    pub placeholder: Option<String>,
+    /// Icon name or path replacing the magnifier of the search
+    pub icon: Option<String>,
+    /// Overrides the global `min_query_length` for the mode
+    pub min_query_length: Option<usize>,
+}
+
+impl ModeConfig {
+    /// Built-in options of the given mode.
+    fn default_for(mode: &str) -> Self {
+        let (placeholder, icon) = match mode {
+            "drun" => (None, Some("system-search-symbolic")),
+            "run" => (None, Some("utilities-terminal-symbolic")),
+            "math" => (Some("Calculate…"), Some("accessories-calculator-symbolic")),
+            _ => (None, None),
+        };
+        Self {
+            placeholder: placeholder.map(str::to_owned),
+            icon: icon.map(str::to_owned),
+            min_query_length: None,
+        }
+    }
+}
+
+/// Custom key of dmenu mode, submitting with it exits with `exit_code`
+/// so scripts can offer several actions for an entry.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DmenuCustomKey {
+    /// Key with optional modifiers joined by `+`, i.e. `Alt+1`
+    pub key: String,
+    /// Shown in the custom key bar
+    pub label: String,
+    /// Defaults to 10 for the first key, 11 for the second and so on, like rofi
+    pub exit_code: Option<i32>,
+}
+
+/// Exit code of worf if an error occurred.
+/// A selection exits with 0, no selection with `no_selection_exit_code`.
+pub const EXIT_CODE_ERROR: i32 = 2;
+
+/// Options applied to the shown window when the config is reloaded,
+/// changes of all others require a restart. See `Config::apply_reloadable_options`.
+pub const RELOADABLE_OPTIONS: &[&str] = &[
+    "style",
+    "prompt",
+    "matching",
+    "insensitive",
+    "fuzzy_min_score",
+    "search_terms_weight",
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ClipboardBackend {
+    /// <https://github.com/sentriz/cliphist>
+    Cliphist,
+    /// <https://github.com/chmouel/clipman>
+    Clipman,
+    /// <https://hluk.github.io/CopyQ/>
+    CopyQ,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum LaunchMethod {
+    /// Started as process in a new session of its own
+    Fork,
+    /// Started in a transient systemd scope via `systemd-run --user --scope`
+    Systemd,
+    /// Desktop entries are started via `gio launch`, other commands are forked
+    Gio,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PointerWarp {
+    /// The pointer is not moved
+    Keep,
+    /// The pointer is moved to the center of the window when it opens
+    Center,
+}
+
+impl FromStr for PointerWarp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "keep" | "none" => Ok(PointerWarp::Keep),
+            "center" => Ok(PointerWarp::Center),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid pointer warp, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for LaunchMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fork" => Ok(LaunchMethod::Fork),
+            "systemd" | "systemd-run" => Ok(LaunchMethod::Systemd),
+            "gio" => Ok(LaunchMethod::Gio),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid launch method, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for ClipboardBackend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cliphist" => Ok(ClipboardBackend::Cliphist),
+            "clipman" => Ok(ClipboardBackend::Clipman),
+            "copyq" => Ok(ClipboardBackend::CopyQ),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid clipboard backend, see help for details"
+            ))),
+        }
+    }
 }
 
 impl FromStr for Layer {
@@ -127,6 +379,9 @@ impl FromStr for SortOrder {
         match s.trim().to_lowercase().as_str() {
             "alphabetical" => Ok(SortOrder::Alphabetical),
             "default" => Ok(SortOrder::Default),
+            "mostused" | "most-used" => Ok(SortOrder::MostUsed),
+            "recentlyused" | "recently-used" => Ok(SortOrder::RecentlyUsed),
+            "frecency" => Ok(SortOrder::Frecency),
             _ => Err(Error::InvalidArgument(
                 format!("{s} is not a valid argument, see help for details").to_owned(),
             )),
@@ -148,13 +403,130 @@ impl FromStr for KeyDetectionType {
     }
 }
 
+impl FromStr for ScrollbarMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(ScrollbarMode::Auto),
+            "always" => Ok(ScrollbarMode::Always),
+            "never" => Ok(ScrollbarMode::Never),
+            "overlay" => Ok(ScrollbarMode::Overlay),
+            _ => Err(Error::InvalidArgument(
+                format!("{s} is not a valid argument, see help for details").to_owned(),
+            )),
+        }
+    }
+}
+
+impl FromStr for ListDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "down" => Ok(ListDirection::Down),
+            "up" => Ok(ListDirection::Up),
+            _ => Err(Error::InvalidArgument(
+                format!("{s} is not a valid argument, see help for details").to_owned(),
+            )),
+        }
+    }
+}
+
+impl FromStr for IconFallback {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "placeholder" => Ok(IconFallback::Placeholder),
+            "blank" => Ok(IconFallback::Blank),
+            "collapse" => Ok(IconFallback::Collapse),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for Focus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "search" => Ok(Focus::Search),
+            "list" => Ok(Focus::List),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for DetailsPosition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "right" => Ok(DetailsPosition::Right),
+            "bottom" => Ok(DetailsPosition::Bottom),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for StylePreset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "default" => Ok(StylePreset::Default),
+            "dock" => Ok(StylePreset::Dock),
+            "bar" => Ok(StylePreset::Bar),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for HiddenSearchInput {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "filter" => Ok(HiddenSearchInput::Filter),
+            "quickjump" | "quick-jump" => Ok(HiddenSearchInput::QuickJump),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
+impl FromStr for ExpanderPosition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "start" => Ok(ExpanderPosition::Start),
+            "end" => Ok(ExpanderPosition::End),
+            _ => Err(Error::InvalidArgument(format!(
+                "{s} is not a valid argument, see help for details"
+            ))),
+        }
+    }
+}
+
 impl FromStr for TextOutputMode {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.trim().to_lowercase().as_str() {
-            "clipboard" => Ok(TextOutputMode::Clipboard),
-            "stdout" | "standardoutput" => Ok(TextOutputMode::StandardOutput),
+            "clipboard" | "copy" => Ok(TextOutputMode::Clipboard),
+            "stdout" | "standardoutput" | "print" => Ok(TextOutputMode::StandardOutput),
+            "type" => Ok(TextOutputMode::Type),
             "none" => Ok(TextOutputMode::None),
             _ => Err(format!("{s} is not a valid layer.")),
         }
@@ -267,6 +639,14 @@ pub enum Key {
     Question,     // ?
     Grave,        // `
     Tilde,        // ~
+
+    // Mouse buttons and scrolling
+    MouseMiddle,
+    MouseRight,
+    MouseBack,
+    MouseForward,
+    ScrollUp,
+    ScrollDown,
 }
 
 impl FromStr for Key {
@@ -377,6 +757,14 @@ impl FromStr for Key {
             "?" => Key::Question,
             "`" => Key::Grave,
             "~" => Key::Tilde,
+
+            // Mouse buttons and scrolling
+            "MouseMiddle" => Key::MouseMiddle,
+            "MouseRight" => Key::MouseRight,
+            "MouseBack" => Key::MouseBack,
+            "MouseForward" => Key::MouseForward,
+            "ScrollUp" => Key::ScrollUp,
+            "ScrollDown" => Key::ScrollDown,
             _ => Key::None,
         };
 
@@ -388,6 +776,125 @@ impl FromStr for Key {
     }
 }
 
+/// A key and the modifiers held while pressing it, written like `Ctrl+j`, `Ctrl-Enter`
+/// or `Alt++`. Bare keys match no matter which modifiers are held,
+/// so `Enter` also submits on `Ctrl+Enter`, unless another binding uses them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBindingSpec {
+    pub key: Key,
+    /// Contains `Modifier::None` if no modifier has to be held.
+    pub modifiers: HashSet<Modifier>,
+}
+
+impl KeyBindingSpec {
+    /// False for bare keys.
+    #[must_use]
+    pub fn has_modifiers(&self) -> bool {
+        self.modifiers
+            .iter()
+            .any(|modifier| *modifier != Modifier::None)
+    }
+
+    /// True if the held modifiers are the ones of the binding, which is always
+    /// the case for bare keys. Caps lock is ignored.
+    #[must_use]
+    pub fn matches_modifiers(&self, held: &HashSet<Modifier>) -> bool {
+        if !self.has_modifiers() {
+            return true;
+        }
+        let held: HashSet<_> = held
+            .iter()
+            .copied()
+            .filter(|modifier| !matches!(modifier, Modifier::None | Modifier::CapsLock))
+            .collect();
+        held == self.modifiers
+    }
+}
+
+impl From<Key> for KeyBindingSpec {
+    fn from(key: Key) -> Self {
+        KeyBindingSpec {
+            key,
+            modifiers: HashSet::from([Modifier::None]),
+        }
+    }
+}
+
+impl FromStr for KeyBindingSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const SEPARATORS: [char; 2] = ['+', '-'];
+        let (modifiers, key) = match s.char_indices().nth_back(1) {
+            // the separators can be bound themselves as last part, i.e. `Alt++` or `Ctrl--`
+            Some((i, c)) if SEPARATORS.contains(&c) && s.ends_with(SEPARATORS) => {
+                (&s[..i], &s[i + 1..])
+            }
+            _ => s
+                .rsplit_once(SEPARATORS)
+                .filter(|(_, key)| !key.is_empty())
+                .unwrap_or(("", s)),
+        };
+        let mut modifiers = modifiers
+            .split(SEPARATORS)
+            .filter(|m| !m.is_empty())
+            .map(|m| match m.trim().to_lowercase().as_str() {
+                "alt" => Ok(Modifier::Alt),
+                "ctrl" | "control" => Ok(Modifier::Control),
+                "shift" => Ok(Modifier::Shift),
+                "super" => Ok(Modifier::Super),
+                "meta" => Ok(Modifier::Meta),
+                _ => Err(Error::InvalidArgument(format!(
+                    "{m} is not a valid modifier of {s}"
+                ))),
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+        if modifiers.is_empty() {
+            modifiers.insert(Modifier::None);
+        }
+
+        let key = key.trim();
+        // the names of the variants are accepted as well, they are used when serializing
+        let key = key
+            .parse::<Key>()
+            .or_else(|e| serde_json::from_value(Value::String(key.to_owned())).map_err(|_| e))?;
+        Ok(KeyBindingSpec { key, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBindingSpec {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyBindingSpec> for String {
+    fn from(value: KeyBindingSpec) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for KeyBindingSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // a fixed order, so equal bindings are written the same way
+        for (modifier, name) in [
+            (Modifier::Control, "Ctrl"),
+            (Modifier::Alt, "Alt"),
+            (Modifier::Shift, "Shift"),
+            (Modifier::Super, "Super"),
+            (Modifier::Meta, "Meta"),
+        ] {
+            if self.modifiers.contains(&modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, Parser)]
 #[clap(
     about = "Worf is a wofi like launcher, written in rust, it aims to be a drop-in replacement"
@@ -407,12 +914,45 @@ pub struct Config {
     #[serde(default = "default_false")]
     version: bool,
 
+    /// Prints statistics of worf, like the path of the newest launch log, and exits.
+    #[clap(long = "stats")]
+    #[serde(default = "default_false")]
+    stats: bool,
+
+    /// Checks the configuration for errors and conflicting key bindings,
+    /// prints the findings and exits with `EXIT_CODE_ERROR` if there are any.
+    #[clap(long = "check-config")]
+    #[serde(default = "default_false")]
+    check_config: bool,
+
+    /// Runs worf as long-running daemon, which shows the menus of the worf instances
+    /// started afterwards without starting up again.
+    #[clap(long = "daemon")]
+    #[serde(default = "default_false")]
+    daemon: bool,
+
+    /// Global shortcuts the daemon registers with the compositor, mapping the mode they show
+    /// to a description of their trigger, pressing one again closes the menu.
+    /// The keys are bound in the compositor, i.e. `bind = SUPER, space, global, worf:drun`.
+    /// Only configurable in the config file as `[daemon_shortcuts]`, i.e. `drun = "SUPER+space"`.
+    /// Requires hyprland, defaults to none.
+    #[clap(skip)]
+    daemon_shortcuts: Option<HashMap<String, String>>,
+
     /// Defines the style sheet to be loaded.
     /// Defaults to `$XDG_CONF_DIR/worf/style.css`
     /// or `$HOME/.config/worf/style.css` if `$XDG_CONF_DIR` is not set.
     #[clap(long = "style")]
     style: Option<String>,
 
+    /// Changes the defaults of other options, options which are set explicitly still apply.
+    /// `Dock` defaults to horizontal orientation, `icon_only`, `dynamic_width`,
+    /// `hide_search` with `hidden_search_overlay` and the icon placeholder.
+    /// `Bar` defaults to horizontal orientation, full width and the top edge as `location`.
+    /// Defaults to Default
+    #[clap(long = "style-preset", alias = "layout")]
+    style_preset: Option<StylePreset>,
+
     /// Default width of the window, defaults to 50% of the screen
     #[clap(long = "width")]
     width: Option<String>,
@@ -421,15 +961,51 @@ pub struct Config {
     #[clap(long = "height")]
     height: Option<String>,
 
+    /// Size the window to its entries instead of `width`.
+    /// In horizontal orientation the height follows the entries as well.
+    /// Defaults to false
+    #[clap(long = "dynamic-width")]
+    dynamic_width: Option<bool>,
+
+    /// Show only the icon of entries, the text is shown as tooltip.
+    /// Defaults to false
+    #[clap(long = "icon-only")]
+    icon_only: Option<bool>,
+
     /// Defines which prompt is used. Default is selected 'show'
     #[clap(short = 'p', long = "prompt")]
     prompt: Option<String>,
 
+    /// Icon name or path replacing the magnifier of the search.
+    /// Defaults to the icon of the mode, see `modes`.
+    #[clap(long = "prompt-icon")]
+    prompt_icon: Option<String>,
+
+    /// Options per mode, i.e. placeholder and icon of the search and `min_query_length`.
+    /// `prompt` and `prompt_icon` take precedence, `min_query_length` is overridden.
+    /// Only configurable in the config file as `[modes.<mode>]`.
+    /// Defaults to a magnifier for drun, a terminal for run and `Calculate…` for math.
+    #[clap(skip)]
+    modes: Option<HashMap<String, ModeConfig>>,
+
+    /// Number of characters the search needs before the mode is asked for entries,
+    /// nothing is shown before. Helps with expensive modes and huge dmenu inputs.
+    /// Defaults to 0
+    #[clap(long = "min-query-length")]
+    min_query_length: Option<usize>,
+
     /// If true a normal window instead of a layer shell will be used
     #[clap(short = 'n', long = "normal-window")]
     #[serde(default = "default_false")]
     normal_window: bool,
 
+    /// Namespace of the layer shell for compositor rules, also the application id
+    /// of the window if it is a valid one, i.e. `org.example.Prompt`.
+    /// Several worf instances can run at once with the same id,
+    /// a different one allows separate rules, i.e. for a prompt of a script. Defaults to `worf`
+    #[clap(long = "app-id")]
+    app_id: Option<String>,
+
     /// Set to 'false' to disable images, defaults to true
     #[clap(short = 'I', long = "allow-images")]
     allow_images: Option<bool>,
@@ -462,10 +1038,28 @@ pub struct Config {
     #[clap(short = 'P', long = "password")]
     password: Option<String>,
 
+    /// Hardened input for password like prompts.
+    /// Forces an exclusive keyboard layer, keeps input methods away from the search,
+    /// overwrites the search text on close and does not log any key presses.
+    /// Defaults to false.
+    #[clap(long = "secure-input")]
+    secure_input: Option<bool>,
+
     /// Defines whether the scrollbar is visible
     #[clap(short = 'b', long = "hide-scroll")]
     hide_scroll: Option<bool>,
 
+    /// Defines how the scrollbar is shown, the scrollbar can be styled via `#scrollbar`.
+    /// Defaults to `Overlay`, or `Never` if `hide_scroll` is set.
+    #[clap(long = "scrollbar")]
+    scrollbar: Option<ScrollbarMode>,
+
+    /// Scrolling the mouse wheel or touchpad over the list moves the selection
+    /// instead of only scrolling the list, flicks keep moving it for a moment.
+    /// Scroll bindings like `mouse_copy = "ScrollUp"` take precedence. Defaults to false.
+    #[clap(long = "scroll-selection")]
+    scroll_selection: Option<bool>,
+
     /// Defines the matching method, defaults to contains
     #[clap(short = 'M', long = "matching")]
     matching: Option<MatchMethod>,
@@ -488,6 +1082,56 @@ pub struct Config {
     #[clap(short = 'a', long = "no-actions")]
     no_actions: Option<bool>,
 
+    /// Show the origin of applications in drun mode as badge,
+    /// i.e. flatpak, snap or 32-bit for native multilib applications.
+    /// Defaults to false
+    #[clap(long = "drun-show-origin")]
+    drun_show_origin: Option<bool>,
+
+    /// Hide flatpak applications in drun mode. Defaults to false
+    #[clap(long = "drun-hide-flatpak")]
+    drun_hide_flatpak: Option<bool>,
+
+    /// Hide snap applications in drun mode. Defaults to false
+    #[clap(long = "drun-hide-snap")]
+    drun_hide_snap: Option<bool>,
+
+    /// Hide native 32-bit applications in drun mode, i.e. on multilib systems.
+    /// Defaults to false
+    #[clap(long = "drun-hide-32bit")]
+    drun_hide_32bit: Option<bool>,
+
+    /// List the entries drun mode hides, i.e. because of `NoDisplay` or `Hidden`
+    /// or a missing binary, with the reason as badge of css class `hidden`.
+    /// Helps to find out why an application is missing, Alt+U unhides the selected one.
+    /// Defaults to false
+    #[clap(long = "include-hidden", alias = "drun-include-hidden")]
+    drun_include_hidden: Option<bool>,
+
+    /// Hide drun entries not meant for the desktops in `$XDG_CURRENT_DESKTOP`
+    /// as given by `OnlyShowIn` and `NotShowIn`, and entries whose `TryExec`
+    /// binary does not exist. Defaults to true
+    #[clap(long = "desktop-env-filter")]
+    desktop_env_filter: Option<bool>,
+
+    /// List every instance of a binary found in multiple `$PATH` directories in run mode,
+    /// instead of only the one the shell would run. Defaults to false
+    #[clap(long = "run-all-instances")]
+    run_all_instances: Option<bool>,
+
+    /// Alt+<key> pins or unpins the selected entry in drun mode.
+    /// Pinned entries stay at the top and have the css class `pinned`.
+    /// Defaults to P
+    #[clap(long = "key-pin")]
+    key_pin: Option<Key>,
+
+    /// Alt+<key> hides the selected entry in drun mode by adding it to
+    /// `$XDG_CONFIG_HOME/worf/drun-blacklist`. With `drun_include_hidden`
+    /// the entry is listed again and Alt+U removes it from the list.
+    /// Defaults to H
+    #[clap(long = "key-hide-entry")]
+    key_hide_entry: Option<Key>,
+
     /// If set, the given amount tof lines will be shown
     #[clap(short = 'L', long = "lines")]
     lines: Option<i32>,
@@ -505,13 +1149,40 @@ pub struct Config {
     #[clap(short = 'w', long = "columns")]
     columns: Option<u32>,
 
+    /// Space in pixels between rows of entries. Defaults to 0.
+    #[clap(long = "row-spacing")]
+    row_spacing: Option<u32>,
+
+    /// Space in pixels between columns of entries. Defaults to 0.
+    #[clap(long = "column-spacing")]
+    column_spacing: Option<u32>,
+
+    /// Space in pixels around the content of an entry. Defaults to 0.
+    #[clap(long = "item-padding")]
+    item_padding: Option<u16>,
+
     /// Defines how elements are sorted
     /// Options:
     /// * Alphabetical
+    /// * `MostUsed` (usage count, supported by drun and run)
+    /// * `RecentlyUsed` (last usage, supported by drun and run)
+    /// * Frecency (usage count decaying over time, supported by drun, run, ssh, emoji and websearch)
     /// * Default (no sort applied)
     #[clap(short = 'O', long = "sort-order")]
     sort_order: Option<SortOrder>,
 
+    /// Days after which a use counts half as much for the sort order `Frecency`,
+    /// must be greater than 0.
+    /// Defaults to 14
+    #[clap(long = "history-half-life", value_parser = parse_half_life)]
+    #[serde(default, deserialize_with = "deserialize_half_life")]
+    history_half_life: Option<f64>,
+
+    /// Entries kept per mode in the usage history, the lowest scored are dropped first.
+    /// Defaults to 500
+    #[clap(long = "history-max-entries")]
+    history_max_entries: Option<usize>,
+
     /// Search for given value at startup
     #[clap(short = 'Q', long = "search")]
     search: Option<String>,
@@ -522,6 +1193,12 @@ pub struct Config {
     #[clap(long = "orientation")]
     orientation: Option<Orientation>,
 
+    /// Direction the list grows in.
+    /// `Up` puts the search at the bottom and the best match right above it,
+    /// i.e. for windows anchored to the bottom. Defaults to `Down`.
+    #[clap(long = "list-direction")]
+    list_direction: Option<ListDirection>,
+
     /// Horizontal alignment
     #[clap(long = "halign")]
     halign: Option<Align>,
@@ -542,33 +1219,189 @@ pub struct Config {
     #[clap(long = "image-size")]
     image_size: Option<u16>,
 
+    /// What is shown for entries without an icon or with one that cannot be found.
+    /// Defaults to Collapse.
+    #[clap(long = "icon-fallback")]
+    icon_fallback: Option<IconFallback>,
+
+    /// Icon name or path shown with `icon_fallback = Placeholder`.
+    /// Defaults to image-missing.
+    #[clap(long = "icon-placeholder")]
+    icon_placeholder: Option<String>,
+
     /// If set to `true` the search field will be hidden.
     #[clap(long = "hide-search")]
     hide_search: Option<bool>,
 
+    /// What typing does while the search is hidden.
+    /// Defaults to Filter.
+    #[clap(long = "hidden-search-input")]
+    hidden_search_input: Option<HiddenSearchInput>,
+
+    /// Show the filter text above the entries while typing with hidden search,
+    /// it can be styled via `#search-overlay`.
+    /// Defaults to false.
+    #[clap(long = "hidden-search-overlay")]
+    hidden_search_overlay: Option<bool>,
+
+    /// Show the magnifier icon in the search field, it can be styled via `#search-icon`.
+    /// Defaults to true.
+    #[clap(long = "search-icon")]
+    search_icon: Option<bool>,
+
+    /// Show a button to clear the search, it can be styled via `#search-clear`.
+    /// Defaults to true.
+    #[clap(long = "search-clear-button")]
+    search_clear_button: Option<bool>,
+
     /// can be set to a key to toggle the search bar.
     /// default is not set.
     #[clap(long = "key-hide-search")]
-    key_hide_search: Option<Key>,
+    key_hide_search: Option<KeyBindingSpec>,
+
+    /// Part of the window receiving the keys when it opens.
+    /// The focused part has the css class `focused`.
+    /// Defaults to Search.
+    #[clap(long = "initial-focus")]
+    initial_focus: Option<Focus>,
+
+    /// can be set to a key to move the focus between the search and the list.
+    /// default is not set.
+    #[clap(long = "key-toggle-focus")]
+    key_toggle_focus: Option<KeyBindingSpec>,
+
+    /// Show the details of the selected entry in a panel, i.e. user and url in worf-warden.
+    /// Only modes providing details fill it, it can be styled via `#details`.
+    /// Defaults to false.
+    #[clap(long = "details")]
+    details: Option<bool>,
+
+    /// Where the details panel is placed, can be `Right` or `Bottom`.
+    /// Defaults to Right.
+    #[clap(long = "details-position")]
+    details_position: Option<DetailsPosition>,
+
+    /// Show a preview of the selected entry, i.e. the description of applications,
+    /// metadata of files or the full text of dmenu lines.
+    /// It is placed like the details panel and can be styled via `#preview`.
+    /// Defaults to false.
+    #[clap(long = "preview")]
+    preview: Option<bool>,
+
+    /// can be set to a key to show or hide the details panel.
+    /// default is not set.
+    #[clap(long = "key-toggle-details")]
+    key_toggle_details: Option<KeyBindingSpec>,
+
+    /// Additional key moving the selection up, the arrow keys always work.
+    /// Can be combined with modifiers, i.e. `Ctrl+k` for vim like navigation.
+    /// default is not set.
+    #[clap(long = "key-up")]
+    key_up: Option<KeyBindingSpec>,
+
+    /// Additional key moving the selection down, i.e. `Ctrl+j`.
+    /// default is not set.
+    #[clap(long = "key-down")]
+    key_down: Option<KeyBindingSpec>,
+
+    /// Additional key moving the selection up by a page, `PageUp` always works.
+    /// default is not set.
+    #[clap(long = "key-pgup")]
+    key_pgup: Option<KeyBindingSpec>,
+
+    /// Additional key moving the selection down by a page, `PageDown` always works.
+    /// default is not set.
+    #[clap(long = "key-pgdn")]
+    key_pgdn: Option<KeyBindingSpec>,
+
+    /// Additional key selecting the first entry, `Home` always works, i.e. `Alt+g`.
+    /// default is not set.
+    #[clap(long = "key-home")]
+    key_home: Option<KeyBindingSpec>,
+
+    /// Additional key selecting the last entry, `End` always works, i.e. `Alt+Shift+g`.
+    /// default is not set.
+    #[clap(long = "key-end")]
+    key_end: Option<KeyBindingSpec>,
 
     /// Key to run the associated thing.
     /// Defaults to enter
     #[clap(long = "key-submit")]
-    key_submit: Option<Key>,
+    key_submit: Option<KeyBindingSpec>,
 
     /// Key to close the window.
     /// Defaults to escape
     #[clap(long = "key-exit")]
-    key_exit: Option<Key>,
+    key_exit: Option<KeyBindingSpec>,
 
     /// Can be set to a Key which copies the action to the clipboard.
     /// Copying to clipboard does not affect any cache file
     #[clap(long = "key-copy")]
-    key_copy: Option<Key>,
+    key_copy: Option<KeyBindingSpec>,
 
     /// Used to expand or autocomplete entries. Defaults to tab
     #[clap(long = "key-expand")]
-    key_expand: Option<Key>,
+    key_expand: Option<KeyBindingSpec>,
+
+    /// Mouse button or scroll direction which copies the action of the entry below the pointer,
+    /// like `key_copy`, i.e. `MouseMiddle`. Defaults to None
+    #[clap(long = "mouse-copy")]
+    mouse_copy: Option<KeyBindingSpec>,
+
+    /// Mouse button or scroll direction which expands the entry below the pointer,
+    /// i.e. to show the actions of an application, like `MouseRight`. Defaults to None
+    #[clap(long = "mouse-expand")]
+    mouse_expand: Option<KeyBindingSpec>,
+
+    /// Key to switch to the next mode of `switch_modes`, keeping the search text.
+    /// Auto mode switches without closing the window.
+    /// Defaults to None
+    #[clap(long = "key-switch-mode")]
+    key_switch_mode: Option<KeyBindingSpec>,
+
+    /// Modes cycled through by `key_switch_mode`, the key does nothing in other modes.
+    /// Auto mode cycles through the ones it contains, i.e. drun, file, ssh, math and websearch,
+    /// and detects the mode from the search again after the last one.
+    /// Defaults to drun, window, emoji.
+    #[clap(long = "switch-modes", value_delimiter = ',')]
+    switch_modes: Option<Vec<String>>,
+
+    /// Commands showing a single row of information in auto mode,
+    /// only configurable in the config file as `[[info_commands]]`.
+    /// Defaults to none.
+    #[clap(skip)]
+    info_commands: Option<Vec<InfoCommand>>,
+
+    /// Custom keys of dmenu mode, submitting with one prints the selection
+    /// and exits with its exit code. Only configurable in the config file as `[[custom_key]]`.
+    /// Defaults to none.
+    #[clap(skip)]
+    #[serde(alias = "custom-key")]
+    custom_key: Option<Vec<DmenuCustomKey>>,
+
+    /// Blend the applications with a websearch row and recently used emoji
+    /// when the search of auto mode matches none of its prefixes.
+    /// Defaults to false
+    #[clap(long = "auto-suggestions")]
+    auto_suggestions: Option<bool>,
+
+    /// Weights of the sources blended by `auto_suggestions`,
+    /// only configurable in the config file as `[auto_suggestion_weights]`.
+    /// Defaults to drun 1.0, websearch 0.5, emoji 0.5.
+    #[clap(skip)]
+    auto_suggestion_weights: Option<AutoSuggestionWeights>,
+
+    /// Show the built-in key bindings (submit, expand, copy, exit) in the key hint bar.
+    /// Hints can be clicked to trigger their binding.
+    /// Defaults to false
+    #[clap(long = "key-hints")]
+    key_hints: Option<bool>,
+
+    /// Clipboard manager the clipboard history is read from.
+    /// Can be one of `Cliphist`, `Clipman` or `CopyQ`.
+    /// Defaults to Cliphist
+    #[clap(long = "clipboard-backend")]
+    clipboard_backend: Option<ClipboardBackend>,
 
     /// If enabled, worf will resize according to the amount of displayed rows
     /// defaults to false
@@ -587,6 +1420,13 @@ pub struct Config {
     #[clap(long = "layer")]
     layer: Option<Layer>,
 
+    /// Moves the pointer to the window when it opens, for focus follows mouse setups
+    /// where the window would lose the focus if it opens on another monitor.
+    /// Needs Hyprland or Sway, as Wayland does not allow clients to move the pointer.
+    /// Defaults to Keep.
+    #[clap(long = "pointer-warp")]
+    pointer_warp: Option<PointerWarp>,
+
     /// If set to `true` single click instead of double click will select
     /// Defaults to `false`
     #[clap(long = "single-click")]
@@ -596,6 +1436,18 @@ pub struct Config {
     #[clap(long = "fuzzy-min-score")]
     fuzzy_min_score: Option<f64>,
 
+    /// Weight of matches in the additional search terms of an item compared
+    /// to matches in its label, see `MenuItem::with_search_terms`.
+    /// Defaults to 0.8.
+    #[clap(long = "search-terms-weight")]
+    search_terms_weight: Option<f64>,
+
+    /// Number of entries closest to the search which are suggested
+    /// if nothing matches it. They are not selected, so submitting still uses the search.
+    /// Defaults to 0, which disables suggestions.
+    #[clap(long = "suggestion-count")]
+    suggestion_count: Option<usize>,
+
     /// Orientation of items in the row box where items are displayed
     #[clap(long = "row-box-orientation")]
     row_box_orientation: Option<Orientation>,
@@ -616,6 +1468,13 @@ pub struct Config {
     #[clap(long = "line-max-width-chars")]
     line_max_width_chars: Option<i32>,
 
+    /// Position of the arrow of entries with sub elements.
+    /// Can be Start or End.
+    /// Sub elements are indented to the text of their parent in both cases.
+    /// Defaults to Start
+    #[clap(long = "expander-position")]
+    expander_position: Option<ExpanderPosition>,
+
     /// Display only icon in emoji mode
     #[clap(long = "emoji-hide-string")]
     emoji_hide_label: Option<bool>,
@@ -630,6 +1489,12 @@ pub struct Config {
     #[clap(long = "search-query")]
     search_query: Option<String>,
 
+    /// Offer to open the query directly if it is an url or domain, i.e. `github.com`,
+    /// in websearch and auto mode. Urls without scheme are opened with https.
+    /// Defaults to true
+    #[clap(long = "search-open-urls")]
+    search_open_urls: Option<bool>,
+
     /// Blur the background of the screen
     /// can be styled via `background`
     #[clap(long = "blurred-background")]
@@ -642,6 +1507,13 @@ pub struct Config {
     #[clap(long = "blurred-background-fullscreen")]
     blurred_background_fullscreen: Option<bool>,
 
+    /// If set, the blurred background only covers this many pixels
+    /// around the window instead of the whole screen.
+    /// It follows the window when it is resized. Requires a layer shell window.
+    /// Defaults to None.
+    #[clap(long = "blurred-background-margin")]
+    blurred_background_margin: Option<i32>,
+
     /// Allow submitting selected entry with expand key if there is only 1 item left.
     #[clap(long = "submit-with-expand")]
     submit_with_expand: Option<bool>,
@@ -650,9 +1522,76 @@ pub struct Config {
     #[clap(long = "auto-select-on-search")]
     auto_select_on_search: Option<bool>,
 
-    /// Jump to the first/last entry when at the end/start and down/up is pressed
+    /// Time in milliseconds the only choice left has to stay the same
+    /// before it is auto selected. Defaults to 200.
+    #[clap(long = "auto-select-delay")]
+    auto_select_delay: Option<u64>,
+
+    /// Time in milliseconds the submitted entry is shown with the `activated`
+    /// css class before the window closes. Set to 0 to disable.
+    /// Defaults to 150.
+    #[clap(long = "activation-feedback-duration")]
+    activation_feedback_duration: Option<u64>,
+
+    /// Time in milliseconds a launched application is watched after the window closed.
+    /// If it cannot be started or exits with an error within that time,
+    /// a notification is shown. Defaults to 0, which disables watching.
+    #[clap(long = "launch-failure-timeout")]
+    launch_failure_timeout: Option<u64>,
+
+    /// Write stdout and stderr of launched applications to
+    /// `$XDG_STATE_HOME/worf/launch-logs/<app>-<timestamp>.log`,
+    /// `--stats` prints the path of the newest one.
+    /// Defaults to false.
+    #[clap(long = "launch-log")]
+    launch_log: Option<bool>,
+
+    /// Number of launch logs which are kept. Defaults to 20.
+    #[clap(long = "launch-log-count")]
+    launch_log_count: Option<usize>,
+
+    /// How applications of drun and run mode are started, can be `Fork`, `Systemd` or `Gio`.
+    /// `Systemd` puts them in a scope of their own, so they are not part of the cgroup of worf.
+    /// Defaults to Fork.
+    #[clap(long = "launch-method")]
+    launch_method: Option<LaunchMethod>,
+
+    /// Print the resolved command of the selected entry instead of running it.
+    /// The output contains the working directory and environment variables if set.
+    /// Defaults to false.
+    #[clap(long = "dry-run")]
+    dry_run: Option<bool>,
+
+    /// Print the monitor, size and layer shell settings of the window as json
+    /// once it is shown and exit. Helps to debug sizing and positioning issues.
+    /// Defaults to false.
+    #[clap(long = "print-window-info")]
+    print_window_info: Option<bool>,
+
+    /// Open the GTK inspector and print the widget tree with the names and css classes
+    /// worf assigns, as css selectors. Helps to find selectors for styles.
+    /// Defaults to false.
+    #[clap(long = "debug-style")]
+    debug_style: Option<bool>,
+
+    /// Write the selection to this inherited file descriptor instead of stdout.
+    /// Keeps stdout clean for wrappers which use it for something else.
+    /// Defaults to stdout
+    #[clap(long = "output-fd")]
+    output_fd: Option<i32>,
+
+    /// Exit code used if the menu is closed without a selection, i.e. via escape.
+    /// A selection exits with 0 and errors with 2, so scripts can tell them apart.
+    /// Defaults to 1
+    #[clap(long = "no-selection-exit-code")]
+    no_selection_exit_code: Option<i32>,
+
+    /// Jump to the first/last entry when at the end/start and down/up is pressed.
+    /// If disabled the selection stays at the first/last entry.
+    /// Also available as `wrap_selection`.
     /// Defaults to true
-    #[clap(long = "rollover")]
+    #[clap(long = "rollover", alias = "wrap-selection")]
+    #[serde(alias = "wrap_selection")]
     rollover: Option<bool>,
 
     /// For text modes, defines which output is used.
@@ -662,8 +1601,118 @@ pub struct Config {
     /// so the mode isn't too useful anymore.
     /// For math mode, setting this to None will provide no output but keep running
     /// math mode in a loop. Other modes will exit and provide results on selected output.
+    /// `Type` types the text into the focused window.
+    /// Emoji mode uses `action` instead, if it is set.
     #[clap(long = "text-output-mode")]
     text_output_mode: Option<TextOutputMode>,
+
+    /// Action applied to the selection in dmenu, emoji and clipboard mode:
+    /// `type` types it into the focused window, i.e. for emoji or snippets,
+    /// `print` prints it and `copy` copies it.
+    /// Defaults to `print` in dmenu mode, `text_output_mode` in emoji mode
+    /// and `copy` in clipboard mode.
+    #[clap(long = "action")]
+    action: Option<TextOutputMode>,
+
+    /// Pin bookmarks, user directories and mounted volumes on top of the file mode.
+    /// They can also be listed by searching for `@bookmarks`.
+    /// Defaults to true
+    #[clap(long = "file-places")]
+    file_places: Option<bool>,
+
+    /// Show the size and modification time of files and the number of entries
+    /// and size of directories below the entries in file mode. They are computed
+    /// in the background, as this costs IO on large or slow directories.
+    /// The size of directories only includes three levels of subdirectories.
+    /// Defaults to false
+    #[clap(long = "file-details")]
+    file_details: Option<bool>,
+
+    /// Show thumbnails of images, and of pdfs or videos if a thumbnailer is
+    /// installed for them, instead of their mime icon in file mode.
+    /// They are taken from or written to `$XDG_CACHE_HOME/thumbnails` in the background.
+    /// Defaults to false
+    #[clap(long = "file-thumbnails")]
+    file_thumbnails: Option<bool>,
+
+    /// Probe the hosts of the ssh mode by connecting to their ssh port
+    /// and show whether they are reachable as badge. The badge has the css class
+    /// `probing`, `reachable` or `unreachable`. Unreachable hosts are sorted
+    /// below the others, unless the sort order is `MostUsed`.
+    /// Defaults to false
+    #[clap(long = "ssh-probe")]
+    ssh_probe: Option<bool>,
+
+    /// File used by the notes mode to store captured entries.
+    /// Defaults to `$XDG_DATA_HOME/worf/notes.md`
+    #[clap(long = "notes-file")]
+    notes_file: Option<String>,
+
+    /// Calculation history of the math mode, it is loaded on start
+    /// and written by the export key (Alt+S). Files ending with `.csv` are
+    /// written as CSV, all others as markdown table.
+    /// Defaults to `$XDG_DATA_HOME/worf/math.md`
+    #[clap(long = "math-history-file")]
+    math_history_file: Option<String>,
+
+    /// Secrets file of the otp mode, one account per line, either as
+    /// `otpauth://totp/` uri or as label followed by the base32 secret.
+    /// Files ending with `.gpg` are decrypted with gpg.
+    /// Defaults to `$XDG_DATA_HOME/worf/otp`
+    #[clap(long = "otp-file")]
+    otp_file: Option<String>,
+
+    /// Uri or file the open mode shows the applications for,
+    /// i.e. `--open-uri %u` when registering worf as handler in a desktop file.
+    /// Defaults to none.
+    #[clap(long = "open-uri")]
+    open_uri: Option<String>,
+
+    /// Command to run when a timer of the timer mode expires,
+    /// i.e. to play a sound. Defaults to none.
+    #[clap(long = "timer-sound")]
+    timer_sound: Option<String>,
+
+    /// Keep reading stdin in dmenu mode while the menu is shown.
+    /// Plain lines are appended, lines starting with `dmenu_control_prefix`
+    /// can clear the list or remove and update items.
+    /// Defaults to false.
+    #[clap(long = "dmenu-stream")]
+    dmenu_stream: Option<bool>,
+
+    /// Prefix of control lines read from stdin in dmenu streaming mode.
+    /// Defaults to `worf::`
+    #[clap(long = "dmenu-control-prefix")]
+    dmenu_control_prefix: Option<String>,
+
+    /// Print the 0-based line number of the selection in dmenu mode instead of its label.
+    /// In streaming mode the id of the item is printed, `-1` if the search text was submitted.
+    /// Defaults to false.
+    #[clap(long = "dmenu-index", alias = "index")]
+    dmenu_index: Option<bool>,
+
+    /// Items of dmenu mode separated by `dmenu_items_delimiter`, stdin is not read if set.
+    /// Simplifies static menus in compositor key bindings, i.e. `--items "lock,logout"`.
+    /// Defaults to none.
+    #[clap(long = "items", alias = "dmenu-items")]
+    dmenu_items: Option<String>,
+
+    /// Delimiter of the items given via `dmenu_items`. Defaults to `,`
+    #[clap(long = "items-delimiter", alias = "dmenu-items-delimiter")]
+    dmenu_items_delimiter: Option<String>,
+
+    /// Allows marking several entries with Ctrl and the submit key or Ctrl+click,
+    /// all marked entries are returned. Marked entries have the css class `marked`.
+    /// Supported by dmenu mode, which prints one entry per line.
+    /// Defaults to false.
+    #[clap(long = "multi-select")]
+    multi_select: Option<bool>,
+
+    /// Command line arguments merged into this config,
+    /// kept to merge them again when the config is reloaded.
+    #[clap(skip)]
+    #[serde(skip)]
+    args: Option<Box<Config>>,
 }
 
 impl Config {
@@ -672,16 +1721,82 @@ impl Config {
         self.fork.unwrap_or(false)
     }
 
+    /// Loads the config file again and merges it with the
+    /// command line arguments this config was created from.
+    /// # Errors
+    ///
+    /// Same as `load_worf_config`
+    pub fn reload(&self) -> Result<Config, Error> {
+        if let Some(args) = &self.args {
+            load_worf_config(Some(args))
+        } else {
+            load_config(self.cfg_path.as_deref(), "worf", "config")
+        }
+    }
+
+    /// Takes the options of `RELOADABLE_OPTIONS` from the reloaded config,
+    /// all others, including the ones changed at runtime, are kept.
+    pub fn apply_reloadable_options(&mut self, reloaded: &Config) {
+        self.style.clone_from(&reloaded.style);
+        // the prompt defaults to the one of the mode, which is not part of the config file
+        if reloaded.prompt.is_some() {
+            self.prompt.clone_from(&reloaded.prompt);
+        }
+        self.matching = reloaded.matching;
+        self.insensitive = reloaded.insensitive;
+        self.fuzzy_min_score = reloaded.fuzzy_min_score;
+        self.search_terms_weight = reloaded.search_terms_weight;
+    }
+
+    /// Names of the options which differ between both configs.
+    #[must_use]
+    pub fn changed_options(&self, other: &Config) -> Vec<String> {
+        let (Ok(Value::Object(own)), Ok(Value::Object(other))) =
+            (serde_json::to_value(self), serde_json::to_value(other))
+        else {
+            return Vec::new();
+        };
+        let mut changed: Vec<_> = own
+            .keys()
+            .chain(other.keys())
+            .filter(|key| own.get(*key) != other.get(*key))
+            .cloned()
+            .collect();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+
     #[must_use]
     pub fn image_size(&self) -> u16 {
         self.image_size.unwrap_or(32)
     }
 
+    #[must_use]
+    pub fn icon_fallback(&self) -> IconFallback {
+        self.icon_fallback.clone().unwrap_or(if self.is_dock() {
+            IconFallback::Placeholder
+        } else {
+            IconFallback::Collapse
+        })
+    }
+
+    #[must_use]
+    pub fn icon_placeholder(&self) -> String {
+        self.icon_placeholder
+            .clone()
+            .unwrap_or_else(|| "image-missing".to_owned())
+    }
+
     #[must_use]
     pub fn match_method(&self) -> MatchMethod {
         self.matching.unwrap_or(MatchMethod::Contains)
     }
 
+    pub fn set_match_method(&mut self, val: MatchMethod) {
+        self.matching = Some(val);
+    }
+
     #[must_use]
     pub fn single_click(&self) -> bool {
         self.single_click.unwrap_or(false)
@@ -692,6 +1807,16 @@ impl Config {
         self.fuzzy_min_score.unwrap_or(0.0)
     }
 
+    #[must_use]
+    pub fn search_terms_weight(&self) -> f64 {
+        self.search_terms_weight.unwrap_or(0.8)
+    }
+
+    #[must_use]
+    pub fn suggestion_count(&self) -> usize {
+        self.suggestion_count.unwrap_or(0)
+    }
+
     #[must_use]
     pub fn style(&self) -> Option<String> {
         style_path(self.style.as_ref())
@@ -703,14 +1828,45 @@ impl Config {
             })
     }
 
+    #[must_use]
+    pub fn style_preset(&self) -> StylePreset {
+        self.style_preset.unwrap_or(StylePreset::Default)
+    }
+
+    fn is_dock(&self) -> bool {
+        self.style_preset() == StylePreset::Dock
+    }
+
+    #[must_use]
+    pub fn is_bar(&self) -> bool {
+        self.style_preset() == StylePreset::Bar
+    }
+
+    #[must_use]
+    pub fn dynamic_width(&self) -> bool {
+        self.dynamic_width.unwrap_or(self.is_dock())
+    }
+
+    #[must_use]
+    pub fn icon_only(&self) -> bool {
+        self.icon_only.unwrap_or(self.is_dock())
+    }
+
     #[must_use]
     pub fn normal_window(&self) -> bool {
         self.normal_window
     }
 
     #[must_use]
-    pub fn location(&self) -> Option<&Vec<Anchor>> {
-        self.location.as_ref()
+    pub fn app_id(&self) -> String {
+        self.app_id.clone().unwrap_or_else(|| "worf".to_owned())
+    }
+
+    #[must_use]
+    pub fn location(&self) -> Option<Vec<Anchor>> {
+        self.location
+            .clone()
+            .or_else(|| self.is_bar().then(|| vec![Anchor::Top]))
     }
 
     #[must_use]
@@ -718,11 +1874,44 @@ impl Config {
         self.hide_scroll.unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn scrollbar(&self) -> ScrollbarMode {
+        self.scrollbar.clone().unwrap_or(if self.hide_scroll() {
+            ScrollbarMode::Never
+        } else {
+            ScrollbarMode::Overlay
+        })
+    }
+
+    #[must_use]
+    pub fn scroll_selection(&self) -> bool {
+        self.scroll_selection.unwrap_or(false)
+    }
+
     #[must_use]
     pub fn columns(&self) -> u32 {
         self.columns.unwrap_or(1)
     }
 
+    pub fn set_columns(&mut self, val: u32) {
+        self.columns = Some(val);
+    }
+
+    #[must_use]
+    pub fn row_spacing(&self) -> u32 {
+        self.row_spacing.unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn column_spacing(&self) -> u32 {
+        self.column_spacing.unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn item_padding(&self) -> u16 {
+        self.item_padding.unwrap_or(0)
+    }
+
     #[must_use]
     pub fn halign(&self) -> Align {
         self.halign.unwrap_or(Align::Fill)
@@ -742,9 +1931,19 @@ impl Config {
     pub fn valign(&self) -> Align {
         self.valign.unwrap_or(Align::Center)
     }
+    #[must_use]
+    pub fn list_direction(&self) -> ListDirection {
+        self.list_direction.clone().unwrap_or(ListDirection::Down)
+    }
+
     #[must_use]
     pub fn orientation(&self) -> Orientation {
-        self.orientation.unwrap_or(Orientation::Vertical)
+        self.orientation
+            .unwrap_or(if self.is_dock() || self.is_bar() {
+                Orientation::Horizontal
+            } else {
+                Orientation::Vertical
+            })
     }
 
     #[must_use]
@@ -756,6 +1955,41 @@ impl Config {
         self.prompt = Some(val);
     }
 
+    #[must_use]
+    pub fn prompt_icon(&self) -> Option<String> {
+        self.prompt_icon.clone().filter(|icon| !icon.is_empty())
+    }
+
+    pub fn set_prompt_icon(&mut self, val: String) {
+        self.prompt_icon = Some(val);
+    }
+
+    #[must_use]
+    pub fn min_query_length(&self) -> usize {
+        self.min_query_length.unwrap_or(0)
+    }
+
+    pub fn set_min_query_length(&mut self, val: usize) {
+        self.min_query_length = Some(val);
+    }
+
+    /// The options of the given mode, configured values override the built-in ones.
+    #[must_use]
+    pub fn mode(&self, mode: &str) -> ModeConfig {
+        let default = ModeConfig::default_for(mode);
+        let configured = self
+            .modes
+            .as_ref()
+            .and_then(|modes| modes.get(mode))
+            .cloned()
+            .unwrap_or_default();
+        ModeConfig {
+            placeholder: configured.placeholder.or(default.placeholder),
+            icon: configured.icon.or(default.icon),
+            min_query_length: configured.min_query_length.or(default.min_query_length),
+        }
+    }
+
     #[must_use]
     pub fn height(&self) -> String {
         self.height.clone().unwrap_or("40%".to_owned())
@@ -763,7 +1997,13 @@ impl Config {
 
     #[must_use]
     pub fn width(&self) -> String {
-        self.width.clone().unwrap_or("50%".to_owned())
+        self.width.clone().unwrap_or_else(|| {
+            if self.is_bar() {
+                "100%".to_owned()
+            } else {
+                "50%".to_owned()
+            }
+        })
     }
 
     #[must_use]
@@ -776,6 +2016,10 @@ impl Config {
         self.allow_images.unwrap_or(true)
     }
 
+    pub fn set_allow_images(&mut self, val: bool) {
+        self.allow_images = Some(val);
+    }
+
     #[must_use]
     pub fn line_wrap(&self) -> WrapMode {
         self.line_wrap.clone().unwrap_or(WrapMode::None)
@@ -791,6 +2035,13 @@ impl Config {
         self.line_max_width_chars
     }
 
+    #[must_use]
+    pub fn expander_position(&self) -> ExpanderPosition {
+        self.expander_position
+            .clone()
+            .unwrap_or(ExpanderPosition::Start)
+    }
+
     #[must_use]
     pub fn term(&self) -> Option<String> {
         self.term.clone().or_else(|| {
@@ -822,32 +2073,178 @@ impl Config {
 
     #[must_use]
     pub fn hide_search(&self) -> bool {
-        self.hide_search.unwrap_or(false)
+        self.hide_search.unwrap_or(self.is_dock())
+    }
+
+    pub fn set_hide_search(&mut self, val: bool) {
+        self.hide_search = Some(val);
+    }
+
+    #[must_use]
+    pub fn hidden_search_input(&self) -> HiddenSearchInput {
+        self.hidden_search_input
+            .clone()
+            .unwrap_or(HiddenSearchInput::Filter)
+    }
+
+    #[must_use]
+    pub fn hidden_search_overlay(&self) -> bool {
+        self.hidden_search_overlay.unwrap_or(self.is_dock())
+    }
+
+    #[must_use]
+    pub fn search_icon(&self) -> bool {
+        self.search_icon.unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn search_clear_button(&self) -> bool {
+        self.search_clear_button.unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn key_hide_search(&self) -> Option<KeyBindingSpec> {
+        self.key_hide_search.clone()
+    }
+
+    #[must_use]
+    pub fn initial_focus(&self) -> Focus {
+        self.initial_focus.unwrap_or(Focus::Search)
+    }
+
+    #[must_use]
+    pub fn key_toggle_focus(&self) -> Option<KeyBindingSpec> {
+        self.key_toggle_focus.clone()
+    }
+
+    #[must_use]
+    pub fn details(&self) -> bool {
+        self.details.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn details_position(&self) -> DetailsPosition {
+        self.details_position.unwrap_or(DetailsPosition::Right)
+    }
+
+    #[must_use]
+    pub fn preview(&self) -> bool {
+        self.preview.unwrap_or(false)
     }
 
     #[must_use]
-    pub fn key_hide_search(&self) -> Option<Key> {
-        self.key_hide_search
+    pub fn key_toggle_details(&self) -> Option<KeyBindingSpec> {
+        self.key_toggle_details.clone()
     }
 
     #[must_use]
-    pub fn key_submit(&self) -> Key {
-        self.key_submit.unwrap_or(Key::Enter)
+    pub fn key_up(&self) -> Option<KeyBindingSpec> {
+        self.key_up.clone()
     }
 
     #[must_use]
-    pub fn key_exit(&self) -> Key {
-        self.key_exit.unwrap_or(Key::Escape)
+    pub fn key_down(&self) -> Option<KeyBindingSpec> {
+        self.key_down.clone()
     }
 
     #[must_use]
-    pub fn key_copy(&self) -> Option<Key> {
-        self.key_copy
+    pub fn key_pgup(&self) -> Option<KeyBindingSpec> {
+        self.key_pgup.clone()
     }
 
     #[must_use]
-    pub fn key_expand(&self) -> Key {
-        self.key_expand.unwrap_or(Key::Tab)
+    pub fn key_pgdn(&self) -> Option<KeyBindingSpec> {
+        self.key_pgdn.clone()
+    }
+
+    #[must_use]
+    pub fn key_home(&self) -> Option<KeyBindingSpec> {
+        self.key_home.clone()
+    }
+
+    #[must_use]
+    pub fn key_end(&self) -> Option<KeyBindingSpec> {
+        self.key_end.clone()
+    }
+
+    #[must_use]
+    pub fn key_submit(&self) -> KeyBindingSpec {
+        self.key_submit.clone().unwrap_or(Key::Enter.into())
+    }
+
+    #[must_use]
+    pub fn key_exit(&self) -> KeyBindingSpec {
+        self.key_exit.clone().unwrap_or(Key::Escape.into())
+    }
+
+    #[must_use]
+    pub fn key_copy(&self) -> Option<KeyBindingSpec> {
+        self.key_copy.clone()
+    }
+
+    #[must_use]
+    pub fn key_expand(&self) -> KeyBindingSpec {
+        self.key_expand.clone().unwrap_or(Key::Tab.into())
+    }
+
+    #[must_use]
+    pub fn mouse_copy(&self) -> Option<KeyBindingSpec> {
+        self.mouse_copy.clone().filter(|spec| spec.key != Key::None)
+    }
+
+    #[must_use]
+    pub fn mouse_expand(&self) -> Option<KeyBindingSpec> {
+        self.mouse_expand
+            .clone()
+            .filter(|spec| spec.key != Key::None)
+    }
+
+    #[must_use]
+    pub fn key_switch_mode(&self) -> Option<KeyBindingSpec> {
+        self.key_switch_mode.clone()
+    }
+
+    #[must_use]
+    pub fn key_hints(&self) -> bool {
+        self.key_hints.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn clipboard_backend(&self) -> ClipboardBackend {
+        self.clipboard_backend
+            .clone()
+            .unwrap_or(ClipboardBackend::Cliphist)
+    }
+
+    #[must_use]
+    pub fn custom_keys(&self) -> Vec<DmenuCustomKey> {
+        self.custom_key.clone().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn info_commands(&self) -> Vec<InfoCommand> {
+        self.info_commands.clone().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn auto_suggestions(&self) -> bool {
+        self.auto_suggestions.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn auto_suggestion_weights(&self) -> AutoSuggestionWeights {
+        self.auto_suggestion_weights.clone().unwrap_or_default()
+    }
+
+    pub fn set_key_switch_mode(&mut self, val: Option<KeyBindingSpec>) {
+        self.key_switch_mode = val;
+    }
+
+    #[must_use]
+    pub fn switch_modes(&self) -> Vec<String> {
+        self.switch_modes
+            .clone()
+            .unwrap_or_else(|| ["drun", "window", "emoji"].map(ToOwned::to_owned).to_vec())
     }
 
     #[must_use]
@@ -855,6 +2252,10 @@ impl Config {
         self.search.clone()
     }
 
+    pub fn set_search(&mut self, val: String) {
+        self.search = Some(val);
+    }
+
     #[must_use]
     pub fn allow_markup(&self) -> bool {
         self.allow_markup.unwrap_or(false)
@@ -870,16 +2271,80 @@ impl Config {
         self.password.clone()
     }
 
+    #[must_use]
+    pub fn secure_input(&self) -> bool {
+        self.secure_input.unwrap_or(false)
+    }
+
     #[must_use]
     pub fn no_actions(&self) -> bool {
         self.no_actions.unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn drun_show_origin(&self) -> bool {
+        self.drun_show_origin.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn drun_hide_flatpak(&self) -> bool {
+        self.drun_hide_flatpak.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn drun_hide_snap(&self) -> bool {
+        self.drun_hide_snap.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn drun_hide_32bit(&self) -> bool {
+        self.drun_hide_32bit.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn drun_include_hidden(&self) -> bool {
+        self.drun_include_hidden.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn desktop_env_filter(&self) -> bool {
+        self.desktop_env_filter.unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn run_all_instances(&self) -> bool {
+        self.run_all_instances.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn key_pin(&self) -> Key {
+        self.key_pin.unwrap_or(Key::P)
+    }
+
+    #[must_use]
+    pub fn key_hide_entry(&self) -> Key {
+        self.key_hide_entry.unwrap_or(Key::H)
+    }
+
     #[must_use]
     pub fn sort_order(&self) -> SortOrder {
         self.sort_order.clone().unwrap_or(SortOrder::Alphabetical)
     }
 
+    pub fn set_sort_order(&mut self, val: SortOrder) {
+        self.sort_order = Some(val);
+    }
+
+    #[must_use]
+    pub fn history_half_life(&self) -> f64 {
+        self.history_half_life.unwrap_or(14.0)
+    }
+
+    #[must_use]
+    pub fn history_max_entries(&self) -> usize {
+        self.history_max_entries.unwrap_or(500)
+    }
+
     #[must_use]
     pub fn emoji_hide_label(&self) -> bool {
         self.emoji_hide_label.unwrap_or(false)
@@ -912,11 +2377,36 @@ impl Config {
         self.version
     }
 
+    #[must_use]
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    #[must_use]
+    pub fn check_config(&self) -> bool {
+        self.check_config
+    }
+
+    #[must_use]
+    pub fn daemon(&self) -> bool {
+        self.daemon
+    }
+
+    #[must_use]
+    pub fn daemon_shortcuts(&self) -> HashMap<String, String> {
+        self.daemon_shortcuts.clone().unwrap_or_default()
+    }
+
     #[must_use]
     pub fn layer(&self) -> Layer {
         self.layer.clone().unwrap_or(Layer::Top)
     }
 
+    #[must_use]
+    pub fn pointer_warp(&self) -> PointerWarp {
+        self.pointer_warp.unwrap_or(PointerWarp::Keep)
+    }
+
     #[must_use]
     pub fn dynamic_lines(&self) -> bool {
         self.dynamic_lines.unwrap_or(false)
@@ -927,6 +2417,11 @@ impl Config {
         self.dynamic_lines_limit.unwrap_or(true)
     }
 
+    #[must_use]
+    pub fn search_open_urls(&self) -> bool {
+        self.search_open_urls.unwrap_or(true)
+    }
+
     #[must_use]
     pub fn search_query(&self) -> String {
         self.search_query
@@ -944,6 +2439,15 @@ impl Config {
         self.blurred_background_fullscreen.unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn blurred_background_margin(&self) -> Option<i32> {
+        if self.normal_window() {
+            None
+        } else {
+            self.blurred_background_margin
+        }
+    }
+
     #[must_use]
     pub fn submit_with_expand(&self) -> bool {
         self.submit_with_expand.unwrap_or(true)
@@ -954,6 +2458,61 @@ impl Config {
         self.auto_select_on_search.unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn auto_select_delay(&self) -> u64 {
+        self.auto_select_delay.unwrap_or(200)
+    }
+
+    #[must_use]
+    pub fn activation_feedback_duration(&self) -> u64 {
+        self.activation_feedback_duration.unwrap_or(150)
+    }
+
+    #[must_use]
+    pub fn launch_failure_timeout(&self) -> u64 {
+        self.launch_failure_timeout.unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn launch_log(&self) -> bool {
+        self.launch_log.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn launch_log_count(&self) -> usize {
+        self.launch_log_count.unwrap_or(20)
+    }
+
+    #[must_use]
+    pub fn launch_method(&self) -> LaunchMethod {
+        self.launch_method.unwrap_or(LaunchMethod::Fork)
+    }
+
+    #[must_use]
+    pub fn dry_run(&self) -> bool {
+        self.dry_run.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn print_window_info(&self) -> bool {
+        self.print_window_info.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn debug_style(&self) -> bool {
+        self.debug_style.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn output_fd(&self) -> Option<i32> {
+        self.output_fd
+    }
+
+    #[must_use]
+    pub fn no_selection_exit_code(&self) -> i32 {
+        self.no_selection_exit_code.unwrap_or(1)
+    }
+
     #[must_use]
     pub fn rollover(&self) -> bool {
         self.rollover.unwrap_or(true)
@@ -965,6 +2524,120 @@ impl Config {
             .clone()
             .unwrap_or(TextOutputMode::Clipboard)
     }
+
+    /// The configured action of dmenu, emoji and clipboard mode, `default` if none is set.
+    #[must_use]
+    pub fn action_or(&self, default: TextOutputMode) -> TextOutputMode {
+        self.action.clone().unwrap_or(default)
+    }
+
+    #[must_use]
+    pub fn file_places(&self) -> bool {
+        self.file_places.unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn file_details(&self) -> bool {
+        self.file_details.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn file_thumbnails(&self) -> bool {
+        self.file_thumbnails.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn open_uri(&self) -> Option<String> {
+        self.open_uri.clone()
+    }
+
+    #[must_use]
+    pub fn ssh_probe(&self) -> bool {
+        self.ssh_probe.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn notes_file(&self) -> PathBuf {
+        self.notes_file.as_deref().map_or_else(
+            || {
+                dirs::data_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("worf")
+                    .join("notes.md")
+            },
+            expand_path,
+        )
+    }
+
+    #[must_use]
+    pub fn math_history_file(&self) -> PathBuf {
+        self.math_history_file.as_deref().map_or_else(
+            || {
+                dirs::data_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("worf")
+                    .join("math.md")
+            },
+            expand_path,
+        )
+    }
+
+    #[must_use]
+    pub fn otp_file(&self) -> PathBuf {
+        self.otp_file.as_deref().map_or_else(
+            || {
+                dirs::data_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("worf")
+                    .join("otp")
+            },
+            expand_path,
+        )
+    }
+
+    #[must_use]
+    pub fn timer_sound(&self) -> Option<String> {
+        self.timer_sound.clone()
+    }
+
+    #[must_use]
+    pub fn dmenu_stream(&self) -> bool {
+        self.dmenu_stream.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn dmenu_control_prefix(&self) -> String {
+        self.dmenu_control_prefix
+            .clone()
+            .unwrap_or_else(|| "worf::".to_owned())
+    }
+
+    #[must_use]
+    pub fn dmenu_index(&self) -> bool {
+        self.dmenu_index.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn dmenu_items(&self) -> Option<String> {
+        self.dmenu_items.clone()
+    }
+
+    #[must_use]
+    pub fn dmenu_items_delimiter(&self) -> String {
+        self.dmenu_items_delimiter
+            .clone()
+            .filter(|delimiter| !delimiter.is_empty())
+            .unwrap_or_else(|| ",".to_owned())
+    }
+
+    #[must_use]
+    pub fn multi_select(&self) -> bool {
+        self.multi_select.unwrap_or(false)
+    }
+
+    pub fn set_multi_select(&mut self, val: bool) {
+        self.multi_select = Some(val);
+    }
 }
 
 fn default_false() -> bool {
@@ -1042,8 +2715,9 @@ pub fn load_worf_config(args_opt: Option<&Config>) -> Result<Config, Error> {
         "config",
     )?;
     if let Some(args) = args_opt {
-        let merge_result = merge_config_with_args(&mut config, args)
+        let mut merge_result = merge_config_with_args(&mut config, args)
             .map_err(|e| Error::ParsingError(format!("{e}")))?;
+        merge_result.args = Some(Box::new(args.clone()));
         Ok(merge_result)
     } else {
         Ok(config)
@@ -1075,6 +2749,33 @@ pub fn load_config<T: DeserializeOwned>(
     }
 }
 
+/// A half-life of 0 or less would divide by 0 or let the score grow with the age.
+fn check_half_life(days: f64) -> Result<f64, String> {
+    if days > 0.0 && days.is_finite() {
+        Ok(days)
+    } else {
+        Err(format!(
+            "history_half_life must be a positive number of days, not {days}"
+        ))
+    }
+}
+
+fn parse_half_life(s: &str) -> Result<f64, String> {
+    s.parse::<f64>()
+        .map_err(|e| format!("{s} is not a valid history_half_life: {e}"))
+        .and_then(check_half_life)
+}
+
+fn deserialize_half_life<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<f64>::deserialize(deserializer)?
+        .map(check_half_life)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
 #[must_use]
 pub fn expand_path(input: &str) -> PathBuf {
     let mut path = input.to_string();
@@ -1139,4 +2840,88 @@ mod tests {
         let config: Config = toml::from_str(toml_str).expect("Failed to parse TOML");
         assert_eq!(config.key_detection_type(), KeyDetectionType::Code);
     }
+
+    #[test]
+    fn test_history_half_life_must_be_positive() {
+        assert!(toml::from_str::<Config>("history_half_life = 0.0").is_err());
+        assert!(toml::from_str::<Config>("history_half_life = -1.0").is_err());
+        let config: Config =
+            toml::from_str("history_half_life = 7.0").expect("Failed to parse TOML");
+        assert!((config.history_half_life() - 7.0).abs() < f64::EPSILON);
+        assert!(parse_half_life("0").is_err());
+    }
+
+    #[test]
+    fn test_key_binding_spec_survives_merge() {
+        let toml_str = r#"
+        key_down = "ctrl+j"
+        key_pgup = "Alt+Shift+1"
+    "#;
+
+        let mut config: Config = toml::from_str(toml_str).expect("Failed to parse TOML");
+        let merged = merge_config_with_args(&mut config, &Config::default()).unwrap();
+        let key_down = merged.key_down().unwrap();
+        assert_eq!(key_down.key, Key::J);
+        assert_eq!(key_down.modifiers, HashSet::from([Modifier::Control]));
+        assert_eq!(key_down.to_string(), "Ctrl+J");
+        assert_eq!(merged.key_pgup().unwrap().to_string(), "Alt+Shift+Num1");
+        assert_eq!(
+            "PageUp".parse::<KeyBindingSpec>().unwrap().modifiers,
+            HashSet::from([Modifier::None])
+        );
+    }
+
+    #[test]
+    fn test_key_binding_spec_separators() {
+        let spec = |s: &str| s.parse::<KeyBindingSpec>().unwrap().to_string();
+        assert_eq!(spec("Ctrl-Enter"), "Ctrl+Enter");
+        assert_eq!(spec("shift-Tab"), "Shift+Tab");
+        assert_eq!(spec("Ctrl--"), "Ctrl+Minus");
+        assert_eq!(spec("Alt+-"), "Alt+Minus");
+        assert_eq!(spec("-"), "Minus");
+        assert_eq!(spec("+"), "Plus");
+        assert!("Hyper-x".parse::<KeyBindingSpec>().is_err());
+
+        let toml_str = r#"
+        key_exit = "Alt-q"
+    "#;
+        let config: Config = toml::from_str(toml_str).expect("Failed to parse TOML");
+        let key_exit = config.key_exit();
+        assert!(key_exit.matches_modifiers(&HashSet::from([Modifier::Alt])));
+        assert!(!key_exit.matches_modifiers(&HashSet::from([Modifier::None])));
+        assert!(
+            config
+                .key_submit()
+                .matches_modifiers(&HashSet::from([Modifier::Control]))
+        );
+    }
+
+    #[test]
+    fn test_mode_overrides_defaults() {
+        let toml_str = r#"
+        [modes.math]
+        placeholder = "="
+
+        [modes.emoji]
+        icon = "face-smile"
+        min_query_length = 2
+    "#;
+
+        let config: Config = toml::from_str(toml_str).expect("Failed to parse TOML");
+        let math = config.mode("math");
+        assert_eq!(math.placeholder.as_deref(), Some("="));
+        assert_eq!(
+            math.icon.as_deref(),
+            Some("accessories-calculator-symbolic")
+        );
+        assert_eq!(
+            config.mode("emoji"),
+            ModeConfig {
+                placeholder: None,
+                icon: Some("face-smile".to_owned()),
+                min_query_length: Some(2),
+            }
+        );
+        assert_eq!(config.mode("ssh"), ModeConfig::default());
+    }
 }
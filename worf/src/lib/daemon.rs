@@ -0,0 +1,146 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, desktop};
+
+/// A menu requested by a worf call, shown by the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    /// Command line arguments of the client
+    pub args: Vec<String>,
+    /// Environment of the client, used for the launched applications
+    pub env: Vec<(String, String)>,
+    /// Working directory of the client
+    pub cwd: Option<PathBuf>,
+}
+
+impl Request {
+    /// The request for the arguments with the environment and working dir of this process.
+    #[must_use]
+    pub fn new(args: &[String]) -> Self {
+        Self {
+            args: args.to_vec(),
+            env: env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+            cwd: env::current_dir().ok(),
+        }
+    }
+
+    /// Applications launched by the menu get the environment and working directory
+    /// of the client, the ones of the daemon are kept.
+    pub fn apply_environment(&self) {
+        desktop::set_launch_environment(self.env.clone(), self.cwd.clone());
+    }
+}
+
+/// Socket the daemon listens on.
+fn socket_path() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("worf.sock"))
+}
+
+/// Serves the requests sent by `forward` one after another until the process is stopped.
+/// Every request holds the command line arguments and environment of the client,
+/// `handler` shows the menu for them and returns the exit code the client exits with.
+/// # Errors
+/// * `Error::MissingFile` if there is no runtime or cache dir
+/// * `Error::Io` if another daemon is running or the socket cannot be created
+pub fn serve(mut handler: impl FnMut(Request) -> i32) -> Result<(), Error> {
+    let path = socket_path().ok_or(Error::MissingFile)?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(Error::Io(format!(
+            "a daemon is already listening on {}",
+            path.display()
+        )));
+    }
+    // left behind by a daemon that did not shut down cleanly
+    let _ = fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).map_err(|e| Error::Io(e.to_string()))?;
+    log::info!("daemon listening on {}", path.display());
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = serve_request(&stream, &mut handler) {
+                    log::error!("failed to serve request: {e}");
+                }
+            }
+            Err(e) => log::error!("failed to accept connection: {e}"),
+        }
+    }
+    Ok(())
+}
+
+fn serve_request(
+    stream: &UnixStream,
+    handler: &mut impl FnMut(Request) -> i32,
+) -> Result<(), Error> {
+    let mut request = String::new();
+    BufReader::new(stream)
+        .read_line(&mut request)
+        .map_err(|e| Error::Io(e.to_string()))?;
+    let request: Request =
+        serde_json::from_str(&request).map_err(|e| Error::ParsingError(e.to_string()))?;
+
+    let code = handler(request);
+    let mut stream = stream;
+    writeln!(stream, "{code}").map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Lets a running daemon show the menu for the given command line arguments,
+/// along with the environment and working dir of this process.
+/// Returns the exit code of the request or `None` if no daemon is running.
+#[must_use]
+pub fn forward(args: &[String]) -> Option<i32> {
+    let mut stream = UnixStream::connect(socket_path()?).ok()?;
+    let request = serde_json::to_string(&Request::new(args)).ok()?;
+    writeln!(stream, "{request}").ok()?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).ok()?;
+    reply.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_request_replies_exit_code() {
+        let (server, mut client) = UnixStream::pair().unwrap();
+        writeln!(
+            client,
+            r#"{{"args":["worf","--show","drun"],"env":[["LANG","de_DE.UTF-8"]],"cwd":"/tmp"}}"#
+        )
+        .unwrap();
+
+        let mut received = None;
+        serve_request(&server, &mut |request| {
+            received = Some(request);
+            3
+        })
+        .unwrap();
+
+        let mut reply = String::new();
+        BufReader::new(&client).read_line(&mut reply).unwrap();
+        assert_eq!(reply, "3\n");
+        assert_eq!(
+            received,
+            Some(Request {
+                args: vec!["worf".to_owned(), "--show".to_owned(), "drun".to_owned()],
+                env: vec![("LANG".to_owned(), "de_DE.UTF-8".to_owned())],
+                cwd: Some(PathBuf::from("/tmp")),
+            })
+        );
+    }
+}
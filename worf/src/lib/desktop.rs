@@ -1,15 +1,22 @@
 use std::{
     collections::HashMap,
     env,
-    ffi::OsStr,
+    ffi::{CString, OsStr, OsString},
     fs,
     hash::BuildHasher,
-    io,
-    os::unix::{fs::PermissionsExt, prelude::CommandExt},
+    io::{self, Read, Write},
+    os::{
+        fd::BorrowedFd,
+        unix::{ffi::OsStrExt, fs::PermissionsExt, net::UnixStream, prelude::CommandExt},
+    },
     path::{Path, PathBuf},
-    process::{Command, Stdio},
-    sync::LazyLock,
-    time::Instant,
+    process::{Child, Command, Stdio},
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use dashmap::DashMap;
@@ -18,13 +25,33 @@ pub use freedesktop_file_parser::{DesktopFile, EntryType};
 use notify_rust::Notification;
 use rayon::prelude::*;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use wl_clipboard_rs::copy::{ClipboardType, MimeType, ServeRequests, Source};
 
 use crate::{
     Error,
-    config::{Config, expand_path},
+    config::{Config, LaunchMethod, TextOutputMode, expand_path},
 };
 
+/// Desktop files read by `find_desktop_files_with_path` with the modification times
+/// of the application directories, if they are kept in memory.
+type DesktopFileCache = (Vec<Option<SystemTime>>, Vec<(PathBuf, DesktopFile)>);
+
+static KEEP_DESKTOP_FILES: AtomicBool = AtomicBool::new(false);
+
+/// Environment and working dir applications are launched with, see `set_launch_environment`.
+type LaunchEnvironment = (Vec<(String, String)>, Option<PathBuf>);
+
+static LAUNCH_ENVIRONMENT: Mutex<Option<LaunchEnvironment>> = Mutex::new(None);
+static DESKTOP_FILE_CACHE: Mutex<Option<DesktopFileCache>> = Mutex::new(None);
+
+/// Keeps the desktop files in memory for long-running processes, i.e. the daemon.
+/// They are read again when an application directory changed,
+/// which happens when desktop files are added or removed.
+pub fn keep_desktop_files_in_memory() {
+    KEEP_DESKTOP_FILES.store(true, Ordering::Relaxed);
+}
+
 /// Returns a regex with supported image extensions
 /// # Panics
 ///
@@ -60,15 +87,50 @@ fn find_files(folder: &Path, file_name: &Regex) -> Option<Vec<PathBuf>> {
 /// * /usr/share/applications
 /// * /usr/local/share/applications
 /// * /var/lib/flatpak/exports/share/applications
+/// * /var/lib/snapd/desktop/applications
 /// # Panics
 ///
 /// When it cannot parse the internal regex
 #[must_use]
 pub fn find_desktop_files() -> Vec<DesktopFile> {
-    static DESKTOP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i).*\.desktop$").unwrap());
+    find_desktop_files_with_path()
+        .into_iter()
+        .map(|(_, file)| file)
+        .collect()
+}
+
+/// Like `find_desktop_files` but also returns the path each desktop file was loaded from,
+/// i.e. to tell flatpak or snap applications apart from native ones.
+/// # Panics
+///
+/// When it cannot parse the internal regex
+#[must_use]
+pub fn find_desktop_files_with_path() -> Vec<(PathBuf, DesktopFile)> {
+    let paths = application_dirs();
+    if !KEEP_DESKTOP_FILES.load(Ordering::Relaxed) {
+        return read_desktop_files(paths);
+    }
+
+    let modified: Vec<_> = paths
+        .iter()
+        .map(|(_, dir)| fs::metadata(dir).and_then(|meta| meta.modified()).ok())
+        .collect();
+    let mut cache = DESKTOP_FILE_CACHE.lock().unwrap();
+    if let Some((cached_modified, files)) = cache.as_ref()
+        && *cached_modified == modified
+    {
+        log::debug!("using {} desktop files kept in memory", files.len());
+        return files.clone();
+    }
 
+    let files = read_desktop_files(paths);
+    *cache = Some((modified, files.clone()));
+    files
+}
+
+/// Directories containing desktop files with their priority, lower values win.
+fn application_dirs() -> Vec<(usize, PathBuf)> {
     let mut paths = Vec::<(usize, PathBuf)>::new();
-    let start = Instant::now();
 
     // get files per prio, highest prio in homedir
     let mut prio = 0;
@@ -98,8 +160,15 @@ pub fn find_desktop_files() -> Vec<DesktopFile> {
         prio,
         PathBuf::from("/var/lib/flatpak/exports/share/applications"),
     ));
+    paths.push((prio, PathBuf::from("/var/lib/snapd/desktop/applications")));
     paths.push((prio, PathBuf::from("/usr/share/applications")));
+    paths
+}
+
+fn read_desktop_files(paths: Vec<(usize, PathBuf)>) -> Vec<(PathBuf, DesktopFile)> {
+    static DESKTOP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i).*\.desktop$").unwrap());
 
+    let start = Instant::now();
     let files: Vec<(usize, PathBuf)> = paths
         .into_par_iter()
         .filter(|(_, dir)| dir.exists())
@@ -137,10 +206,18 @@ pub fn find_desktop_files() -> Vec<DesktopFile> {
             fs::read_to_string(&desktop_file)
                 .ok()
                 .and_then(|content| freedesktop_file_parser::parse(&content).ok())
+                .map(|parsed| (desktop_file, parsed))
         })
-        .for_each(|parsed| {
-            let name = parsed.entry.name.default.clone();
-            p.insert(name, parsed);
+        .for_each(|(path, parsed)| {
+            // hidden entries, i.e. user overrides, must not replace a listed one of the same name
+            let key = if parsed.entry.hidden.unwrap_or(false)
+                || parsed.entry.no_display.unwrap_or(false)
+            {
+                path.to_string_lossy().into_owned()
+            } else {
+                parsed.entry.name.default.clone()
+            };
+            p.insert(key, (path, parsed));
         });
 
     // Convert to Vec if needed, or just iterate over p directly
@@ -202,12 +279,208 @@ pub fn fork_if_configured(config: &Config) {
 }
 
 /// Spawn a new process and forks it away from the current worf process
+/// The given environment variables are set in addition to the ones of worf.
 /// # Errors
 /// * No action in menu item
 /// * Cannot run command (i.e. not found)
+/// * Working directory does not exist
 /// # Panics
 /// When internal regex unwrapping fails. Should not happen as the regex is static
-pub fn spawn_fork(cmd: &str, working_dir: Option<&String>) -> Result<(), Error> {
+pub fn spawn_fork(
+    cmd: &str,
+    working_dir: Option<&String>,
+    env: &[(String, String)],
+) -> Result<(), Error> {
+    start_forked_cmd(build_command(cmd, working_dir, env)?)
+}
+
+/// Spawn a new process like `spawn_fork`, applying the launch settings of the config.
+/// If `launch_failure_timeout` is set, the process is watched for that time
+/// and a notification is shown if it cannot be started or exits with an error.
+/// If `launch_log` is set, the output of the process is written to a log file,
+/// see `launch_log_dir`.
+/// If `dry_run` is set, the resolved command is printed to stdout instead.
+/// # Errors
+/// * No action in menu item
+/// * Cannot run command (i.e. not found)
+/// * Working directory does not exist
+/// * Command exited with an error within `launch_failure_timeout`
+pub fn launch(
+    cmd: &str,
+    working_dir: Option<&String>,
+    env: &[(String, String)],
+    config: &Config,
+) -> Result<(), Error> {
+    launch_app(cmd, working_dir, env, None, config)
+}
+
+/// Like `launch`, the desktop file of the command is used by `LaunchMethod::Gio`.
+/// # Errors
+/// Same as `launch`
+pub fn launch_app(
+    cmd: &str,
+    working_dir: Option<&String>,
+    env: &[(String, String)],
+    desktop_file: Option<&Path>,
+    config: &Config,
+) -> Result<(), Error> {
+    let method = config.launch_method();
+    let build = || {
+        build_command(cmd, working_dir, env)
+            .map(|command| apply_launch_method(command, method, desktop_file))
+    };
+    if config.dry_run() {
+        println!("{}", describe_command(&build()?));
+        return Ok(());
+    }
+
+    let timeout = Duration::from_millis(config.launch_failure_timeout());
+    let result = build()
+        .and_then(|command| {
+            let log = if config.launch_log() {
+                create_launch_log(&command, config.launch_log_count())
+                    .inspect_err(|e| log::warn!("cannot create launch log {e}"))
+                    .ok()
+            } else {
+                None
+            };
+            spawn_detached(command, log)
+        })
+        .and_then(|mut child| {
+            let result = if timeout.is_zero() {
+                Ok(())
+            } else {
+                watch_launched_process(&mut child, timeout)
+            };
+            reap(child);
+            result
+        });
+
+    if !timeout.is_zero()
+        && let Err(e) = &result
+        && let Err(notify_err) = Notification::new()
+            .summary("Failed to launch")
+            .body(&format!("{cmd}\n{e}"))
+            .icon("dialog-error")
+            .show()
+    {
+        log::error!("failed to show launch failure notification {notify_err}");
+    }
+    result
+}
+
+/// Wraps the command so it is started by the given launch method.
+/// `LaunchMethod::Gio` needs the desktop file, commands without one are returned as is.
+#[must_use]
+pub fn apply_launch_method(
+    cmd: Command,
+    method: LaunchMethod,
+    desktop_file: Option<&Path>,
+) -> Command {
+    let mut wrapped = match (method, desktop_file) {
+        (LaunchMethod::Systemd, _) => {
+            let mut wrapped = Command::new("systemd-run");
+            wrapped
+                .args([
+                    "--user",
+                    "--scope",
+                    "--quiet",
+                    "--collect",
+                    "--slice=app.slice",
+                ])
+                .arg(format!("--unit={}", scope_unit_name(cmd.get_program())))
+                .arg("--")
+                .arg(cmd.get_program())
+                .args(cmd.get_args());
+            wrapped
+        }
+        (LaunchMethod::Gio, Some(desktop_file)) => {
+            let mut wrapped = Command::new("gio");
+            wrapped.arg("launch").arg(desktop_file);
+            wrapped
+        }
+        _ => return cmd,
+    };
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => wrapped.env(key, value),
+            None => wrapped.env_remove(key),
+        };
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+    wrapped
+}
+
+/// Unique scope name as recommended by systemd for applications, `app-worf-<app>-<id>.scope`.
+fn scope_unit_name(program: &OsStr) -> String {
+    let app: String = Path::new(program)
+        .file_name()
+        .unwrap_or(program)
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    format!("app-worf-{app}-{id}.scope")
+}
+
+/// Formats the command as it would be typed in a shell, i.e.
+/// `cd /home/user && FOO=bar program arg1 'arg 2'`
+#[must_use]
+pub fn describe_command(cmd: &Command) -> String {
+    fn quote(value: &OsStr) -> String {
+        let value = value.to_string_lossy();
+        if value.is_empty() || value.contains(|c: char| c.is_whitespace() || "'\"\\$`".contains(c))
+        {
+            format!("'{}'", value.replace('\'', "'\\''"))
+        } else {
+            value.into_owned()
+        }
+    }
+
+    let mut parts = Vec::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        parts.push(format!("cd {} &&", quote(dir.as_os_str())));
+    }
+    for (key, value) in cmd.get_envs().filter_map(|(k, v)| Some((k, v?))) {
+        parts.push(format!("{}={}", key.to_string_lossy(), quote(value)));
+    }
+    parts.push(quote(cmd.get_program()));
+    parts.extend(cmd.get_args().map(quote));
+    parts.join(" ")
+}
+
+/// Waits up to `timeout` for the process to fail.
+fn watch_launched_process(child: &mut Child, timeout: Duration) -> Result<(), Error> {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        match child.try_wait() {
+            Ok(Some(status)) if !status.success() => {
+                return Err(Error::RunFailed(format!("exited with {status}")));
+            }
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(Error::Io(e.to_string())),
+        }
+    }
+    Ok(())
+}
+
+fn build_command(
+    cmd: &str,
+    working_dir: Option<&String>,
+    env: &[(String, String)],
+) -> Result<Command, Error> {
     static RE: LazyLock<Regex> =
         LazyLock::new(|| Regex::new(r#"'([^']*)'|"([^"]*)"|(\S+)"#).unwrap());
     let re = &*RE;
@@ -227,36 +500,86 @@ pub fn spawn_fork(cmd: &str, working_dir: Option<&String>) -> Result<(), Error>
         return Err(Error::MissingAction);
     }
 
-    if let Some(dir) = working_dir {
-        env::set_current_dir(dir)
-            .map_err(|e| Error::RunFailed(format!("cannot set workdir {e}")))?;
-    }
-
     let exec = parts[0].replace('"', "");
-    let args: Vec<_> = parts
+    let args = parts
         .iter()
         .skip(1)
         .filter(|arg| !arg.starts_with('%'))
-        .map(|arg| expand_path(arg))
-        .collect();
+        .map(|arg| expand_path(arg));
 
-    start_forked(&exec, args)
-}
-
-fn start_forked<I, S>(exec: &str, args: I) -> Result<(), Error>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-{
     let mut cmd = Command::new(exec);
     cmd.args(args);
-    start_forked_cmd(cmd)
+    cmd.envs(env.iter().map(|(key, value)| (key, value)));
+    if let Some(dir) = working_dir {
+        let dir = expand_path(dir);
+        if !dir.is_dir() {
+            return Err(Error::RunFailed(format!(
+                "cannot set workdir {}, not a directory",
+                dir.display()
+            )));
+        }
+        cmd.current_dir(dir);
+    }
+    Ok(cmd)
+}
+
+fn start_forked_cmd(cmd: Command) -> Result<(), Error> {
+    reap(spawn_detached(cmd, None)?);
+    Ok(())
+}
+
+/// Launches applications with the given environment and working dir instead of the ones
+/// of this process, i.e. the ones of the client the daemon shows the menu for.
+/// The environment of the process itself is not changed, as other threads may read it.
+pub fn set_launch_environment(env: Vec<(String, String)>, cwd: Option<PathBuf>) {
+    *LAUNCH_ENVIRONMENT.lock().unwrap() = Some((env, cwd));
 }
 
-fn start_forked_cmd(mut cmd: Command) -> Result<(), Error> {
-    cmd.stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null());
+fn apply_launch_environment(cmd: &mut Command) {
+    let Some((env, cwd)) = &*LAUNCH_ENVIRONMENT.lock().unwrap() else {
+        return;
+    };
+    // variables of the command itself, i.e. from the desktop file, take precedence
+    let own: Vec<(OsString, Option<OsString>)> = cmd
+        .get_envs()
+        .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+        .collect();
+    cmd.env_clear();
+    cmd.envs(env.iter().map(|(key, value)| (key, value)));
+    for (key, value) in own {
+        match value {
+            Some(value) => cmd.env(key, value),
+            None => cmd.env_remove(key),
+        };
+    }
+    if cmd.get_current_dir().is_none()
+        && let Some(cwd) = cwd
+    {
+        cmd.current_dir(cwd);
+    }
+}
+
+/// Waits for the process in the background, so it does not stay a zombie
+/// in long running processes like the daemon.
+fn reap(mut child: Child) {
+    thread::spawn(move || {
+        if let Err(e) = child.wait() {
+            log::warn!("failed to wait for launched process {e}");
+        }
+    });
+}
+
+/// Spawns the command in a new session.
+/// Output is written into the given log file or discarded if there is none.
+fn spawn_detached(mut cmd: Command, log: Option<fs::File>) -> Result<Child, Error> {
+    apply_launch_environment(&mut cmd);
+    cmd.stdin(Stdio::null());
+    if let Some(log) = log {
+        let stdout = log.try_clone().map_err(|e| Error::Io(e.to_string()))?;
+        cmd.stdout(stdout).stderr(log);
+    } else {
+        cmd.stdout(Stdio::null()).stderr(Stdio::null());
+    }
 
     unsafe {
         cmd.pre_exec(|| {
@@ -265,8 +588,60 @@ fn start_forked_cmd(mut cmd: Command) -> Result<(), Error> {
         });
     }
 
-    cmd.spawn().map_err(|e| Error::Io(e.to_string()))?;
-    Ok(())
+    cmd.spawn().map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Directory launch logs are written to, `$XDG_STATE_HOME/worf/launch-logs`
+#[must_use]
+pub fn launch_log_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("worf")
+        .join("launch-logs")
+}
+
+/// The launch logs, oldest first.
+fn launch_logs(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut logs: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| Error::Io(e.to_string()))?
+        .filter_map(Result::ok)
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    logs.sort();
+    Ok(logs.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Path of the newest launch log, see `launch_log`.
+#[must_use]
+pub fn last_launch_log() -> Option<PathBuf> {
+    launch_logs(&launch_log_dir()).ok()?.pop()
+}
+
+/// Creates the log file `<app>-<timestamp>.log` for the given command
+/// and removes the oldest logs so only `keep` files are left.
+fn create_launch_log(cmd: &Command, keep: usize) -> Result<fs::File, Error> {
+    let dir = launch_log_dir();
+    fs::create_dir_all(&dir).map_err(|e| Error::Io(e.to_string()))?;
+
+    let logs = launch_logs(&dir)?;
+    let remove_count = (logs.len() + 1).saturating_sub(keep);
+    for path in logs.into_iter().take(remove_count) {
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("cannot remove old launch log {}: {e}", path.display());
+        }
+    }
+
+    let app = Path::new(cmd.get_program())
+        .file_name()
+        .map_or_else(|| "app".to_owned(), |n| n.to_string_lossy().into_owned());
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = dir.join(format!("{app}-{timestamp}.log"));
+    let file = fs::File::create(&path).map_err(|e| Error::Io(e.to_string()))?;
+    log::info!("writing output of {app} to {}", path.display());
+    Ok(file)
 }
 
 /// Get the path of a given cache file
@@ -328,6 +703,128 @@ pub fn save_cache_file<S: BuildHasher>(
     Ok(())
 }
 
+/// Icon file resolved by `IconResolver` with its modification time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ResolvedIcon {
+    path: String,
+    mtime: u64,
+}
+
+/// Content of the icon cache, it only applies to the theme and size it was created for.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IconCache {
+    theme: String,
+    size: u16,
+    icons: HashMap<String, ResolvedIcon>,
+}
+
+/// Resolves icon names to files of the current icon theme.
+/// Resolved icons are kept in `$XDG_CACHE_HOME/worf/icons.toml` across runs, as looking
+/// them up is a large part of the startup time. The cache is dropped if the icon theme
+/// or `image_size` changed, an icon is looked up again if its file changed.
+pub struct IconResolver {
+    path: Option<PathBuf>,
+    theme: String,
+    size: u16,
+    icons: DashMap<String, ResolvedIcon>,
+    changed: AtomicBool,
+}
+
+impl IconResolver {
+    /// Loads the icon cache for the current gtk icon theme.
+    #[must_use]
+    pub fn load(config: &Config) -> Self {
+        let path = dirs::cache_dir().map(|dir| dir.join("worf").join("icons.toml"));
+        let theme = freedesktop_icons::default_theme_gtk().unwrap_or("hicolor".to_owned());
+        let size = config.image_size();
+        let cache: IconCache = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| {
+                toml::from_str(&content)
+                    .inspect_err(|e| log::warn!("cannot parse icon cache {e}"))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        let icons = if cache.theme == theme && cache.size == size {
+            cache.icons.into_iter().collect()
+        } else {
+            log::debug!("icon theme or size changed, dropping icon cache");
+            DashMap::new()
+        };
+        IconResolver {
+            path,
+            theme,
+            size,
+            icons,
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// The file of the icon with the given name, `None` if the theme has no such icon.
+    /// Paths are returned as they are.
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        if name.starts_with('/') {
+            return Some(name.to_owned());
+        }
+        if let Some(icon) = self.icons.get(name)
+            && file_mtime(Path::new(&icon.path)) == Some(icon.mtime)
+        {
+            return Some(icon.path.clone());
+        }
+
+        let path = freedesktop_icons::lookup(name)
+            .with_size(self.size)
+            .with_scale(1)
+            .with_theme(&self.theme)
+            .find()?;
+        let icon = ResolvedIcon {
+            mtime: file_mtime(&path)?,
+            path: path.to_string_lossy().into_owned(),
+        };
+        self.icons.insert(name.to_owned(), icon.clone());
+        self.changed.store(true, Ordering::Relaxed);
+        Some(icon.path)
+    }
+
+    /// Writes the cache if icons were resolved since it was loaded.
+    /// # Errors
+    /// `Error::Io` if the cache cannot be written
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let cache = IconCache {
+            theme: self.theme.clone(),
+            size: self.size,
+            icons: self
+                .icons
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        };
+        let content = toml::to_string(&cache).map_err(|e| Error::ParsingError(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::Io(e.to_string()))?;
+        }
+        fs::write(path, content).map_err(|e| Error::Io(e.to_string()))
+    }
+}
+
+/// Modification time of the file in seconds since the unix epoch.
+fn file_mtime(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
 /// Crates a new file if it does not exist yet.
 /// # Errors
 /// `Errors::Io` if creating the file failed
@@ -345,14 +842,77 @@ pub fn create_file_if_not_exists(path: &PathBuf) -> Result<(), Error> {
     }
 }
 
-/// Check if the given dir entry is an executable
+/// Check if the given path is a regular file the current user may execute.
+/// Symlinks are followed, broken ones are not executable.
 #[must_use]
 pub fn is_executable(entry: &Path) -> bool {
-    if let Ok(metadata) = entry.metadata() {
-        let permissions = metadata.permissions();
-        metadata.is_file() && (permissions.mode() & 0o111 != 0)
-    } else {
-        false
+    let Ok(metadata) = entry.metadata() else {
+        return false;
+    };
+    if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+        return false;
+    }
+
+    // the mode bits do not tell if they apply to the current user
+    let Ok(path) = CString::new(entry.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: the path is a valid nul terminated string that outlives the call.
+    unsafe { libc::access(path.as_ptr(), libc::X_OK) == 0 }
+}
+
+/// Print the selected text to stdout or to the file descriptor configured via `output_fd`.
+/// # Errors
+/// Will return an error if the configured file descriptor is not open or cannot be written.
+pub fn print_selection(text: &str, config: &Config) -> Result<(), Error> {
+    let Some(fd) = config.output_fd() else {
+        println!("{text}");
+        return Ok(());
+    };
+
+    // SAFETY: only queries the flags of the descriptor, does not touch it otherwise.
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(Error::Io(format!("output fd {fd} is not open")));
+    }
+    // SAFETY: the descriptor is open (checked above) and is only borrowed to duplicate it,
+    // the inherited descriptor itself stays open.
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    let mut output = fs::File::from(
+        fd.try_clone_to_owned()
+            .map_err(|e| Error::Io(e.to_string()))?,
+    );
+    writeln!(output, "{text}").map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Decodes `%XX` sequences, invalid sequences are kept as they are.
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = input.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Outputs the selected text of a mode as given by `mode`, see `text_output_mode`.
+/// # Errors
+/// Forwards the errors of copying, printing or typing the text.
+pub fn output_text(text: &str, mode: &TextOutputMode, config: &Config) -> Result<(), Error> {
+    match mode {
+        TextOutputMode::Clipboard => copy_to_clipboard(text.to_owned(), None),
+        TextOutputMode::StandardOutput => print_selection(text, config),
+        TextOutputMode::Type => type_text(text),
+        TextOutputMode::None => Ok(()),
     }
 }
 
@@ -360,10 +920,21 @@ pub fn is_executable(entry: &Path) -> bool {
 /// # Errors
 /// Will return an error if copying to the clipboard failed.
 pub fn copy_to_clipboard(text: String, notify_body: Option<&str>) -> Result<(), Error> {
+    copy(text.into_bytes(), MimeType::Text, notify_body)
+}
+
+/// Copy binary data like images into the clipboard, the mime type is detected from the data.
+/// # Errors
+/// Will return an error if copying to the clipboard failed.
+pub fn copy_data_to_clipboard(data: Vec<u8>, notify_body: Option<&str>) -> Result<(), Error> {
+    copy(data, MimeType::Autodetect, notify_body)
+}
+
+fn copy(data: Vec<u8>, mime_type: MimeType, notify_body: Option<&str>) -> Result<(), Error> {
     let mut opts = wl_clipboard_rs::copy::Options::new();
     opts.clipboard(ClipboardType::Regular);
     opts.serve_requests(ServeRequests::Only(1));
-    let result = opts.copy(Source::Bytes(text.into_bytes().into()), MimeType::Text);
+    let result = opts.copy(Source::Bytes(data.into()), mime_type);
 
     match result {
         Ok(()) => {
@@ -385,3 +956,106 @@ pub fn copy_to_clipboard(text: String, notify_body: Option<&str>) -> Result<(),
         }
     }
 }
+
+/// Type the given text into the currently focused window.
+/// Uses the `zwp_virtual_keyboard_v1` wayland protocol if the compositor supports it
+/// and falls back to `wtype` or `ydotool` otherwise.
+/// The `ydotool` daemon is started if `ydotool` is used.
+/// # Errors
+/// Will return an error if none of the typing backends is available or typing failed.
+pub fn type_text(text: &str) -> Result<(), Error> {
+    let native_error = match crate::virtual_keyboard::type_text(text) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    log::info!("cannot type via virtual keyboard, trying external tools: {native_error}");
+
+    let mut cmd = if which::which("wtype").is_ok() {
+        let mut cmd = Command::new("wtype");
+        cmd.arg("--");
+        cmd
+    } else if which::which("ydotool").is_ok() {
+        // will exit if there is a daemon running already, so it's fine to call this everytime.
+        if let Err(e) = spawn_fork("ydotoold", None, &[]) {
+            log::error!("Failed to start ydotool daemon: {e}");
+        }
+        let mut cmd = Command::new("ydotool");
+        cmd.arg("type");
+        cmd
+    } else {
+        return Err(Error::RunFailed(format!(
+            "no typing backend available, virtual keyboard failed with {native_error} \
+             and neither wtype nor ydotool are installed"
+        )));
+    };
+
+    let output = cmd
+        .arg(text)
+        .output()
+        .map_err(|e| Error::RunFailed(e.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::RunFailed(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+}
+
+/// Socket of the running Hyprland instance, newer versions place it in the runtime dir.
+pub(crate) fn hyprland_socket() -> Option<PathBuf> {
+    let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    dirs::runtime_dir()
+        .into_iter()
+        .chain([PathBuf::from("/tmp")])
+        .map(|dir| dir.join("hypr").join(&signature).join(".socket.sock"))
+        .find(|path| path.exists())
+}
+
+/// Sends a request to the socket of Hyprland, i.e. `dispatch focuswindow`, and returns the reply.
+/// # Errors
+/// Will return an error if Hyprland is not running or the socket cannot be used.
+pub(crate) fn hyprland_request(request: &str) -> Result<String, Error> {
+    let socket = hyprland_socket().ok_or(Error::MissingFile)?;
+    let mut stream = UnixStream::connect(socket).map_err(|e| Error::Io(e.to_string()))?;
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| Error::Io(e.to_string()))?;
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|e| Error::Io(e.to_string()))?;
+    Ok(reply)
+}
+
+/// Moves the pointer to the given position in global, logical coordinates.
+/// Wayland does not allow clients to do this, so only Hyprland and Sway are supported.
+/// # Errors
+/// Will return an error if the compositor is not supported or refused to move the pointer.
+pub fn warp_pointer(x: i32, y: i32) -> Result<(), Error> {
+    if hyprland_socket().is_some() {
+        let reply = hyprland_request(&format!("dispatch movecursor {x} {y}"))?;
+        return if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(Error::RunFailed(reply))
+        };
+    }
+    if env::var_os("SWAYSOCK").is_none() {
+        return Err(Error::RunFailed(
+            "moving the pointer is only supported on Hyprland and Sway".to_owned(),
+        ));
+    }
+
+    let output = Command::new("swaymsg")
+        .args(["seat", "-", "cursor", "set", &x.to_string(), &y.to_string()])
+        .output()
+        .map_err(|e| Error::RunFailed(e.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::RunFailed(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::HashSet,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    Error,
+    config::{Config, Key, SortOrder},
+    gui::{
+        self, CustomKeys, ExpandMode, ItemProvider, KeyBinding, MenuItem, Modifier, ProviderData,
+    },
+};
+
+#[derive(Clone)]
+struct ChoiceProvider {
+    items: Vec<MenuItem<usize>>,
+}
+
+impl ItemProvider<usize> for ChoiceProvider {
+    fn get_elements(&mut self, _: Option<&str>) -> ProviderData<usize> {
+        ProviderData::Items(self.items.clone())
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<usize>) -> ProviderData<usize> {
+        ProviderData::Unchanged
+    }
+}
+
+/// Shortcut of every button, the first letter of its label if no other button uses it.
+fn shortcuts(buttons: &[&str]) -> Vec<Option<Key>> {
+    let mut used = HashSet::new();
+    buttons
+        .iter()
+        .map(|button| {
+            let first = button.chars().next()?.to_ascii_lowercase();
+            if !first.is_ascii_alphabetic() || !used.insert(first) {
+                return None;
+            }
+            Key::from_str(&first.to_string()).ok()
+        })
+        .collect()
+}
+
+/// Shows the question with the given buttons and returns the index of the chosen one.
+/// The search is hidden and every button can be chosen by the first letter of its label.
+/// # Errors
+///
+/// Will return `Err`
+/// * `Error::NoSelection` if the dialog was closed without choosing
+/// * if the ui cannot be shown
+pub fn choose(config: &Config, question: &str, buttons: &[&str]) -> Result<usize, Error> {
+    let mut config = config.clone();
+    config.set_prompt(question.to_owned());
+    config.set_hide_search(true);
+    config.set_sort_order(SortOrder::Default);
+
+    #[allow(clippy::cast_precision_loss)] // there are only a few buttons
+    let items = buttons
+        .iter()
+        .enumerate()
+        .map(|(i, button)| {
+            MenuItem::new(
+                (*button).to_owned(),
+                None,
+                None,
+                vec![],
+                None,
+                (buttons.len() - i) as f64,
+                Some(i),
+            )
+        })
+        .collect();
+
+    let bindings: Vec<_> = shortcuts(buttons)
+        .into_iter()
+        .zip(buttons)
+        .filter_map(|(key, button)| {
+            Some(KeyBinding {
+                key: key?,
+                modifiers: HashSet::from([Modifier::None]),
+                label: (*button).to_owned(),
+                visible: false,
+            })
+        })
+        .collect();
+
+    let selection = gui::show(
+        &config,
+        Arc::new(Mutex::new(ChoiceProvider { items })),
+        None,
+        None,
+        ExpandMode::Verbatim,
+        Some(CustomKeys {
+            bindings,
+            hint: None,
+        }),
+    )?;
+
+    if let Some(key) = selection.custom_key {
+        buttons
+            .iter()
+            .position(|button| **button == key.label)
+            .ok_or(Error::InvalidSelection)
+    } else {
+        selection.menu.data.ok_or(Error::InvalidSelection)
+    }
+}
+
+/// Asks a yes/no question, `y` and `n` choose without moving the selection.
+/// Closing the dialog counts as no.
+/// # Errors
+///
+/// Will return `Err` if the ui cannot be shown
+pub fn confirm(config: &Config, question: &str) -> Result<bool, Error> {
+    match choose(config, question, &["Yes", "No"]) {
+        Ok(choice) => Ok(choice == 0),
+        Err(Error::NoSelection) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcuts_skip_duplicates() {
+        assert_eq!(
+            shortcuts(&["Yes", "No", "never", "1 more"]),
+            vec![Some(Key::Y), Some(Key::N), None, None]
+        );
+    }
+}
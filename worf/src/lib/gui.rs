@@ -1,23 +1,29 @@
 use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
+    fmt,
     marker::PhantomData,
+    path::Path,
     rc::Rc,
-    sync::{Arc, Mutex, RwLock},
+    sync::{Arc, LazyLock, Mutex, MutexGuard, Once, RwLock},
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crossbeam::channel::{self, Sender};
 use gdk4::{
-    Display, Rectangle,
-    gio::File,
-    glib::{self, MainContext, Propagation, SignalHandlerId},
-    prelude::{Cast, DisplayExt, MonitorExt, ObjectExt, SurfaceExt},
+    Display, Monitor, Rectangle,
+    gio::{self, File},
+    glib::{self, MainContext, Propagation, SignalHandlerId, SourceId},
+    prelude::{Cast, DisplayExt, IsA, ListModelExt, MonitorExt, ObjectExt, StaticType, SurfaceExt},
 };
 use gtk4::{
-    Align, Application, ApplicationWindow, CssProvider, EventControllerKey, Expander, FlowBox,
-    FlowBoxChild, GestureClick, Image, Label, ListBox, ListBoxRow, NaturalWrapMode, Ordering,
-    Orientation, PolicyType, ScrolledWindow, SearchEntry, Widget,
+    Adjustment, Align, Application, ApplicationWindow, CssProvider, EventControllerKey,
+    EventControllerScroll, EventControllerScrollFlags, Expander, FlowBox, FlowBoxChild,
+    GestureClick, Grid, IconTheme, Image, Label, ListBox, ListBoxRow, NaturalWrapMode, Ordering,
+    Orientation, Overlay, PickFlags, PolicyType, ProgressBar, ScrolledWindow, SearchEntry,
+    TextDirection, Viewport, Widget,
     glib::ControlFlow,
     prelude::{
         AdjustmentExt, ApplicationExt, ApplicationExtManual, BoxExt, EditableExt,
@@ -32,8 +38,9 @@ use regex::Regex;
 use crate::{
     Error,
     config::{
-        self, Anchor, Config, CustomKeyHintLocation, Key, KeyDetectionType, MatchMethod, SortOrder,
-        WrapMode,
+        self, Anchor, Config, CustomKeyHintLocation, DetailsPosition, ExpanderPosition, Focus,
+        HiddenSearchInput, IconFallback, Key, KeyBindingSpec, KeyDetectionType, ListDirection,
+        MatchMethod, PointerWarp, ScrollbarMode, SortOrder, WrapMode,
     },
     desktop,
     desktop::known_image_extension_regex_pattern,
@@ -43,24 +50,169 @@ pub type ArcMenuMap<T> = Arc<RwLock<HashMap<FlowBoxChild, MenuItem<T>>>>;
 pub type ArcProvider<T> = Arc<Mutex<dyn ItemProvider<T> + Send>>;
 pub type ArcFactory<T> = Arc<Mutex<dyn ItemFactory<T> + Send>>;
 
+#[derive(Clone)]
 pub struct Selection<T: Clone + Send> {
     pub menu: MenuItem<T>,
     pub custom_key: Option<KeyBinding>,
+    /// Modifiers held while submitting, i.e. to tell Ctrl+Enter from Enter.
+    /// Contains `Modifier::None` if no modifier was held.
+    pub modifiers: HashSet<Modifier>,
+    /// How the selection was submitted
+    pub source: SubmitSource,
+    /// Entries marked with `multi_select`, see `show_multi`
+    pub(crate) marked: Vec<MenuItem<T>>,
+}
+
+/// Describes how a selection was submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitSource {
+    /// Submit, expand or a custom key was pressed
+    Keyboard,
+    /// The entry was clicked
+    Click,
+    /// Only one entry was left, see `auto_select_on_search`
+    AutoSelect,
 }
 type SelectionSender<T> = Sender<Result<Selection<T>, Error>>;
 
-pub struct ProviderData<T: Clone> {
-    pub items: Option<Vec<MenuItem<T>>>,
+/// Result of querying an `ItemProvider`
+pub enum ProviderData<T: Clone> {
+    /// Nothing changed, the currently shown items are kept.
+    Unchanged,
+    /// Replaces the shown items. An empty list clears the view.
+    Items(Vec<MenuItem<T>>),
+    /// Querying the provider failed, the error is shown to the user.
+    /// The currently shown items are kept.
+    Error(Error),
+    /// An informational message shown to the user, i.e. a hint why nothing is found.
+    /// The currently shown items are kept.
+    Message(String),
 }
 
 pub trait ItemProvider<T: Clone> {
     fn get_elements(&mut self, search: Option<&str>) -> ProviderData<T>;
 
+    /// Used instead of `get_elements` for the items shown when the window opens,
+    /// so slow providers do not block the ui until all items are known.
+    /// The returned data is shown right away, every batch sent to `sink` afterwards
+    /// is appended to it. The stream ends once `sink` is dropped, batches arriving
+    /// after the shown items have been replaced are discarded.
+    /// If batches were sent, the items are replaced by `get_elements(None)` once the
+    /// stream ended, so providers can stream unsorted items while reading them.
+    /// Providers keep `sink` and send from their own thread, as the provider is
+    /// locked while this is called.
+    /// Defaults to `get_elements` without streaming.
+    fn get_elements_stream(
+        &mut self,
+        search: Option<&str>,
+        sink: Sender<Vec<MenuItem<T>>>,
+    ) -> ProviderData<T> {
+        drop(sink);
+        self.get_elements(search)
+    }
+
     /// Get elements below the given menu entry.
     /// Will be called for completion
-    /// If (true, None) is returned and submit-accept is set in the config, this
-    /// will be handled the name way as pressing enter (or the configured submit key).
+    /// If `ProviderData::Unchanged` is returned, this will be handled
+    /// the same way as pressing enter (or the configured submit key).
+    /// Called on a worker thread, the ui stays responsive meanwhile.
     fn get_sub_elements(&mut self, item: &MenuItem<T>) -> ProviderData<T>;
+
+    /// If set, `get_elements` is called periodically with the current search
+    /// to refresh the shown items, i.e. for live data like timers.
+    /// Items are updated in place, as long as every shown item can be matched to
+    /// a refreshed one by its id, or its action (or label if there is no action).
+    /// Asked again before every refresh, which is skipped while it returns `None`,
+    /// i.e. once the background work of the provider is done.
+    /// Defaults to `None`, which disables refreshing.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Time until the data returned last by the provider is outdated, i.e. the
+    /// validity of one time passwords or the results of a network scan.
+    /// Asked whenever the provider returned data, once it elapsed while the window
+    /// is still open, the items are queried again like with `refresh_interval`.
+    /// Defaults to `None`, which means the data does not expire.
+    fn expires_in(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Details of the selected item, shown as name and value in the details panel
+    /// if it is enabled via `details` or `key_toggle_details`.
+    /// Defaults to `None`, which hides the panel.
+    fn details(&self, _item: &MenuItem<T>) -> Option<Vec<(String, String)>> {
+        None
+    }
+
+    /// Text shown in the preview pane for the selected item if `preview` is enabled,
+    /// i.e. metadata which is too expensive to gather for all items.
+    /// Called on a worker thread, so it may block, i.e. to read the file.
+    /// Defaults to the preview of the item, see `MenuItem::with_preview`.
+    fn preview(&self, item: &MenuItem<T>) -> Option<String> {
+        item.preview.clone()
+    }
+
+    /// Called before the last visible item is submitted because of `auto_select_on_search`.
+    /// Return false to prevent submitting the item, i.e. for items that are always shown.
+    /// Defaults to `true`.
+    fn allow_auto_select(&self, _item: &MenuItem<T>) -> bool {
+        true
+    }
+
+    /// Called when `key_switch_mode` is pressed. Providers combining multiple modes
+    /// switch to their next mode and return its name, which replaces the prompt.
+    /// The items are queried again afterwards.
+    /// Defaults to `None`, which closes the window with `Error::SwitchMode`,
+    /// so the next mode of `switch_modes` is shown.
+    fn switch_mode(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Called before a selection is submitted and the window closes.
+    /// The provider can keep the window open, see `SubmitDisposition`.
+    /// Defaults to `SubmitDisposition::Accept`.
+    fn on_submit(&mut self, _selection: &Selection<T>) -> SubmitDisposition<T>
+    where
+        T: Send,
+    {
+        SubmitDisposition::Accept
+    }
+
+    /// Called for every key press before worf handles it, after custom keys.
+    /// Allows modes to edit the search in their own way, i.e. to step numbers.
+    /// `cursor` is the position of the cursor in the search, counted in chars.
+    /// If `Some` is returned, the search is replaced, the items are queried again
+    /// and worf does not handle the key itself.
+    /// Defaults to `None`.
+    fn handle_search_key(
+        &mut self,
+        _key: Key,
+        _modifiers: &HashSet<Modifier>,
+        _search: &str,
+        _cursor: usize,
+    ) -> Option<SearchEdit> {
+        None
+    }
+}
+
+/// New search text and cursor position returned by `ItemProvider::handle_search_key`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchEdit {
+    pub text: String,
+    /// Position of the cursor, counted in chars.
+    pub cursor: usize,
+}
+
+/// Decides what happens with a submitted selection, see `ItemProvider::on_submit`
+pub enum SubmitDisposition<T: Clone> {
+    /// Closes the window and returns the selection
+    Accept,
+    /// Keeps the window open and shows the given message
+    Reject(String),
+    /// Keeps the window open and shows the given items instead,
+    /// i.e. to ask a follow-up question.
+    Replace(Vec<MenuItem<T>>),
 }
 
 pub trait ItemFactory<T: Clone> {
@@ -148,14 +300,39 @@ fn into_core_order(gtk_order: Ordering) -> core::cmp::Ordering {
 pub struct MenuItem<T: Clone> {
     /// text to show in the UI
     pub label: String,
+    /// Stable identity of the item, i.e. a desktop file id or window address.
+    /// Used instead of the label for caches and updates if set, see `key`
+    pub id: Option<String>,
     /// optional icon, will use fallback icon if None is given
     pub icon_path: Option<String>,
+    /// Text symbol shown instead of the icon, i.e. a Nerd Font glyph, see `with_glyph`
+    pub glyph: Option<String>,
     /// the action to run when this is selected.
     pub action: Option<String>,
     /// Sub elements of this entry. If this already has a parent entry, nesting is not supported
     pub sub_elements: Vec<MenuItem<T>>,
     /// Working directory to run the action in.
     pub working_dir: Option<String>,
+    /// Environment variables set when running the action, see `with_env`
+    pub env: Vec<(String, String)>,
+    /// Additional terms the search matches against, which are not displayed,
+    /// see `with_search_terms`
+    pub search_terms: Vec<String>,
+    /// Short text shown right aligned next to the label, i.e. a counter.
+    /// It is not part of the search.
+    pub badge: Option<String>,
+    /// Css class added to the badge, i.e. to color a status, see `with_badge_class`
+    pub badge_class: Option<String>,
+    /// Secondary text shown below the label, i.e. the size of a file, see `with_description`.
+    /// It is not part of the search.
+    pub description: Option<String>,
+    /// Css class added to the row, i.e. to mark pinned entries, see `with_css_class`
+    pub css_class: Option<String>,
+    /// Fraction between 0 and 1 shown as thin bar below the label,
+    /// i.e. battery level or disk usage, see `with_progress`
+    pub progress: Option<f64>,
+    /// Text shown in the preview pane while the item is selected, see `with_preview`
+    pub preview: Option<String>,
     /// Initial sort score to display favourites at the top
     pub initial_sort_score: f64,
 
@@ -166,6 +343,8 @@ pub struct MenuItem<T: Clone> {
     search_sort_score: f64,
     /// True if the item is visible
     visible: bool,
+    /// Position given by `keep_order`, orders items with the same score
+    rank: Option<usize>,
 }
 
 impl From<gtk4::gdk::Key> for Key {
@@ -446,6 +625,17 @@ pub struct KeyBinding {
     pub visible: bool,
 }
 
+impl KeyBinding {
+    /// The key and modifiers triggering the binding.
+    #[must_use]
+    pub fn spec(&self) -> KeyBindingSpec {
+        KeyBindingSpec {
+            key: self.key,
+            modifiers: self.modifiers.clone(),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct CustomKeyHint {
     pub label: String,
@@ -458,6 +648,93 @@ pub struct CustomKeys {
     pub hint: Option<CustomKeyHint>,
 }
 
+/// Two bindings reacting to the same key press, only the first one is triggered.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyConflict {
+    pub key: Key,
+    pub modifiers: Vec<Modifier>,
+    /// Binding receiving the key press
+    pub action: String,
+    /// Binding never receiving the key press
+    pub shadowed: String,
+}
+
+impl fmt::Display for KeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{modifier:?}+")?;
+        }
+        write!(
+            f,
+            "{:?} triggers '{}', '{}' is not reachable",
+            self.key, self.action, self.shadowed
+        )
+    }
+}
+
+/// Finds bindings which overlap with an earlier one.
+/// Custom keys are checked before the built-in keys.
+#[must_use]
+pub fn key_conflicts(config: &Config, custom_keys: Option<&CustomKeys>) -> Vec<KeyConflict> {
+    let built_in = [
+        ("hide search", config.key_hide_search()),
+        ("toggle focus", config.key_toggle_focus()),
+        ("toggle details", config.key_toggle_details()),
+        ("submit", Some(config.key_submit())),
+        ("exit", Some(config.key_exit())),
+        ("copy", config.key_copy()),
+        ("copy", config.mouse_copy()),
+        ("expand", Some(config.key_expand())),
+        ("expand", config.mouse_expand()),
+        ("switch mode", config.key_switch_mode()),
+    ]
+    .into_iter()
+    .filter_map(|(action, spec)| spec.map(|spec| (action.to_owned(), spec)));
+    // translated to the arrow keys after the built-in keys are checked
+    let navigation = [
+        ("up", config.key_up()),
+        ("down", config.key_down()),
+        ("page up", config.key_pgup()),
+        ("page down", config.key_pgdn()),
+        ("first entry", config.key_home()),
+        ("last entry", config.key_end()),
+    ]
+    .into_iter()
+    .filter_map(|(action, spec)| spec.map(|spec| (action.to_owned(), spec)));
+
+    // in the order `handle_custom_keys` checks them
+    let bindings: Vec<_> = custom_keys
+        .iter()
+        .flat_map(|keys| &keys.bindings)
+        .map(|binding| (format!("custom key {}", binding.label), binding.spec()))
+        .chain(built_in)
+        .chain(navigation)
+        .filter(|(_, spec)| spec.key != Key::None)
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, (action, spec)) in bindings.iter().enumerate() {
+        for (shadowed, other) in &bindings[i + 1..] {
+            // the earlier binding also reacts to the modifiers the later one needs
+            if spec.key != other.key || !spec.matches_modifiers(&other.modifiers) {
+                continue;
+            }
+            conflicts.push(KeyConflict {
+                key: spec.key,
+                modifiers: other
+                    .modifiers
+                    .iter()
+                    .copied()
+                    .filter(|modifier| *modifier != Modifier::None)
+                    .collect(),
+                action: action.clone(),
+                shadowed: shadowed.clone(),
+            });
+        }
+    }
+    conflicts
+}
+
 impl<T: Clone> MenuItem<T> {
     #[must_use]
     pub fn new(
@@ -472,17 +749,108 @@ impl<T: Clone> MenuItem<T> {
     ) -> Self {
         MenuItem {
             label,
+            id: None,
             icon_path,
+            glyph: None,
             action,
             sub_elements,
             working_dir,
+            env: Vec::new(),
+            search_terms: Vec::new(),
+            badge: None,
+            badge_class: None,
+            description: None,
+            css_class: None,
+            progress: None,
+            preview: None,
             initial_sort_score,
             data,
             //allow_submit,
             search_sort_score: 0.0,
             visible: true,
+            rank: None,
         }
     }
+
+    /// Sets the stable identity of the item, see `key`.
+    #[must_use]
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Identity of the item, the id if set, otherwise the label.
+    /// Items sharing a label can be told apart by giving them ids.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.label)
+    }
+
+    /// Sets the environment variables used when running the action.
+    #[must_use]
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Sets additional terms the search matches against, i.e. a window class
+    /// or a user name stored in `data`.
+    /// Matches in these terms are weighted by `search_terms_weight`.
+    #[must_use]
+    pub fn with_search_terms(mut self, search_terms: Vec<String>) -> Self {
+        self.search_terms = search_terms;
+        self
+    }
+
+    /// Sets the text shown in the preview pane, i.e. the description of an application.
+    #[must_use]
+    pub fn with_preview(mut self, preview: String) -> Self {
+        self.preview = Some(preview);
+        self
+    }
+
+    /// Sets the badge shown next to the label, i.e. a window count.
+    #[must_use]
+    pub fn with_badge(mut self, badge: String) -> Self {
+        self.badge = Some(badge);
+        self
+    }
+
+    /// Sets the text shown below the label, i.e. the path of a binary.
+    #[must_use]
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Adds a css class to the badge, i.e. `reachable` to color a status.
+    #[must_use]
+    pub fn with_badge_class(mut self, class: String) -> Self {
+        self.badge_class = Some(class);
+        self
+    }
+
+    /// Shows the given symbol instead of the icon, i.e. a Nerd Font glyph.
+    /// Codepoints can be given as `U+F269`.
+    #[must_use]
+    pub fn with_glyph(mut self, glyph: &str) -> Self {
+        self.glyph = Some(resolve_glyph(glyph));
+        self
+    }
+
+    /// Adds a css class to the row, i.e. `pinned` to highlight an entry.
+    #[must_use]
+    pub fn with_css_class(mut self, class: String) -> Self {
+        self.css_class = Some(class);
+        self
+    }
+
+    /// Sets the progress shown as bar below the label, clamped to 0..=1.
+    #[must_use]
+    pub fn with_progress(mut self, progress: f64) -> Self {
+        self.progress = Some(progress.clamp(0.0, 1.0));
+        self
+    }
 }
 
 impl<T: Clone> AsRef<MenuItem<T>> for MenuItem<T> {
@@ -495,9 +863,52 @@ struct MetaData<T: Clone + Send> {
     item_provider: ArcProvider<T>,
     item_factory: Option<ArcFactory<T>>,
     selected_sender: SelectionSender<T>,
-    config: Arc<RwLock<Config>>,
+    /// Replaced when the config is reloaded, see `reload_config`
+    config: RefCell<Arc<Config>>,
+    /// The config as read from the file, without the options changed at runtime,
+    /// to tell the options changed in the file apart when it is reloaded.
+    file_config: RefCell<Option<Config>>,
+    style_provider: RefCell<Option<CssProvider>>,
     search_ignored_words: Option<Vec<Regex>>,
     expand_mode: ExpandMode,
+    /// Set while no items are shown because the query is shorter than `min_query_length`
+    held_back: Cell<bool>,
+    /// Validity of the data the provider returned last, see `ItemProvider::expires_in`.
+    /// Asked whenever the provider is locked for data anyway.
+    expires_in: Cell<Option<Duration>>,
+    /// Set if a query was only filtered by the ui because the provider was busy,
+    /// it is asked again once the provider is available.
+    pending_query: Cell<bool>,
+}
+
+impl<T: Clone + Send> MetaData<T> {
+    fn config(&self) -> Arc<Config> {
+        Arc::clone(&self.config.borrow())
+    }
+
+    /// Locks the provider on the ui thread. `None` while a background query holds it,
+    /// i.e. for sub elements, so the ui keeps responding instead of waiting for it.
+    fn try_provider(&self) -> Option<MutexGuard<'_, dyn ItemProvider<T> + Send + 'static>> {
+        self.item_provider.try_lock().ok()
+    }
+}
+
+/// Makes sure only the first submit of a session is accepted,
+/// no matter if it comes from a key, a click or auto selection.
+#[derive(Default)]
+struct SubmitGuard {
+    submitted: Cell<bool>,
+}
+
+impl SubmitGuard {
+    /// Returns true if this is the first submit.
+    fn try_submit(&self) -> bool {
+        !self.submitted.replace(true)
+    }
+
+    fn is_submitted(&self) -> bool {
+        self.submitted.get()
+    }
 }
 
 struct UiElements<T: Clone> {
@@ -509,9 +920,67 @@ struct UiElements<T: Clone> {
     menu_rows: ArcMenuMap<T>,
     search_text: Arc<Mutex<String>>,
     search_delete_event: Arc<Mutex<Option<SignalHandlerId>>>,
+    auto_select_source: Cell<Option<SourceId>>,
+    /// Queries the items again once they expired, see `ItemProvider::expires_in`
+    expiry_source: Cell<Option<SourceId>>,
+    submit_guard: SubmitGuard,
+    /// True while entries are shown as suggestions because nothing matched
+    suggestions_shown: Cell<bool>,
+    /// True while the sub elements of an item are queried
+    sub_elements_pending: Cell<bool>,
+    /// Incremented whenever the shown items are replaced, streamed batches
+    /// for older items are discarded.
+    items_generation: Cell<u64>,
+    /// Part of the window keys act on
+    focus: Cell<Focus>,
     outer_box: gtk4::Box,
     scroll: ScrolledWindow,
     custom_key_box: gtk4::Box,
+    message: Label,
+    /// Shows the typed text while the search is hidden
+    search_overlay: Label,
+    /// Details of the selected entry, see `ItemProvider::details`
+    details: Grid,
+    /// True while the details panel is enabled, toggled by `key_toggle_details`
+    details_enabled: Cell<bool>,
+    /// Preview of the selected entry, see `ItemProvider::preview`
+    preview: Label,
+    /// Entries marked with `multi_select`, in the order they were marked
+    marked: RefCell<Vec<MenuItem<T>>>,
+}
+
+impl<T: Clone> UiElements<T> {
+    fn new(app: Application, window: ApplicationWindow, config: &Config) -> Self {
+        let search = SearchEntry::new();
+        search.set_can_focus(false);
+        window.set_can_focus(true);
+        Self {
+            app,
+            window,
+            background: create_background(config),
+            search,
+            main_box: FlowBox::new(),
+            menu_rows: Arc::new(RwLock::new(HashMap::new())),
+            search_text: Arc::new(Mutex::new(String::new())),
+            search_delete_event: Arc::new(Mutex::new(None)),
+            auto_select_source: Cell::new(None),
+            expiry_source: Cell::new(None),
+            submit_guard: SubmitGuard::default(),
+            suggestions_shown: Cell::new(false),
+            sub_elements_pending: Cell::new(false),
+            items_generation: Cell::new(0),
+            focus: Cell::new(Focus::Search),
+            outer_box: gtk4::Box::new(config.orientation().into(), 0),
+            scroll: ScrolledWindow::new(),
+            custom_key_box: gtk4::Box::new(Orientation::Vertical, 0),
+            message: Label::new(None),
+            search_overlay: Label::new(None),
+            details: Grid::new(),
+            details_enabled: Cell::new(config.details()),
+            preview: Label::new(None),
+            marked: RefCell::new(Vec::new()),
+        }
+    }
 }
 
 /// Shows the user interface and **blocks** until the user selected an entry
@@ -521,7 +990,7 @@ struct UiElements<T: Clone> {
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show<T>(
-    config: &Arc<RwLock<Config>>,
+    config: &Config,
     item_provider: ArcProvider<T>,
     item_factory: Option<ArcFactory<T>>,
     search_ignored_words: Option<Vec<Regex>>,
@@ -533,33 +1002,36 @@ where
 {
     gtk4::init().map_err(|e| Error::Graphics(e.to_string()))?;
     log::debug!("Starting GUI");
-    if let Some(ref css) = config.read().unwrap().style() {
-        log::debug!("loading css from {css}");
-        let provider = CssProvider::new();
-        let css_file_path = File::for_path(css);
-        provider.load_from_file(&css_file_path);
-        if let Some(display) = Display::default() {
-            gtk4::style_context_add_provider_for_display(
-                &display,
-                &provider,
-                gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-            );
-        }
+    for conflict in key_conflicts(config, custom_keys.as_ref()) {
+        log::warn!("key binding conflict: {conflict}");
     }
-
-    let app = Application::builder().application_id("worf").build();
+    // the ui never changes the config, a snapshot avoids locking it for every row
+    let config = Arc::new(config.clone());
+    let style_provider = load_style(&config);
+
+    // other instances must not take over this one, i.e. a dmenu prompt of a launched script
+    let mut app = Application::builder().flags(gio::ApplicationFlags::NON_UNIQUE);
+    if let Some(application_id) = application_id(&config) {
+        app = app.application_id(application_id);
+    }
+    let app = app.build();
     let (sender, receiver) = channel::bounded(1);
 
     let meta = Rc::new(MetaData {
         item_provider,
         item_factory,
         selected_sender: sender,
-        config: Arc::clone(config),
+        config: RefCell::new(Arc::clone(&config)),
+        file_config: RefCell::new(config.reload().ok()),
+        style_provider: RefCell::new(style_provider),
         search_ignored_words,
         expand_mode,
+        held_back: Cell::new(config.min_query_length() > 0),
+        expires_in: Cell::new(None),
+        pending_query: Cell::new(false),
     });
 
-    let connect_cfg = Arc::clone(config);
+    let connect_cfg = Arc::clone(&config);
     app.connect_activate(move |app| {
         build_ui::<T>(&connect_cfg, &meta, app.clone(), custom_keys.as_ref());
     });
@@ -578,8 +1050,87 @@ where
     receiver_result?
 }
 
+/// Like `show`, but several entries can be marked with Ctrl and the submit key or Ctrl+click.
+/// Returns the marked entries, or the submitted one if nothing was marked.
+/// # Errors
+///
+/// Will return Err when the channel between the UI and this is broken
+pub fn show_multi<T>(
+    config: &Config,
+    item_provider: ArcProvider<T>,
+    item_factory: Option<ArcFactory<T>>,
+    search_ignored_words: Option<Vec<Regex>>,
+    expand_mode: ExpandMode,
+    custom_keys: Option<CustomKeys>,
+) -> Result<Vec<Selection<T>>, Error>
+where
+    T: Clone + 'static + Send,
+{
+    let mut config = config.clone();
+    config.set_multi_select(true);
+    let selection = show(
+        &config,
+        item_provider,
+        item_factory,
+        search_ignored_words,
+        expand_mode,
+        custom_keys,
+    )?;
+
+    if selection.marked.is_empty() {
+        return Ok(vec![selection]);
+    }
+    Ok(selection
+        .marked
+        .iter()
+        .map(|menu| Selection {
+            menu: menu.clone(),
+            custom_key: selection.custom_key.clone(),
+            modifiers: selection.modifiers.clone(),
+            source: selection.source,
+            marked: Vec::new(),
+        })
+        .collect())
+}
+
+thread_local! {
+    /// Exits the menu shown on this thread, see `close_shown`.
+    static CLOSE_SHOWN: RefCell<Option<Rc<dyn Fn()>>> = const { RefCell::new(None) };
+    /// Reloads the config of the menu shown on this thread, see `watch_config_reload`.
+    static RELOAD_SHOWN: RefCell<Option<Rc<dyn Fn()>>> = const { RefCell::new(None) };
+}
+
+/// Reloads the config of the shown menu on `SIGHUP`, see `reload_config`.
+/// The handler is kept while the process runs, so the daemon is not stopped
+/// by the signal while no menu is shown. Has to be called on the thread showing the menus.
+pub fn watch_config_reload() {
+    static WATCHED: Once = Once::new();
+    WATCHED.call_once(|| {
+        glib::unix_signal_add_local(libc::SIGHUP, || {
+            // cloned, the handler is cleared when the menu closes
+            if let Some(reload) = RELOAD_SHOWN.with(|reload| reload.borrow().clone()) {
+                reload();
+            } else {
+                log::info!("no menu shown, the config is read again for the next one");
+            }
+            ControlFlow::Continue
+        });
+    });
+}
+
+/// Closes the shown menu without selection, i.e. when its global shortcut is pressed again.
+/// Can be called from any thread, the menu is closed on the thread showing it.
+pub fn close_shown() {
+    MainContext::default().invoke(|| {
+        // cloned, closing the menu clears the handler
+        if let Some(close) = CLOSE_SHOWN.with(|close| close.borrow().clone()) {
+            close();
+        }
+    });
+}
+
 fn build_ui<T>(
-    config: &Arc<RwLock<Config>>,
+    config: &Arc<Config>,
     meta: &Rc<MetaData<T>>,
     app: Application,
     custom_keys: Option<&CustomKeys>,
@@ -589,9 +1140,17 @@ fn build_ui<T>(
     let start = Instant::now();
 
     let provider_clone = Arc::clone(&meta.item_provider);
+    let (stream_sender, stream_receiver) = channel::unbounded();
+    let wait_for_query = config.min_query_length() > 0;
     let get_provider_elements = thread::spawn(move || {
+        if wait_for_query {
+            // the provider is asked once the search is long enough
+            return (ProviderData::Items(Vec::new()), None);
+        }
         log::debug!("getting items");
-        provider_clone.lock().unwrap().get_elements(None)
+        let mut provider = provider_clone.lock().unwrap();
+        let data = provider.get_elements_stream(None, stream_sender);
+        (data, provider.expires_in())
     });
 
     let window = ApplicationWindow::builder()
@@ -602,60 +1161,53 @@ fn build_ui<T>(
         .default_height(1)
         .build();
 
-    let background = create_background(&config.read().unwrap());
-
-    let search_entry = SearchEntry::new();
-    search_entry.set_can_focus(false);
-    let main_window = window.clone();
-    main_window.set_can_focus(true);
-    let ui_elements = Rc::new(UiElements {
-        app,
-        window: main_window,
-        background,
-        search: search_entry,
-        main_box: FlowBox::new(),
-        menu_rows: Arc::new(RwLock::new(HashMap::new())),
-        search_text: Arc::new(Mutex::new(String::new())),
-        search_delete_event: Arc::new(Mutex::new(None)),
-        outer_box: gtk4::Box::new(config.read().unwrap().orientation().into(), 0),
-        scroll: ScrolledWindow::new(),
-        custom_key_box: gtk4::Box::new(Orientation::Vertical, 0),
-    });
+    let ui_elements = Rc::new(UiElements::new(app, window, config));
 
     // handle keys as soon as possible
     setup_key_event_handler(&ui_elements, meta, custom_keys);
+    setup_mouse_event_handler(config, &ui_elements, meta, custom_keys);
 
     log::debug!("keyboard ready after {:?}", start.elapsed());
 
-    if !config.read().unwrap().normal_window() {
+    let secure_input = config.secure_input();
+    if secure_input && config.normal_window() {
+        log::warn!("secure input requires a layer shell window, ignoring normal window");
+    }
+
+    if secure_input || !config.normal_window() {
         // Initialize the window as a layer
         ui_elements.window.init_layer_shell();
-        ui_elements
-            .window
-            .set_layer(config.read().unwrap().layer().into());
+        ui_elements.window.set_layer(config.layer().into());
         ui_elements
             .window
             .set_keyboard_mode(KeyboardMode::Exclusive);
     }
 
     ui_elements.window.set_widget_name("window");
-    ui_elements.window.set_namespace(Some("worf"));
+    ui_elements.window.set_namespace(Some(&config.app_id()));
 
-    if let Some(location) = config.read().unwrap().location() {
-        for anchor in location {
+    if let Some(location) = config.location() {
+        for anchor in &location {
             ui_elements.window.set_anchor(anchor.into(), true);
         }
     }
 
     ui_elements.outer_box.set_widget_name("outer-box");
     ui_elements.outer_box.append(&ui_elements.search);
-    if let Some(custom_keys) = custom_keys {
-        build_custom_key_view(
-            custom_keys,
-            &ui_elements.outer_box,
-            &ui_elements.custom_key_box,
-        );
+    if secure_input {
+        // keys are handled by the window, the entry never needs focus.
+        // this keeps input methods from getting hold of the input.
+        ui_elements.search.set_focusable(false);
+        ui_elements.search.set_can_focus(false);
+
+        let ui = Rc::clone(&ui_elements);
+        ui_elements.app.connect_shutdown(move |_| {
+            clear_search_text_secure(&ui);
+        });
     }
+    build_message_label(&ui_elements.message);
+    ui_elements.outer_box.append(&ui_elements.message);
+    build_custom_key_view(&ui_elements, meta, custom_keys);
 
     ui_elements.window.set_child(Some(&ui_elements.outer_box));
     // Set initial focus to the search entry
@@ -665,45 +1217,175 @@ fn build_ui<T>(
     ui_elements.scroll.set_hexpand(true);
     ui_elements.scroll.set_vexpand(true);
 
-    if config.read().unwrap().hide_scroll() {
+    build_scrollbar(&config, &ui_elements.scroll);
+    if config.is_bar() {
+        // a single row, entries which do not fit the width are scrolled to
+        let (hpolicy, _) = ui_elements.scroll.policy();
+        ui_elements.scroll.set_policy(hpolicy, PolicyType::Never);
+        ui_elements.scroll.set_vexpand(false);
+    }
+    if config.dynamic_width() {
+        ui_elements.scroll.set_propagate_natural_width(true);
         ui_elements
             .scroll
-            .set_policy(PolicyType::External, PolicyType::External);
+            .set_propagate_natural_height(config.orientation() == config::Orientation::Horizontal);
+    }
+    let results: Widget = if config.hidden_search_overlay() {
+        build_search_overlay(&ui_elements.search_overlay);
+        let overlay = Overlay::new();
+        overlay.set_child(Some(&ui_elements.scroll));
+        overlay.add_overlay(&ui_elements.search_overlay);
+        overlay.upcast()
+    } else {
+        ui_elements.scroll.clone().upcast()
+    };
+    let results = build_details(&config, &ui_elements, results);
+    ui_elements.outer_box.append(&results);
+    if config.list_direction() == ListDirection::Up {
+        ui_elements
+            .outer_box
+            .reorder_child_after(&ui_elements.message, Some(&results));
+        ui_elements
+            .outer_box
+            .reorder_child_after(&ui_elements.search, Some(&ui_elements.message));
     }
-    ui_elements.outer_box.append(&ui_elements.scroll);
 
-    build_main_box(&config.read().unwrap(), &ui_elements);
-    build_search_entry(&config.read().unwrap(), &ui_elements, meta);
+    build_main_box(&config, &ui_elements);
+    setup_details_update(&ui_elements, meta);
+    build_search_entry(&config, &ui_elements, meta);
+    set_focus(&ui_elements, config.initial_focus());
 
     let wrapper_box = gtk4::Box::new(Orientation::Vertical, 0);
     wrapper_box.append(&ui_elements.main_box);
     ui_elements.scroll.set_child(Some(&wrapper_box));
 
     let wait_for_items = Instant::now();
-    let provider_elements = get_provider_elements.join().unwrap();
+    let (provider_elements, expires_in) = get_provider_elements.join().unwrap();
+    meta.expires_in.set(expires_in);
     log::debug!("got items after {:?}", wait_for_items.elapsed());
 
     let cfg = Arc::clone(config);
     let ui = Rc::clone(&ui_elements);
     ui_elements.window.connect_is_active_notify(move |_| {
-        window_show_resize(&cfg.read().unwrap(), &ui);
+        window_show_resize(&cfg, &ui);
     });
+    watch_monitor_changes(config, &ui_elements);
+    setup_pointer_warp(config, &ui_elements.window);
+    if config.debug_style() {
+        gtk4::Window::set_interactive_debugging(true);
+        let printed = Cell::new(false);
+        ui_elements.window.connect_is_active_notify(move |window| {
+            if !window.is_active() || printed.replace(true) {
+                return;
+            }
+            // wait for the first rows to be added
+            let window = window.clone();
+            glib::timeout_add_local_once(Duration::from_millis(500), move || {
+                print_widget_tree(window.upcast_ref(), 0);
+            });
+        });
+    }
+    if config.print_window_info() {
+        let ui = Rc::clone(&ui_elements);
+        let meta = Rc::clone(meta);
+        // the window is not necessarily activated, i.e. with keyboard mode none
+        ui_elements.window.connect_map(move |_| {
+            let ui = Rc::clone(&ui);
+            let meta = Rc::clone(&meta);
+            // give the compositor time to apply the size set by `window_show_resize`
+            glib::timeout_add_local_once(Duration::from_millis(100), move || {
+                if !ui.submit_guard.try_submit() {
+                    return;
+                }
+                print_window_info(&ui.window);
+                if let Err(e) = meta.selected_sender.send(Err(Error::WindowInfoPrinted)) {
+                    log::error!("failed to send message {e}");
+                }
+                close_gui(&ui.app);
+            });
+        });
+    }
+
+    {
+        let ui = Rc::clone(&ui_elements);
+        let meta = Rc::clone(meta);
+        CLOSE_SHOWN.with(|close| {
+            close.replace(Some(Rc::new(move || {
+                handle_key_exit(&ui, &meta);
+            })));
+        });
+        ui_elements.app.connect_shutdown(|_| {
+            CLOSE_SHOWN.with(|close| close.replace(None));
+        });
+    }
+
+    apply_provider_data(&ui_elements, meta, provider_elements);
+    receive_streamed_items(&ui_elements, meta, stream_receiver);
 
-    if let Some(elements) = provider_elements.items {
-        build_ui_from_menu_items(&ui_elements, meta, elements);
+    watch_config_reload();
+    {
+        let ui = Rc::clone(&ui_elements);
+        let meta = Rc::clone(meta);
+        RELOAD_SHOWN.with(|reload| {
+            reload.replace(Some(Rc::new(move || {
+                reload_config(&ui, &meta);
+            })));
+        });
+        ui_elements.app.connect_shutdown(|_| {
+            RELOAD_SHOWN.with(|reload| reload.replace(None));
+        });
     }
 
-    let window_start = Instant::now();
-    ui_elements.window.present();
-    if let Some(background) = &ui_elements.background {
-        background.present();
+    let refresh_interval = meta.item_provider.lock().unwrap().refresh_interval();
+    if let Some(interval) = refresh_interval {
+        let ui = Rc::clone(&ui_elements);
+        let meta = Rc::clone(meta);
+        let source_id = glib::timeout_add_local(interval, move || {
+            let refresh = meta
+                .try_provider()
+                .is_some_and(|provider| provider.refresh_interval().is_some());
+            if refresh {
+                refresh_view_from_provider(&ui, &meta);
+            }
+            ControlFlow::Continue
+        });
+        let source_id = Rc::new(Cell::new(Some(source_id)));
+        ui_elements.app.connect_shutdown(move |_| {
+            if let Some(id) = source_id.take() {
+                id.remove();
+            }
+        });
     }
 
-    log::debug!("window show took {:?}", window_start.elapsed());
+    present_window(config, &ui_elements);
 
     log::debug!("Building UI took {:?}", start.elapsed(),);
 }
 
+/// Presents the window once the first rows are added, they are added in idle chunks
+/// and needed to measure the row height, see `reserve_window_size`.
+fn present_window<T: Clone + 'static>(config: &Arc<Config>, ui: &Rc<UiElements<T>>) {
+    let cfg = Arc::clone(config);
+    let ui = Rc::clone(ui);
+    // queued after the first chunk of rows
+    glib::idle_add_local_once(move || {
+        // i.e. the only entry was auto selected
+        if ui.submit_guard.is_submitted() {
+            return;
+        }
+        reserve_window_size(&cfg, &ui);
+
+        let window_start = Instant::now();
+        // the background is shown first, so it stays below the window
+        if let Some(background) = &ui.background {
+            background.present();
+        }
+        ui.window.present();
+
+        log::debug!("window show took {:?}", window_start.elapsed());
+    });
+}
+
 fn create_background(config: &Config) -> Option<ApplicationWindow> {
     if config.blurred_background() {
         let background = ApplicationWindow::builder()
@@ -713,11 +1395,23 @@ fn create_background(config: &Config) -> Option<ApplicationWindow> {
             .default_width(100)
             .default_height(100)
             .build();
+        if config.blurred_background_margin().is_some() {
+            // placed like the main window, so it is centered around it.
+            background.init_layer_shell();
+            background.set_keyboard_mode(KeyboardMode::None);
+            for anchor in config.location().into_iter().flatten() {
+                background.set_anchor((&anchor).into(), true);
+            }
+        }
         if !config.normal_window() {
             background.set_layer(config.layer().into());
         }
         background.set_widget_name("background");
-        background.set_namespace(Some("worf"));
+        background.set_namespace(Some(&config.app_id()));
+        if config.blurred_background_margin().is_some() {
+            // sized by `window_show_resize`
+            return Some(background);
+        }
         background.connect_is_active_notify(move |window| {
             let Some(geometry) = get_monitor_geometry(window.surface().as_ref()) else {
                 return;
@@ -732,6 +1426,161 @@ fn create_background(config: &Config) -> Option<ApplicationWindow> {
     }
 }
 
+fn build_scrollbar(config: &Config, scroll: &ScrolledWindow) {
+    scroll.vscrollbar().set_widget_name("scrollbar");
+    scroll.hscrollbar().set_widget_name("scrollbar");
+
+    match config.scrollbar() {
+        ScrollbarMode::Auto => {
+            scroll.set_overlay_scrolling(false);
+            scroll.set_policy(PolicyType::Automatic, PolicyType::Automatic);
+        }
+        ScrollbarMode::Always => {
+            scroll.set_overlay_scrolling(false);
+            // only along the entries, the bar scrolls sideways
+            if config.is_bar() {
+                scroll.set_policy(PolicyType::Always, PolicyType::Automatic);
+            } else {
+                scroll.set_policy(PolicyType::Automatic, PolicyType::Always);
+            }
+        }
+        ScrollbarMode::Never => {
+            scroll.set_policy(PolicyType::External, PolicyType::External);
+        }
+        ScrollbarMode::Overlay => {
+            scroll.set_overlay_scrolling(true);
+            scroll.set_kinetic_scrolling(true);
+            scroll.set_policy(PolicyType::Automatic, PolicyType::Automatic);
+        }
+    }
+}
+
+/// Places the details panel and the preview next to or below the results,
+/// the results are returned unchanged if neither can ever be shown.
+fn build_details<T: Clone>(config: &Config, ui: &UiElements<T>, results: Widget) -> Widget {
+    let show_details = config.details() || config.key_toggle_details().is_some();
+    if !show_details && !config.preview() {
+        return results;
+    }
+
+    let side_box = gtk4::Box::new(Orientation::Vertical, 0);
+    if show_details {
+        ui.details.set_widget_name("details");
+        ui.details.set_column_spacing(10);
+        ui.details.set_visible(false);
+        side_box.append(&ui.details);
+    }
+    if config.preview() {
+        ui.preview.set_widget_name("preview");
+        ui.preview.set_xalign(0.0);
+        ui.preview.set_yalign(0.0);
+        ui.preview.set_wrap(true);
+        ui.preview.set_wrap_mode(gtk4::pango::WrapMode::WordChar);
+        ui.preview.set_visible(false);
+        side_box.append(&ui.preview);
+    }
+
+    let orientation = match config.details_position() {
+        DetailsPosition::Right => {
+            side_box.set_valign(Align::Start);
+            Orientation::Horizontal
+        }
+        DetailsPosition::Bottom => Orientation::Vertical,
+    };
+    let details_box = gtk4::Box::new(orientation, 0);
+    details_box.append(&results);
+    details_box.append(&side_box);
+    details_box.upcast()
+}
+
+fn setup_details_update<T: Clone + 'static + Send>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) {
+    let ui_clone = Rc::clone(ui);
+    let meta = Rc::clone(meta);
+    ui.main_box.connect_selected_children_changed(move |_| {
+        // the rows may be locked while the selection changes
+        let ui = Rc::clone(&ui_clone);
+        let meta = Rc::clone(&meta);
+        glib::idle_add_local_once(move || {
+            update_details(&ui, &meta);
+            update_preview(&ui, &meta);
+        });
+    });
+}
+
+/// Shows the details of the selected entry, see `ItemProvider::details`.
+/// The panel is hidden if it is disabled or the entry has no details.
+fn update_details<T: Clone>(ui: &UiElements<T>, meta: &MetaData<T>) {
+    while let Some(child) = ui.details.first_child() {
+        ui.details.remove(&child);
+    }
+
+    let selected = if ui.details_enabled.get() {
+        ui.main_box.selected_children().into_iter().next()
+    } else {
+        None
+    };
+    let details = selected
+        .and_then(|child| ui.menu_rows.read().unwrap().get(&child).cloned())
+        // skipped while the provider is busy, i.e. loading sub elements
+        .and_then(|item| meta.item_provider.try_lock().ok()?.details(&item))
+        .filter(|details| !details.is_empty());
+    let Some(details) = details else {
+        ui.details.set_visible(false);
+        return;
+    };
+
+    for (row, (name, value)) in details.iter().enumerate() {
+        let row = i32::try_from(row).unwrap_or(i32::MAX);
+        let name_label = Label::new(Some(name));
+        name_label.set_widget_name("detail-key");
+        name_label.set_xalign(0.0);
+        name_label.set_valign(Align::Start);
+        let value_label = Label::new(Some(value));
+        value_label.set_widget_name("detail-value");
+        value_label.set_xalign(0.0);
+        value_label.set_wrap(true);
+        ui.details.attach(&name_label, 0, row, 1, 1);
+        ui.details.attach(&value_label, 1, row, 1, 1);
+    }
+    ui.details.set_visible(true);
+}
+
+/// Shows the preview of the selected entry, see `ItemProvider::preview`.
+/// It is hidden if it is disabled or the entry has no preview.
+/// The preview is gathered on a worker thread, as it may read the file system,
+/// and dropped if the selection changed in the meantime.
+fn update_preview<T: Clone + Send + 'static>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) {
+    if !meta.config().preview() {
+        return;
+    }
+
+    let selected = ui.main_box.selected_children().into_iter().next();
+    let item = selected
+        .as_ref()
+        .and_then(|child| ui.menu_rows.read().unwrap().get(child).cloned());
+    let (Some(child), Some(item)) = (selected, item) else {
+        ui.preview.set_visible(false);
+        return;
+    };
+
+    let provider = Arc::clone(&meta.item_provider);
+    let handle = gio::spawn_blocking(move || provider.lock().unwrap().preview(&item));
+
+    let ui = Rc::clone(ui);
+    glib::spawn_future_local(async move {
+        let Ok(preview) = handle.await else {
+            log::error!("failed to get the preview");
+            return;
+        };
+        if ui.main_box.selected_children().first() != Some(&child) {
+            return;
+        }
+        let preview = preview.filter(|preview| !preview.is_empty());
+        ui.preview.set_text(preview.as_deref().unwrap_or_default());
+        ui.preview.set_visible(preview.is_some());
+    });
+}
+
 fn build_main_box<T: Clone + 'static>(config: &Config, ui_elements: &Rc<UiElements<T>>) {
     ui_elements.main_box.set_widget_name("inner-box");
     ui_elements.main_box.set_css_classes(&["inner-box"]);
@@ -744,27 +1593,29 @@ fn build_main_box<T: Clone + 'static>(config: &Config, ui_elements: &Rc<UiElemen
     ui_elements
         .main_box
         .set_max_children_per_line(config.columns());
+    ui_elements.main_box.set_row_spacing(config.row_spacing());
+    ui_elements
+        .main_box
+        .set_column_spacing(config.column_spacing());
     ui_elements.main_box.set_activate_on_single_click(true);
     ui_elements.main_box.set_halign(config.halign().into());
     ui_elements.main_box.set_valign(config.valign().into());
     if config.orientation() == config::Orientation::Horizontal {
         ui_elements.main_box.set_valign(Align::Center);
         ui_elements.main_box.set_orientation(Orientation::Vertical);
+    } else if config.list_direction() == ListDirection::Up {
+        ui_elements.main_box.set_valign(Align::End);
     } else {
         ui_elements.main_box.set_valign(Align::Start);
     }
     let ui_clone = Rc::clone(ui_elements);
+    let best_match = best_match_position(config);
     ui_elements.main_box.connect_map(move |fb| {
         fb.grab_focus();
         fb.invalidate_sort();
 
         let lock = ui_clone.menu_rows.read().unwrap();
-        select_visible_child(
-            &*lock,
-            &ui_clone.main_box,
-            &ui_clone.scroll,
-            &ChildPosition::Front,
-        );
+        select_visible_child(&*lock, &ui_clone.main_box, &ui_clone.scroll, &best_match);
     });
 }
 
@@ -779,6 +1630,7 @@ fn build_search_entry<T: Clone + Send + 'static>(
         .search
         .set_placeholder_text(Some(&config.prompt().unwrap_or("Search...".to_owned())));
     ui_elements.search.set_can_focus(false);
+    build_search_icons(config, &ui_elements.search);
     search_start_listen_delete_event(ui_elements, meta);
 
     if config.hide_search() {
@@ -789,8 +1641,38 @@ fn build_search_entry<T: Clone + Send + 'static>(
     }
 }
 
-fn search_start_listen_delete_event<T: Clone + Send + 'static>(
-    ui_elements: &Rc<UiElements<T>>,
+/// The search entry contains the magnifier icon and the clear button as images
+/// before and after the text. Clearing is handled by the delete event listener
+/// as it empties the text.
+fn build_search_icons(config: &Config, search: &SearchEntry) {
+    let mut first_image = true;
+    let mut child = search.first_child();
+    while let Some(c) = child {
+        if c.is::<Image>() {
+            if first_image {
+                c.set_widget_name("search-icon");
+                c.set_visible(config.search_icon());
+                if let Some(icon) = config.prompt_icon()
+                    && let Some(image) = c.downcast_ref::<Image>()
+                {
+                    if icon.starts_with('/') {
+                        image.set_from_file(Some(&icon));
+                    } else {
+                        image.set_icon_name(Some(&icon));
+                    }
+                }
+                first_image = false;
+            } else {
+                c.set_widget_name("search-clear");
+                c.set_visible(config.search_clear_button());
+            }
+        }
+        child = c.next_sibling();
+    }
+}
+
+fn search_start_listen_delete_event<T: Clone + Send + 'static>(
+    ui_elements: &Rc<UiElements<T>>,
     meta: &Rc<MetaData<T>>,
 ) {
     let ui_clone = Rc::clone(ui_elements);
@@ -811,8 +1693,128 @@ fn search_stop_listen_delete_event<T: Clone + Send + 'static>(ui_elements: &UiEl
     }
 }
 
-fn build_custom_key_view(custom_keys: &CustomKeys, outer_box: &gtk4::Box, inner_box: &gtk4::Box) {
-    fn create_label(inner_box: &FlowBox, text: &str, label_css: &str, box_css: &str) {
+/// Overwrites the search text before releasing it, so it does not linger in memory.
+fn clear_search_text_secure<T: Clone>(ui: &UiElements<T>) {
+    let mut text = ui.search_text.lock().unwrap();
+    let len = text.len();
+    text.clear();
+    // the capacity is at least len, so this overwrites the previous content in place.
+    text.extend(std::iter::repeat_n('\0', len));
+    std::hint::black_box(&*text);
+    text.clear();
+    ui.search.set_text("");
+}
+
+fn build_search_overlay(label: &Label) {
+    label.set_widget_name("search-overlay");
+    label.set_css_classes(&["search-overlay"]);
+    label.set_halign(Align::End);
+    label.set_valign(Align::Start);
+    label.set_can_target(false);
+    label.set_visible(false);
+}
+
+fn build_message_label(label: &Label) {
+    label.set_widget_name("message");
+    label.set_css_classes(&["message"]);
+    label.set_wrap(true);
+    label.set_xalign(0.0);
+    label.set_visible(false);
+}
+
+/// Action triggered by clicking an entry of the key hint bar
+#[derive(Clone)]
+enum KeyHintAction {
+    Custom(KeyBinding),
+    Submit,
+    Exit,
+    Expand,
+    Copy,
+}
+
+/// Returns the hints for the built-in key bindings, if enabled by `key_hints`
+fn builtin_key_hints(config: &Config) -> Vec<(String, KeyHintAction)> {
+    if !config.key_hints() {
+        return Vec::new();
+    }
+
+    let mut hints = vec![
+        (
+            format!("<b>{}</b> Submit", config.key_submit()),
+            KeyHintAction::Submit,
+        ),
+        (
+            format!("<b>{}</b> Expand", config.key_expand()),
+            KeyHintAction::Expand,
+        ),
+    ];
+    if let Some(key) = config.key_copy() {
+        hints.push((format!("<b>{key}</b> Copy"), KeyHintAction::Copy));
+    }
+    hints.push((
+        format!("<b>{}</b> Exit", config.key_exit()),
+        KeyHintAction::Exit,
+    ));
+    hints
+}
+
+fn handle_key_hint_click<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>, action: &KeyHintAction)
+where
+    T: Clone + Send + 'static,
+{
+    let no_modifier = HashSet::from([Modifier::None]);
+    match action {
+        KeyHintAction::Custom(binding) => {
+            let query = ui.search_text.lock().unwrap().clone();
+            if let Err(e) = handle_selected_item(
+                ui,
+                meta,
+                Some(&query),
+                None,
+                Some(binding),
+                SubmitSource::Click,
+                &binding.modifiers,
+            ) {
+                log::error!("{e}");
+            }
+        }
+        KeyHintAction::Submit => {
+            let query = ui.search_text.lock().unwrap().clone();
+            if let Err(e) = handle_selected_item(
+                ui,
+                meta,
+                Some(&query),
+                None,
+                None,
+                SubmitSource::Click,
+                &no_modifier,
+            ) {
+                log::error!("{e}");
+            }
+        }
+        KeyHintAction::Exit => {
+            handle_key_exit(ui, meta);
+        }
+        KeyHintAction::Expand => {
+            handle_key_expand(ui, meta, &no_modifier);
+        }
+        KeyHintAction::Copy => {
+            handle_key_copy(ui, meta);
+        }
+    }
+}
+
+fn build_custom_key_view<T: Clone + Send + 'static>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    custom_keys: Option<&CustomKeys>,
+) {
+    fn create_label(
+        inner_box: &FlowBox,
+        text: &str,
+        label_css: &str,
+        box_css: &str,
+    ) -> FlowBoxChild {
         let label_box = FlowBoxChild::new();
         label_box.set_halign(Align::Fill);
         inner_box.set_valign(Align::Start);
@@ -830,8 +1832,25 @@ fn build_custom_key_view(custom_keys: &CustomKeys, outer_box: &gtk4::Box, inner_
         label.set_wrap(false);
         label.set_xalign(0.0);
         label_box.set_child(Some(&label));
+        label_box
+    }
+
+    let hint = custom_keys.and_then(|custom_keys| custom_keys.hint.as_ref());
+    // the labels of custom keys are only shown together with their hint,
+    // the built-in keys are opted in by `key_hints`.
+    let mut key_hints: Vec<(String, KeyHintAction)> = custom_keys
+        .iter()
+        .filter(|custom_keys| custom_keys.hint.is_some())
+        .flat_map(|custom_keys| custom_keys.bindings.iter())
+        .filter(|key| key.visible)
+        .map(|key| (key.label.clone(), KeyHintAction::Custom(key.clone())))
+        .collect();
+    key_hints.extend(builtin_key_hints(&meta.config()));
+    if key_hints.is_empty() && hint.is_none() {
+        return;
     }
 
+    let inner_box = &ui.custom_key_box;
     inner_box.set_halign(Align::Fill);
 
     let hint_box = FlowBox::new();
@@ -844,17 +1863,28 @@ fn build_custom_key_view(custom_keys: &CustomKeys, outer_box: &gtk4::Box, inner_
     inner_box.append(&custom_key_box);
 
     let make_key_labels = || {
-        for key in custom_keys.bindings.iter().filter(|key| key.visible) {
-            create_label(
+        for (label, action) in &key_hints {
+            let label_box = create_label(
                 &custom_key_box,
-                key.label.as_ref(),
+                label,
                 "custom-key-label-text",
                 "custom-key-label-box",
             );
+
+            // clicking a hint does the same as pressing its key
+            let click = GestureClick::new();
+            click.set_button(gtk4::gdk::BUTTON_PRIMARY);
+            let ui = Rc::clone(ui);
+            let meta = Rc::clone(meta);
+            let action = action.clone();
+            click.connect_pressed(move |_, _, _, _| {
+                handle_key_hint_click(&ui, &meta, &action);
+            });
+            label_box.add_controller(click);
         }
     };
 
-    if let Some(hint) = custom_keys.hint.as_ref() {
+    if let Some(hint) = hint {
         match hint.location {
             CustomKeyHintLocation::Top => {
                 inner_box.append(&hint_box);
@@ -877,9 +1907,11 @@ fn build_custom_key_view(custom_keys: &CustomKeys, outer_box: &gtk4::Box, inner_
                 inner_box.append(&hint_box);
             }
         }
+    } else {
+        make_key_labels();
     }
 
-    outer_box.append(inner_box);
+    ui.outer_box.append(inner_box);
 }
 
 fn set_search_text<T: Clone + Send + 'static>(
@@ -890,7 +1922,7 @@ fn set_search_text<T: Clone + Send + 'static>(
     search_stop_listen_delete_event(ui);
     let mut lock = ui.search_text.lock().unwrap();
     query.clone_into(&mut lock);
-    if let Some(pw) = meta.config.read().unwrap().password() {
+    if let Some(pw) = meta.config().password() {
         let mut ui_text = String::new();
         for _ in 0..query.len() {
             ui_text += &pw;
@@ -899,6 +1931,10 @@ fn set_search_text<T: Clone + Send + 'static>(
     } else {
         ui.search.set_text(query);
     }
+    ui.search_overlay.set_text(&ui.search.text());
+    ui.search_overlay.set_visible(
+        meta.config().hidden_search_overlay() && !ui.search.is_visible() && !query.is_empty(),
+    );
     search_start_listen_delete_event(ui, meta);
 }
 
@@ -907,16 +1943,26 @@ fn build_ui_from_menu_items<T: Clone + 'static + Send>(
     meta: &Rc<MetaData<T>>,
     mut items: Vec<MenuItem<T>>,
 ) {
-    if meta.config.read().unwrap().sort_order() != SortOrder::Default {
+    if meta.config().sort_order() != SortOrder::Default {
         items.reverse();
     }
+    ui.items_generation.set(ui.items_generation.get() + 1);
     let start = Instant::now();
     {
-        while let Some(b) = ui.main_box.child_at_index(0) {
-            ui.main_box.remove(&b);
-            drop(b);
+        // rows without sub elements are kept hidden and reused for items looking the same,
+        // refreshing providers mostly return the same items again.
+        let mut pool: Vec<(FlowBoxChild, MenuItem<T>)> = Vec::new();
+        for (child, menu) in ui.menu_rows.write().unwrap().drain() {
+            if menu.sub_elements.is_empty() {
+                child.set_visible(false);
+                child.remove_css_class("suggestion");
+                child.remove_css_class("loading");
+                child.remove_css_class("activated");
+                pool.push((child, menu));
+            } else {
+                ui.main_box.remove(&child);
+            }
         }
-        ui.menu_rows.write().unwrap().clear();
 
         let meta_clone = Rc::<MetaData<T>>::clone(meta);
         let ui_clone = Rc::<UiElements<T>>::clone(ui);
@@ -929,24 +1975,40 @@ fn build_ui_from_menu_items<T: Clone + 'static + Send>(
 
                 for _ in 0..25 {
                     if let Some(item) = items.pop() {
-                        lock.insert(add_menu_item(&ui_clone, &meta_clone, &item), item);
+                        let child = if let Some(index) =
+                            pool.iter().position(|(_, menu)| can_reuse_row(menu, &item))
+                        {
+                            let (child, menu) = pool.swap_remove(index);
+                            update_row_widgets(&child, &menu, &item, &meta_clone.config());
+                            child
+                        } else {
+                            add_menu_item(&ui_clone, &meta_clone, &item)
+                        };
+                        lock.insert(child, item);
                     } else {
                         done = true;
                     }
                 }
+                if done {
+                    for (child, _) in pool.drain(..) {
+                        ui_clone.main_box.remove(&child);
+                    }
+                }
 
                 let search_lock = ui_clone.search_text.lock().unwrap();
                 let menus = &mut *lock;
                 set_menu_visibility_for_search(
                     &search_lock,
                     menus,
-                    &meta_clone.config,
+                    &meta_clone.config(),
                     meta_clone.search_ignored_words.as_ref(),
                 );
             }
             let items_sort = ArcMenuMap::clone(&ui_clone.menu_rows);
+            let reverse = meta_clone.config().list_direction() == ListDirection::Up;
             ui_clone.main_box.set_sort_func(move |child1, child2| {
-                sort_flow_box_childs(child1, child2, &items_sort)
+                let order = sort_flow_box_childs(child1, child2, &items_sort);
+                if reverse { reverse_order(order) } else { order }
             });
 
             if done {
@@ -956,7 +2018,7 @@ fn build_ui_from_menu_items<T: Clone + 'static + Send>(
                     &*lock,
                     &ui_clone.main_box,
                     &ui_clone.scroll,
-                    &ChildPosition::Front,
+                    &best_match_position(&meta_clone.config()),
                 );
 
                 log::debug!(
@@ -973,6 +2035,103 @@ fn build_ui_from_menu_items<T: Clone + 'static + Send>(
     }
 }
 
+/// Appends the batches streamed by the provider, see `ItemProvider::get_elements_stream`.
+fn receive_streamed_items<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    receiver: channel::Receiver<Vec<MenuItem<T>>>,
+) {
+    let generation = ui.items_generation.get();
+    let ui = Rc::clone(ui);
+    let meta = Rc::clone(meta);
+    glib::spawn_future_local(async move {
+        let mut streamed = false;
+        loop {
+            let receiver = receiver.clone();
+            let Ok(Ok(items)) = gio::spawn_blocking(move || receiver.recv()).await else {
+                break;
+            };
+            if !accept_streamed_items(&ui, &meta, generation, items) {
+                return;
+            }
+            streamed = true;
+        }
+        if streamed {
+            show_streamed_result(&ui, &meta, generation);
+        }
+    });
+}
+
+/// Replaces the streamed batches with the complete items of the provider,
+/// unless they have been replaced or the menu was submitted meanwhile.
+fn show_streamed_result<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    generation: u64,
+) {
+    if ui.items_generation.get() != generation || ui.submit_guard.is_submitted() {
+        return;
+    }
+    let Some(mut provider) = meta.try_provider() else {
+        log::debug!("provider busy, keeping the streamed items");
+        return;
+    };
+    let data = provider.get_elements(None);
+    meta.expires_in.set(provider.expires_in());
+    drop(provider);
+
+    if apply_provider_data(ui, meta, data) {
+        let query = ui.search_text.lock().unwrap().clone();
+        update_view(ui, meta, &query);
+    }
+}
+
+/// Appends a streamed batch, unless the items of the given generation were replaced
+/// or the menu was submitted meanwhile. Returns false once the stream is discarded.
+fn accept_streamed_items<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    generation: u64,
+    items: Vec<MenuItem<T>>,
+) -> bool {
+    if ui.items_generation.get() != generation || ui.submit_guard.is_submitted() {
+        log::debug!("discarding {} streamed items", items.len());
+        return false;
+    }
+    append_menu_items(ui, meta, items);
+    true
+}
+
+/// Adds rows for the items to the shown ones, which are kept as they are.
+fn append_menu_items<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    items: Vec<MenuItem<T>>,
+) {
+    let mut lock = ui.menu_rows.write().unwrap();
+    for item in items {
+        let child = add_menu_item(ui, meta, &item);
+        lock.insert(child, item);
+    }
+
+    let search = ui.search_text.lock().unwrap().clone();
+    set_menu_visibility_for_search(
+        &search,
+        &mut *lock,
+        &meta.config(),
+        meta.search_ignored_words.as_ref(),
+    );
+    ui.main_box.invalidate_sort();
+    if ui.main_box.selected_children().is_empty() {
+        select_visible_child(
+            &*lock,
+            &ui.main_box,
+            &ui.scroll,
+            &best_match_position(&meta.config()),
+        );
+    }
+}
+
 fn setup_key_event_handler<T: Clone + 'static + Send>(
     ui_elements: &Rc<UiElements<T>>,
     meta: &Rc<MetaData<T>>,
@@ -1002,23 +2161,173 @@ fn setup_key_event_handler<T: Clone + 'static + Send>(
     connect_key_handler(&ui_elements.window, ui_elements, meta, custom_keys.cloned());
 }
 
+/// The configured app id if it can be used as id of the gtk application,
+/// which requires at least one dot, i.e. `org.example.Prompt`.
+/// Without one the program name is used, the layer shell namespace is set in any case.
+fn application_id(config: &Config) -> Option<String> {
+    Some(config.app_id()).filter(|app_id| gio::Application::id_is_valid(app_id))
+}
+
+/// Mouse buttons which can be bound, the primary one selects and submits entries.
+fn mouse_button_key(button: u32) -> Option<Key> {
+    match button {
+        gtk4::gdk::BUTTON_MIDDLE => Some(Key::MouseMiddle),
+        gtk4::gdk::BUTTON_SECONDARY => Some(Key::MouseRight),
+        8 => Some(Key::MouseBack),
+        9 => Some(Key::MouseForward),
+        _ => None,
+    }
+}
+
+/// Seconds a flick keeps moving the selection with `scroll_selection`,
+/// the velocity of kinetic scrolling is given in scroll steps per second.
+const KINETIC_SCROLL_DURATION: f64 = 0.15;
+
+/// Handles mouse buttons and scrolling bound via the same options as keys,
+/// i.e. `mouse_copy`, custom keys or the provider's `handle_search_key`.
+fn setup_mouse_event_handler<T: Clone + 'static + Send>(
+    config: &Config,
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    custom_keys: Option<&CustomKeys>,
+) {
+    let click = GestureClick::new();
+    click.set_button(0);
+    let click_ui = Rc::clone(ui);
+    let click_meta = Rc::clone(meta);
+    let click_keys = custom_keys.cloned();
+    click.connect_pressed(move |gesture, _, x, y| {
+        let Some(key) = mouse_button_key(gesture.current_button()) else {
+            return;
+        };
+        // the binding applies to the entry below the pointer
+        if let Some(child) = click_ui
+            .window
+            .pick(x, y, PickFlags::DEFAULT)
+            .and_then(|widget| widget.ancestor(FlowBoxChild::static_type()))
+            .and_then(|widget| widget.downcast::<FlowBoxChild>().ok())
+        {
+            click_ui.main_box.select_child(&child);
+        }
+        handle_mouse_key(
+            &click_ui,
+            &click_meta,
+            key,
+            &modifiers_from_mask(gesture.current_event_state()),
+            click_keys.as_ref(),
+        );
+    });
+    ui.window.add_controller(click);
+
+    // runs before the scrolled window, so scrolling without a binding still scrolls the list
+    let scroll_selection = config.scroll_selection();
+    let mut flags = EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE;
+    if scroll_selection {
+        flags |= EventControllerScrollFlags::KINETIC;
+    }
+    let scroll = EventControllerScroll::new(flags);
+    scroll.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    let scroll_ui = Rc::clone(ui);
+    let scroll_meta = Rc::clone(meta);
+    let scroll_keys = custom_keys.cloned();
+    scroll.connect_scroll(move |controller, _, dy| {
+        let key = if dy < 0.0 {
+            Key::ScrollUp
+        } else if dy > 0.0 {
+            Key::ScrollDown
+        } else {
+            return Propagation::Proceed;
+        };
+        let propagate = handle_mouse_key(
+            &scroll_ui,
+            &scroll_meta,
+            key,
+            &modifiers_from_mask(controller.current_event_state()),
+            scroll_keys.as_ref(),
+        );
+        if propagate == Propagation::Proceed && scroll_selection {
+            scroll_selection_by(&scroll_ui, dy);
+            return Propagation::Stop;
+        }
+        propagate
+    });
+    if scroll_selection {
+        let decelerate_ui = Rc::clone(ui);
+        scroll.connect_decelerate(move |_, _, velocity| {
+            // keep moving as far as the flick would have scrolled in a short moment
+            scroll_selection_by(&decelerate_ui, velocity * KINETIC_SCROLL_DURATION);
+        });
+    }
+    ui.window.add_controller(scroll);
+}
+
+/// Moves the selection by the given amount of discrete scroll steps,
+/// at most by the rows fitting into the list.
+fn scroll_selection_by<T: Clone + Send + 'static>(ui: &Rc<UiElements<T>>, steps: f64) {
+    let page = ui
+        .main_box
+        .selected_children()
+        .first()
+        .map_or(1, |selected| {
+            (ui.scroll.height() / selected.height().max(1)).max(1)
+        });
+    // truncation is intended, the steps are clamped to the page size
+    #[allow(clippy::cast_possible_truncation)]
+    let offset = steps.trunc().clamp(-f64::from(page), f64::from(page)) as i32;
+    if offset != 0 {
+        move_grid_selection(ui, offset);
+    }
+}
+
+fn handle_mouse_key<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    key: Key,
+    mods: &HashSet<Modifier>,
+    custom_keys: Option<&CustomKeys>,
+) -> Propagation {
+    if ui.submit_guard.is_submitted() {
+        return Propagation::Stop;
+    }
+
+    let propagate = handle_custom_keys(
+        ui,
+        meta,
+        &|bound| bound == key,
+        mods,
+        custom_keys,
+        SubmitSource::Click,
+    );
+    if propagate == Propagation::Stop {
+        return propagate;
+    }
+    handle_provider_search_key(ui, meta, key, mods)
+}
+
 fn is_key_match(
-    key_opt: Option<Key>,
+    key: Key,
     key_detection_type: &KeyDetectionType,
     key_code: u32,
     gdk_key: gdk4::Key,
 ) -> bool {
-    if let Some(key) = key_opt {
-        if key_detection_type == &KeyDetectionType::Code {
-            key == key_code.into()
-        } else {
-            key == gdk_key.to_upper().into()
-        }
+    if key_detection_type == &KeyDetectionType::Code {
+        key == key_code.into()
     } else {
-        false
+        key == gdk_key.to_upper().into()
     }
 }
 
+/// Checks the key via `is_match` and the held modifiers against the binding.
+fn is_binding_match(
+    spec: Option<&KeyBindingSpec>,
+    mods: &HashSet<Modifier>,
+    is_match: &dyn Fn(Key) -> bool,
+) -> bool {
+    spec.is_some_and(|spec| {
+        spec.key != Key::None && is_match(spec.key) && spec.matches_modifiers(mods)
+    })
+}
+
 fn handle_key_press<T: Clone + 'static + Send>(
     ui: &Rc<UiElements<T>>,
     meta: &Rc<MetaData<T>>,
@@ -1027,16 +2336,56 @@ fn handle_key_press<T: Clone + 'static + Send>(
     modifier_type: gdk4::ModifierType,
     custom_keys: Option<&CustomKeys>,
 ) -> Propagation {
-    log::debug!("received key. code: {key_code}, key: {keyboard_key:?}");
+    if !meta.config().secure_input() {
+        log::debug!("received key. code: {key_code}, key: {keyboard_key:?}");
+    }
 
-    let propagate =
-        handle_custom_keys(ui, meta, keyboard_key, key_code, modifier_type, custom_keys);
+    // the window may still be open to show the activation feedback
+    if ui.submit_guard.is_submitted() {
+        return Propagation::Stop;
+    }
+
+    let detection_type = meta.config().key_detection_type();
+    let mods = modifiers_from_mask(modifier_type);
+    let propagate = handle_custom_keys(
+        ui,
+        meta,
+        &|key| is_key_match(key, &detection_type, key_code, keyboard_key),
+        &mods,
+        custom_keys,
+        SubmitSource::Keyboard,
+    );
 
     if propagate == Propagation::Stop {
         return propagate;
     }
 
+    let keyboard_key = navigation_key(&meta.config(), &mods, &|key| {
+        is_key_match(key, &detection_type, key_code, keyboard_key)
+    })
+    .unwrap_or(keyboard_key);
+
+    if ui.focus.get() != Focus::List
+        && handle_provider_search_key(ui, meta, keyboard_key.into(), &mods) == Propagation::Stop
+    {
+        return Propagation::Stop;
+    }
+
+    let grid = grid_columns(&meta.config());
+    let list_focused = ui.focus.get() == Focus::List;
     match keyboard_key {
+        // the search is not edited while the list has the focus
+        gdk4::Key::BackSpace | gdk4::Key::Delete if list_focused => return Propagation::Stop,
+        gdk4::Key::Home | gdk4::Key::End => {
+            let position = if keyboard_key == gdk4::Key::Home {
+                ChildPosition::Front
+            } else {
+                ChildPosition::Back
+            };
+            let lock = ui.menu_rows.read().unwrap();
+            select_visible_child(&*lock, &ui.main_box, &ui.scroll, &position);
+            return Propagation::Stop;
+        }
         gdk4::Key::BackSpace | gdk4::Key::Delete => {
             let mut query = {
                 let search_text = ui.search_text.lock().unwrap();
@@ -1060,13 +2409,29 @@ fn handle_key_press<T: Clone + 'static + Send>(
                 update_view_from_provider(ui, meta, &query);
             }
         }
-        gdk4::Key::Home => {
-            ui.search.set_position(0);
+        gdk4::Key::Left | gdk4::Key::Right if grid.is_some() => {
+            return handle_key_horizontal(ui, keyboard_key == gdk4::Key::Right, modifier_type);
         }
-        gdk4::Key::End => {
-            if let Ok(i) = i32::try_from(ui.search_text.lock().unwrap().len() + 1) {
-                ui.search.set_position(i);
-            }
+        gdk4::Key::Up | gdk4::Key::Down if grid.is_some() && !has_expanded_selection(ui) => {
+            let columns = grid.unwrap_or(1);
+            let (offset, direction) = if keyboard_key == gdk4::Key::Up {
+                (-columns, Direction::Up)
+            } else {
+                (columns, Direction::Down)
+            };
+            return if move_grid_selection(ui, offset) {
+                Propagation::Stop
+            } else {
+                move_selection(ui, meta, &direction)
+            };
+        }
+        gdk4::Key::Page_Up | gdk4::Key::Page_Down => {
+            let direction = if keyboard_key == gdk4::Key::Page_Up {
+                Direction::Up
+            } else {
+                Direction::Down
+            };
+            return move_selection_page(ui, &meta.config(), &direction);
         }
         gdk4::Key::Up | gdk4::Key::Left => {
             return move_selection(ui, meta, &Direction::Up);
@@ -1074,6 +2439,16 @@ fn handle_key_press<T: Clone + 'static + Send>(
         gdk4::Key::Down | gdk4::Key::Right => {
             return move_selection(ui, meta, &Direction::Down);
         }
+        _ if list_focused
+            || (!ui.search.is_visible()
+                && meta.config().hidden_search_input() == HiddenSearchInput::QuickJump) =>
+        {
+            if let Some(c) = keyboard_key.to_unicode()
+                && c.is_alphanumeric()
+            {
+                return quick_jump(ui, c);
+            }
+        }
         _ => {
             if let Some(c) = keyboard_key.to_unicode() {
                 let mut query = {
@@ -1098,6 +2473,122 @@ fn handle_key_press<T: Clone + 'static + Send>(
     Propagation::Proceed
 }
 
+/// The arrow, page or home key the pressed key is configured as, i.e. `Down` for `Ctrl+j`.
+fn navigation_key(
+    config: &Config,
+    mods: &HashSet<Modifier>,
+    is_match: &dyn Fn(Key) -> bool,
+) -> Option<gdk4::Key> {
+    [
+        (config.key_up(), gdk4::Key::Up),
+        (config.key_down(), gdk4::Key::Down),
+        (config.key_pgup(), gdk4::Key::Page_Up),
+        (config.key_pgdn(), gdk4::Key::Page_Down),
+        (config.key_home(), gdk4::Key::Home),
+        (config.key_end(), gdk4::Key::End),
+    ]
+    .into_iter()
+    .find(|(spec, _)| is_binding_match(spec.as_ref(), mods, is_match))
+    .map(|(_, key)| key)
+}
+
+/// Moves the selection by as many rows as fit into the list,
+/// or to the first or last entry if there are fewer left.
+fn move_selection_page<T: Clone + Send + 'static>(
+    ui: &Rc<UiElements<T>>,
+    config: &Config,
+    direction: &Direction,
+) -> Propagation {
+    let Some(selected) = ui.main_box.selected_children().into_iter().next() else {
+        return Propagation::Proceed;
+    };
+    let rows = (ui.scroll.height() / selected.height().max(1)).max(1);
+    let offset = rows * grid_columns(config).unwrap_or(1);
+    let offset = if *direction == Direction::Up {
+        -offset
+    } else {
+        offset
+    };
+
+    if !move_grid_selection(ui, offset) {
+        let position = if *direction == Direction::Up {
+            ChildPosition::Front
+        } else {
+            ChildPosition::Back
+        };
+        let lock = ui.menu_rows.read().unwrap();
+        select_visible_child(&*lock, &ui.main_box, &ui.scroll, &position);
+    }
+    Propagation::Stop
+}
+
+/// Returns the number of columns if the items are laid out in a grid
+fn grid_columns(config: &Config) -> Option<i32> {
+    let columns = i32::try_from(config.columns()).unwrap_or(i32::MAX);
+    (columns > 1 && config.orientation() == config::Orientation::Vertical).then_some(columns)
+}
+
+/// Left and right move the search cursor until it reaches the end of the text,
+/// then they move the selection to the neighbouring column.
+/// With Alt held the selection is moved right away.
+fn handle_key_horizontal<T: Clone + Send + 'static>(
+    ui: &Rc<UiElements<T>>,
+    forward: bool,
+    modifier_type: gdk4::ModifierType,
+) -> Propagation {
+    let pos = ui.search.position();
+    let len = i32::try_from(ui.search_text.lock().unwrap().chars().count()).unwrap_or(i32::MAX);
+    let at_edge = if forward { pos >= len } else { pos <= 0 };
+
+    if at_edge || modifier_type.contains(gdk4::ModifierType::ALT_MASK) {
+        move_grid_selection(ui, if forward { 1 } else { -1 });
+    } else {
+        ui.search
+            .set_position(if forward { pos + 1 } else { pos - 1 });
+    }
+    Propagation::Stop
+}
+
+fn has_expanded_selection<T: Clone + Send + 'static>(ui: &Rc<UiElements<T>>) -> bool {
+    ui.main_box
+        .selected_children()
+        .first()
+        .is_some_and(|selected| {
+            selected
+                .child()
+                .and_then(|child| child.downcast::<Expander>().ok())
+                .is_some_and(|expander| expander.is_expanded())
+        })
+}
+
+/// Moves the selection by `offset` visible items.
+/// Returns false if there is no item at the new position.
+fn move_grid_selection<T: Clone + Send + 'static>(ui: &Rc<UiElements<T>>, offset: i32) -> bool {
+    let Some(selected) = ui.main_box.selected_children().into_iter().next() else {
+        return false;
+    };
+
+    let visible: Vec<FlowBoxChild> = (0..)
+        .map_while(|i| ui.main_box.child_at_index(i))
+        .filter(WidgetExt::is_visible)
+        .collect();
+    let target = visible
+        .iter()
+        .position(|child| *child == selected)
+        .and_then(|current| i32::try_from(current).ok())
+        .and_then(|current| usize::try_from(current + offset).ok())
+        .and_then(|target| visible.get(target));
+
+    if let Some(child) = target {
+        ui.main_box.select_child(child);
+        child.grab_focus();
+        scroll_to_child(&ui.scroll, child);
+        true
+    } else {
+        false
+    }
+}
+
 #[derive(PartialEq)]
 enum Direction {
     Up,
@@ -1109,13 +2600,13 @@ fn move_selection<T: Clone + Send + 'static>(
     meta: &Rc<MetaData<T>>,
     direction: &Direction,
 ) -> Propagation {
-    if !meta.config.read().unwrap().rollover() {
-        return Propagation::Proceed;
-    }
-
     let selected_children = ui.main_box.selected_children();
     let Some(selected) = selected_children.first() else {
-        return Propagation::Proceed;
+        // i.e. suggestions, which are not selected until picked with the arrow keys
+        let lock = ui.menu_rows.read().unwrap();
+        let best_match = best_match_position(&meta.config());
+        select_visible_child(&*lock, &ui.main_box, &ui.scroll, &best_match);
+        return Propagation::Stop;
     };
 
     // If the selected FlowBoxChild contains an expanded Expander and one of its
@@ -1206,114 +2697,563 @@ fn move_selection<T: Clone + Send + 'static>(
         return Propagation::Proceed;
     };
 
-    if *direction == Direction::Up && first_child == *selected && visible_items_count > 1 {
-        select_visible_child(
-            &ui.menu_rows.read().unwrap(),
-            &ui.main_box,
-            &ui.scroll,
-            &ChildPosition::Back,
-        );
-        Propagation::Stop
-    } else if *direction == Direction::Down && last_child == *selected && visible_items_count > 1 {
+    let at_start = *direction == Direction::Up && first_child == *selected;
+    let at_end = *direction == Direction::Down && last_child == *selected;
+    if !at_start && !at_end {
+        return Propagation::Proceed;
+    }
+
+    if meta.config().rollover() && visible_items_count > 1 {
         select_visible_child(
             &ui.menu_rows.read().unwrap(),
             &ui.main_box,
             &ui.scroll,
-            &ChildPosition::Front,
+            if at_start {
+                &ChildPosition::Back
+            } else {
+                &ChildPosition::Front
+            },
         );
-        Propagation::Stop
     } else {
-        Propagation::Proceed
+        // keep the selection pinned instead of letting gtk move the focus out of the list
+        scroll_to_child(&ui.scroll, selected);
     }
+    Propagation::Stop
 }
 
 fn handle_custom_keys<T: Clone + 'static + Send>(
     ui: &Rc<UiElements<T>>,
     meta: &Rc<MetaData<T>>,
-    keyboard_key: gdk4::Key,
-    key_code: u32,
-    modifier_type: gdk4::ModifierType,
+    is_match: &dyn Fn(Key) -> bool,
+    mods: &HashSet<Modifier>,
     custom_keys: Option<&CustomKeys>,
+    source: SubmitSource,
 ) -> Propagation {
-    let detection_type = meta.config.read().unwrap().key_detection_type();
+    let secure_input = meta.config().secure_input();
     if let Some(custom_keys) = custom_keys {
-        let mods = modifiers_from_mask(modifier_type);
         for custom_key in &custom_keys.bindings {
-            let custom_key_match = if detection_type == KeyDetectionType::Code {
-                custom_key.key == key_code.into()
-            } else {
-                custom_key.key == keyboard_key.to_upper().into()
-            } && mods.is_subset(&custom_key.modifiers);
+            let custom_key_match =
+                is_match(custom_key.key) && custom_key.spec().matches_modifiers(mods);
 
-            log::debug!("custom key {custom_key:?}, match {custom_key_match}");
+            if !secure_input {
+                log::debug!("custom key {custom_key:?}, match {custom_key_match}");
+            }
 
             if custom_key_match {
                 let search_lock = ui.search_text.lock().unwrap();
-                if let Err(e) =
-                    handle_selected_item(ui, meta, Some(&search_lock), None, Some(custom_key))
-                {
+                if let Err(e) = handle_selected_item(
+                    ui,
+                    meta,
+                    Some(&search_lock),
+                    None,
+                    Some(custom_key),
+                    source,
+                    mods,
+                ) {
                     log::error!("{e}");
                 }
+                // the key must not trigger another binding or the submit key
+                return Propagation::Stop;
             }
         }
     }
 
-    // hide search
-    if is_key_match(
-        meta.config.read().unwrap().key_hide_search(),
-        &detection_type,
-        key_code,
-        keyboard_key,
-    ) {
-        handle_key_hide_search(ui)
-    // submit
-    } else if is_key_match(
-        Some(meta.config.read().unwrap().key_submit()),
-        &detection_type,
-        key_code,
-        keyboard_key,
-    ) {
-        handle_key_submit(ui, meta)
-    // exit
-    } else if is_key_match(
-        Some(meta.config.read().unwrap().key_exit()),
-        &detection_type,
-        key_code,
-        keyboard_key,
-    ) {
-        handle_key_exit(ui, meta)
-    // copy
-    } else if is_key_match(
-        meta.config.read().unwrap().key_copy(),
-        &detection_type,
-        key_code,
-        keyboard_key,
-    ) {
-        handle_key_copy(ui, meta)
-    // expand
-    } else if is_key_match(
-        Some(meta.config.read().unwrap().key_expand()),
-        &detection_type,
-        key_code,
-        keyboard_key,
-    ) {
-        handle_key_expand(ui, meta)
-    } else {
-        Propagation::Proceed
+    match built_in_action(&meta.config(), is_match, mods) {
+        Some(BuiltInAction::HideSearch) => handle_key_hide_search(ui),
+        Some(BuiltInAction::ToggleFocus) => {
+            set_focus(
+                ui,
+                if ui.focus.get() == Focus::Search {
+                    Focus::List
+                } else {
+                    Focus::Search
+                },
+            );
+            Propagation::Stop
+        }
+        Some(BuiltInAction::ToggleDetails) => {
+            ui.details_enabled.set(!ui.details_enabled.get());
+            update_details(ui, meta);
+            Propagation::Stop
+        }
+        Some(BuiltInAction::Submit) => handle_key_submit(ui, meta, mods),
+        Some(BuiltInAction::Exit) => handle_key_exit(ui, meta),
+        Some(BuiltInAction::Copy) => handle_key_copy(ui, meta),
+        Some(BuiltInAction::Expand) => handle_key_expand(ui, meta, mods),
+        Some(BuiltInAction::SwitchMode) => handle_key_switch_mode(ui, meta),
+        None => Propagation::Proceed,
     }
 }
 
-fn update_view_from_provider<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>, query: &str)
+#[derive(Clone, Copy)]
+enum BuiltInAction {
+    HideSearch,
+    ToggleFocus,
+    ToggleDetails,
+    Submit,
+    Exit,
+    Copy,
+    Expand,
+    SwitchMode,
+}
+
+/// The built-in binding triggered by the key. Bindings with modifiers are checked first,
+/// so i.e. `Ctrl+Enter` is not taken by the bare `Enter` of `key_submit`.
+fn built_in_action(
+    config: &Config,
+    is_match: &dyn Fn(Key) -> bool,
+    mods: &HashSet<Modifier>,
+) -> Option<BuiltInAction> {
+    let bindings = [
+        (config.key_hide_search(), BuiltInAction::HideSearch),
+        (config.key_toggle_focus(), BuiltInAction::ToggleFocus),
+        (config.key_toggle_details(), BuiltInAction::ToggleDetails),
+        (Some(config.key_submit()), BuiltInAction::Submit),
+        (Some(config.key_exit()), BuiltInAction::Exit),
+        (config.key_copy(), BuiltInAction::Copy),
+        (config.mouse_copy(), BuiltInAction::Copy),
+        (Some(config.key_expand()), BuiltInAction::Expand),
+        (config.mouse_expand(), BuiltInAction::Expand),
+        (config.key_switch_mode(), BuiltInAction::SwitchMode),
+    ];
+    let with_modifiers =
+        |spec: &Option<KeyBindingSpec>| spec.as_ref().is_some_and(KeyBindingSpec::has_modifiers);
+    bindings
+        .iter()
+        .filter(|(spec, _)| with_modifiers(spec))
+        .chain(bindings.iter().filter(|(spec, _)| !with_modifiers(spec)))
+        .find(|(spec, _)| is_binding_match(spec.as_ref(), mods, is_match))
+        .map(|(_, action)| *action)
+}
+
+/// Lets the provider edit the search for the key, see `ItemProvider::handle_search_key`.
+fn handle_provider_search_key<T: Clone + 'static + Send>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    key: Key,
+    mods: &HashSet<Modifier>,
+) -> Propagation {
+    let search = ui.search_text.lock().unwrap().clone();
+    let cursor = usize::try_from(ui.search.position()).unwrap_or(0);
+    let search_edit = meta
+        .try_provider()
+        .and_then(|mut provider| provider.handle_search_key(key, mods, &search, cursor));
+
+    if let Some(edit) = search_edit {
+        set_search_text(ui, meta, &edit.text);
+        ui.search
+            .set_position(i32::try_from(edit.cursor).unwrap_or(i32::MAX));
+        update_view_from_provider(ui, meta, &edit.text);
+        Propagation::Stop
+    } else {
+        Propagation::Proceed
+    }
+}
+
+fn update_view_from_provider<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>, query: &str)
 where
     T: Clone + Send + 'static,
 {
-    let data = meta.item_provider.lock().unwrap().get_elements(Some(query));
-    if let Some(filtered_list) = data.items {
-        build_ui_from_menu_items(ui, meta, filtered_list);
-    }
+    let data = provider_elements(meta, query);
+    apply_provider_data(ui, meta, data);
     update_view(ui, meta, query);
 }
 
+/// Asks the provider for the entries of the query,
+/// there are none until the query has `min_query_length` characters.
+fn provider_elements<T>(meta: &MetaData<T>, query: &str) -> ProviderData<T>
+where
+    T: Clone + Send + 'static,
+{
+    if query.chars().count() < meta.config().min_query_length() {
+        meta.held_back.set(true);
+        return ProviderData::Items(Vec::new());
+    }
+
+    let Some(mut provider) = meta.try_provider() else {
+        // the shown items are filtered until the provider is available again
+        meta.pending_query.set(true);
+        return ProviderData::Unchanged;
+    };
+    meta.pending_query.set(false);
+    let mut data = provider.get_elements(Some(query));
+    if meta.held_back.replace(false) && matches!(data, ProviderData::Unchanged) {
+        // providers which leave filtering to the ui have not returned their items yet
+        data = provider.get_elements(None);
+    }
+    meta.expires_in.set(provider.expires_in());
+    data
+}
+
+/// Applies the result of a provider query to the ui.
+/// Returns true if the shown items have been replaced.
+fn apply_provider_data<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    data: ProviderData<T>,
+) -> bool
+where
+    T: Clone + Send + 'static,
+{
+    schedule_expiry(ui, meta);
+    match data {
+        ProviderData::Unchanged => false,
+        ProviderData::Items(items) => {
+            set_message(ui, None);
+            build_ui_from_menu_items(ui, meta, items);
+            true
+        }
+        ProviderData::Error(e) => {
+            log::error!("provider failed: {e}");
+            set_message(ui, Some(&e.to_string()));
+            false
+        }
+        ProviderData::Message(message) => {
+            set_message(ui, Some(&message));
+            false
+        }
+    }
+}
+
+/// Shortest delay until expired items are queried again.
+const MIN_EXPIRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Queries the items again once the data of the provider expired,
+/// replacing the previously scheduled query.
+fn schedule_expiry<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>)
+where
+    T: Clone + Send + 'static,
+{
+    if let Some(source) = ui.expiry_source.take() {
+        source.remove();
+    }
+    let Some(expires_in) = meta.expires_in.get() else {
+        return;
+    };
+
+    let ui_clone = Rc::clone(ui);
+    let meta_clone = Rc::clone(meta);
+    // data which is already expired must not query the provider in a busy loop
+    let delay = expires_in.max(MIN_EXPIRY_DELAY);
+    let source = glib::timeout_add_local_once(delay, move || {
+        ui_clone.expiry_source.set(None);
+        refresh_view_from_provider(&ui_clone, &meta_clone);
+    });
+    ui.expiry_source.set(Some(source));
+}
+
+fn set_message<T: Clone>(ui: &UiElements<T>, message: Option<&str>) {
+    if let Some(message) = message {
+        ui.message.set_text(message);
+        ui.message.set_visible(true);
+    } else {
+        ui.message.set_visible(false);
+    }
+}
+
+fn load_style(config: &Config) -> Option<CssProvider> {
+    let css = config.style()?;
+    log::debug!("loading css from {css}");
+    let provider = CssProvider::new();
+    provider.load_from_file(&File::for_path(css));
+    if let Some(display) = Display::default() {
+        gtk4::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+    }
+    Some(provider)
+}
+
+/// Reads the config file again, i.e. on `SIGHUP`, and applies style, prompt
+/// and search options to the shown window.
+fn reload_config<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>)
+where
+    T: Clone + Send + 'static,
+{
+    let current = meta.config();
+    let reloaded = match current.reload() {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("failed to reload config: {e}");
+            return;
+        }
+    };
+
+    let changed = meta
+        .file_config
+        .borrow()
+        .as_ref()
+        .unwrap_or(current.as_ref())
+        .changed_options(&reloaded);
+    for option in changed
+        .iter()
+        .filter(|option| !config::RELOADABLE_OPTIONS.contains(&option.as_str()))
+    {
+        log::warn!("option {option} changed, restart worf to apply it");
+    }
+    log::info!("reloaded config, changed options: {changed:?}");
+
+    let mut config = (*current).clone();
+    config.apply_reloadable_options(&reloaded);
+    meta.file_config.replace(Some(reloaded));
+
+    if let Some(provider) = meta.style_provider.take()
+        && let Some(display) = Display::default()
+    {
+        gtk4::style_context_remove_provider_for_display(&display, &provider);
+    }
+    meta.style_provider.replace(load_style(&config));
+    ui.search
+        .set_placeholder_text(Some(&config.prompt().unwrap_or("Search...".to_owned())));
+    meta.config.replace(Arc::new(config));
+
+    let query = ui.search_text.lock().unwrap().clone();
+    update_view(ui, meta, &query);
+}
+
+fn refresh_view_from_provider<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>)
+where
+    T: Clone + Send + 'static,
+{
+    let query = ui.search_text.lock().unwrap().clone();
+    let data = provider_elements(meta, &query);
+    if let ProviderData::Items(items) = &data
+        && update_menu_items_in_place(ui, items, &meta.config())
+    {
+        schedule_expiry(ui, meta);
+        return;
+    }
+
+    if apply_provider_data(ui, meta, data) {
+        update_view(ui, meta, &query);
+    }
+}
+
+/// Updates the labels, icons, badges, descriptions, progress and data of the shown items
+/// without rebuilding the rows, so selection and expanded state are kept.
+/// Returns false if the given items do not match the shown ones.
+fn update_menu_items_in_place<T: Clone>(
+    ui: &UiElements<T>,
+    items: &[MenuItem<T>],
+    config: &Config,
+) -> bool {
+    fn is_same_entry<T: Clone>(a: &MenuItem<T>, b: &MenuItem<T>) -> bool {
+        if a.id.is_some() || b.id.is_some() {
+            a.id == b.id
+        } else if a.action.is_some() || b.action.is_some() {
+            a.action == b.action
+        } else {
+            a.label == b.label
+        }
+    }
+
+    let mut menu_rows = ui.menu_rows.write().unwrap();
+    if menu_rows.len() != items.len() {
+        return false;
+    }
+
+    let mut updates = Vec::with_capacity(items.len());
+    for (child, menu) in menu_rows.iter() {
+        let Some(new_item) = items.iter().find(|item| is_same_entry(menu, item)) else {
+            return false;
+        };
+        // badge, description and progress bar are only created for items which have them
+        if menu.progress.is_some() != new_item.progress.is_some()
+            || menu.badge.is_some() != new_item.badge.is_some()
+            || menu.description.is_some() != new_item.description.is_some()
+        {
+            return false;
+        }
+        // icons can only be swapped in rows which show an image
+        if menu.icon_path != new_item.icon_path
+            && find_named_child::<Image>(child.upcast_ref(), "img").is_none()
+        {
+            return false;
+        }
+        updates.push((child.clone(), new_item));
+    }
+
+    let mut resort = false;
+    for (child, new_item) in updates {
+        if let Some(menu) = menu_rows.get_mut(&child) {
+            update_row_widgets(&child, menu, new_item, config);
+            menu.progress = new_item.progress;
+            resort |= menu
+                .initial_sort_score
+                .total_cmp(&new_item.initial_sort_score)
+                .is_ne();
+            menu.initial_sort_score = new_item.initial_sort_score;
+            resort |= menu.rank != new_item.rank;
+            menu.rank = new_item.rank;
+            menu.badge.clone_from(&new_item.badge);
+            menu.badge_class.clone_from(&new_item.badge_class);
+            menu.description.clone_from(&new_item.description);
+            menu.icon_path.clone_from(&new_item.icon_path);
+            menu.css_class.clone_from(&new_item.css_class);
+            menu.label.clone_from(&new_item.label);
+            menu.data.clone_from(&new_item.data);
+        }
+    }
+
+    // the sort function locks the rows itself
+    drop(menu_rows);
+    if resort {
+        ui.main_box.invalidate_sort();
+    }
+    true
+}
+
+/// Sets the text of the label of a row, cut to `line_max_chars`.
+/// With `icon_only` the label is hidden and the text is the tooltip of the row.
+fn set_row_label(row: &Widget, label: &Label, text: Option<&str>, config: &Config) {
+    let text_value = text.unwrap_or_default();
+    if let Some(max_len) = config.line_max_chars()
+        && let Some((end, _)) = text_value.char_indices().nth(max_len)
+    {
+        label.set_text(&format!("{}...", &text_value[..end]));
+    } else {
+        label.set_label(text_value);
+    }
+
+    if config.icon_only() {
+        label.set_visible(false);
+        row.set_tooltip_text(text);
+    }
+}
+
+/// Updates the label, icon, progress, badge and description of a row created by `add_menu_item`
+/// which shows `menu`, so it shows `new_item`.
+fn update_row_widgets<T: Clone>(
+    child: &FlowBoxChild,
+    menu: &MenuItem<T>,
+    new_item: &MenuItem<T>,
+    config: &Config,
+) {
+    if menu.label != new_item.label
+        && let Some(label) = find_named_child::<Label>(child.upcast_ref(), "text")
+        && let Some(row) = child.child()
+    {
+        let (_, _, text) = parse_label(&new_item.label);
+        set_row_label(&row, &label, text.as_deref(), config);
+    }
+    if menu.icon_path != new_item.icon_path
+        && let Some(icon_path) = new_item.icon_path.as_deref()
+        && let Some(image) = find_named_child::<Image>(child.upcast_ref(), "img")
+    {
+        if icon_path.starts_with('/') {
+            image.set_from_file(Some(icon_path));
+        } else {
+            image.set_icon_name(Some(icon_path));
+        }
+    }
+    if menu.progress != new_item.progress
+        && let Some(progress) = new_item.progress
+        && let Some(bar) = find_named_child::<ProgressBar>(child.upcast_ref(), "progress")
+    {
+        bar.set_fraction(progress);
+    }
+    if (menu.badge != new_item.badge || menu.badge_class != new_item.badge_class)
+        && let Some(badge) = find_named_child::<Label>(child.upcast_ref(), "badge")
+    {
+        badge.set_label(new_item.badge.as_deref().unwrap_or_default());
+        badge.set_css_classes(&badge_css_classes(new_item));
+    }
+    if menu.description != new_item.description
+        && let Some(description) = find_named_child::<Label>(child.upcast_ref(), "description")
+    {
+        description.set_label(new_item.description.as_deref().unwrap_or_default());
+    }
+    if menu.css_class != new_item.css_class {
+        if let Some(class) = &menu.css_class {
+            child.remove_css_class(class);
+        }
+        if let Some(class) = &new_item.css_class {
+            child.add_css_class(class);
+        }
+    }
+}
+
+/// A row can be reused for an item which only differs in text, badge, description or progress.
+fn can_reuse_row<T: Clone>(menu: &MenuItem<T>, item: &MenuItem<T>) -> bool {
+    menu.sub_elements.is_empty()
+        && item.sub_elements.is_empty()
+        && menu.icon_path == item.icon_path
+        && menu.glyph == item.glyph
+        && menu.badge.is_some() == item.badge.is_some()
+        && menu.description.is_some() == item.description.is_some()
+        && menu.progress.is_some() == item.progress.is_some()
+        && {
+            let (menu_img, menu_glyph, _) = parse_label(&menu.label);
+            let (item_img, item_glyph, _) = parse_label(&item.label);
+            menu_img == item_img && menu_glyph == item_glyph
+        }
+}
+
+fn badge_css_classes<T: Clone>(item: &MenuItem<T>) -> Vec<&str> {
+    let mut classes = vec!["badge"];
+    if let Some(class) = &item.badge_class {
+        classes.push(class);
+    }
+    classes
+}
+
+fn find_named_child<W: IsA<Widget>>(widget: &Widget, name: &str) -> Option<W> {
+    if widget.widget_name() == name {
+        return widget.clone().downcast::<W>().ok();
+    }
+
+    let mut child = widget.first_child();
+    while let Some(c) = child {
+        if let Some(found) = find_named_child(&c, name) {
+            return Some(found);
+        }
+        child = c.next_sibling();
+    }
+    None
+}
+
+/// Submits the given item once the results did not change for `auto_select_delay`.
+/// Any pending auto select is cancelled, so results have to be stable until the delay passed.
+fn schedule_auto_select<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    item: Option<MenuItem<T>>,
+) where
+    T: Clone + Send + 'static,
+{
+    if let Some(source) = ui.auto_select_source.take() {
+        source.remove();
+    }
+
+    let Some(item) = item.filter(|item| {
+        meta.try_provider()
+            .is_some_and(|provider| provider.allow_auto_select(item))
+    }) else {
+        return;
+    };
+
+    let delay = Duration::from_millis(meta.config().auto_select_delay());
+    let ui_clone = Rc::clone(ui);
+    let meta_clone = Rc::clone(meta);
+    let source = glib::timeout_add_local_once(delay, move || {
+        ui_clone.auto_select_source.set(None);
+        if let Err(e) = handle_selected_item(
+            &ui_clone,
+            &meta_clone,
+            None,
+            Some(item),
+            None,
+            SubmitSource::AutoSelect,
+            &modifiers_from_mask(gdk4::ModifierType::empty()),
+        ) {
+            log::error!("failed to handle selected item {e}");
+        }
+    });
+    ui.auto_select_source.set(Some(source));
+}
+
 fn update_view<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>, query: &str)
 where
     T: Clone + Send + 'static,
@@ -1322,13 +3262,27 @@ where
     set_menu_visibility_for_search(
         query,
         &mut menu_rows,
-        &meta.config,
+        &meta.config(),
         meta.search_ignored_words.as_ref(),
     );
 
-    select_visible_child(&*menu_rows, &ui.main_box, &ui.scroll, &ChildPosition::Front);
+    let has_matches = menu_rows.values().any(|menu| menu.visible);
+    update_suggestions(ui, meta, query, &mut menu_rows, has_matches);
 
-    if meta.config.read().unwrap().auto_select_on_search() {
+    if ui.suggestions_shown.get() {
+        // submitting uses the typed text unless a suggestion is picked explicitly
+        ui.main_box.unselect_all();
+    } else {
+        select_visible_child(
+            &*menu_rows,
+            &ui.main_box,
+            &ui.scroll,
+            &best_match_position(&meta.config()),
+        );
+    }
+
+    // suggestions are never auto selected
+    if meta.config().auto_select_on_search() && has_matches {
         let visible_items: Vec<_> = menu_rows.iter().filter(|(_, menu)| menu.visible).collect();
 
         let item = if visible_items.len() == 1 {
@@ -1338,29 +3292,78 @@ where
         };
 
         drop(menu_rows);
-
-        if let Some(item) = item
-            && let Err(e) = handle_selected_item(ui, meta, None, Some(item), None)
-        {
-            log::error!("failed to handle selected item {e}");
-        }
+        schedule_auto_select(ui, meta, item);
     } else {
         drop(menu_rows);
     }
 
-    if meta.config.read().unwrap().dynamic_lines()
+    if meta.config().dynamic_lines()
         && let Some(geometry) = get_monitor_geometry(ui.window.surface().as_ref())
     {
-        let height =
-            calculate_dynamic_lines_window_height(&meta.config.read().unwrap(), ui, geometry);
+        let height = calculate_dynamic_lines_window_height(&meta.config(), ui, geometry);
         ui.window.set_height_request(height);
     }
+
+    if meta.config().multi_select() {
+        refresh_marks(ui);
+    }
+}
+
+/// Shows the entries closest to the query with the `suggestion` css class
+/// if nothing matched the query.
+fn update_suggestions<T>(
+    ui: &UiElements<T>,
+    meta: &MetaData<T>,
+    query: &str,
+    items: &mut HashMap<FlowBoxChild, MenuItem<T>>,
+    has_matches: bool,
+) where
+    T: Clone + Send + 'static,
+{
+    if ui.suggestions_shown.replace(false) {
+        items
+            .keys()
+            .for_each(|fb| fb.remove_css_class("suggestion"));
+        set_message(ui, None);
+    }
+
+    let count = meta.config().suggestion_count();
+    if has_matches || query.is_empty() || count == 0 {
+        return;
+    }
+
+    let query = query.to_lowercase();
+    let mut suggestions: Vec<_> = items
+        .iter_mut()
+        .map(|(fb, menu)| {
+            let score = strsim::jaro_winkler(&query, &menu.label.to_lowercase());
+            (score, fb, menu)
+        })
+        .filter(|(score, _, _)| *score > 0.0)
+        .collect();
+    if suggestions.is_empty() {
+        return;
+    }
+
+    suggestions.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+    for (score, fb, menu) in suggestions.into_iter().take(count) {
+        menu.search_sort_score = score;
+        menu.visible = true;
+        fb.set_visible(true);
+        fb.add_css_class("suggestion");
+    }
+
+    ui.suggestions_shown.set(true);
+    set_message(ui, Some("No matches, did you mean"));
 }
 
 fn handle_key_exit<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) -> Propagation
 where
     T: Clone + Send + 'static,
 {
+    if !ui.submit_guard.try_submit() {
+        return Propagation::Stop;
+    }
     if let Err(e) = meta.selected_sender.send(Err(Error::NoSelection)) {
         log::error!("failed to send message {e}");
     }
@@ -1368,7 +3371,11 @@ where
     Propagation::Stop
 }
 
-fn handle_key_expand<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) -> Propagation
+fn handle_key_expand<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    modifiers: &HashSet<Modifier>,
+) -> Propagation
 where
     T: Clone + Send + 'static,
 {
@@ -1386,39 +3393,173 @@ where
                 list_box.select_row(first_row.downcast_ref::<ListBoxRow>());
             }
         } else {
-            let data = {
-                let lock = ui.menu_rows.read().unwrap();
-                let menu_item = lock.get(fb);
-                menu_item.map(|menu_item| {
-                    (
-                        meta.item_provider
-                            .lock()
-                            .unwrap()
-                            .get_sub_elements(menu_item),
-                        menu_item.clone(),
-                    )
-                })
+            let menu_item = ui.menu_rows.read().unwrap().get(fb).cloned();
+            if let Some(menu_item) = menu_item {
+                request_sub_elements(ui, meta, fb, menu_item, modifiers.clone());
+            }
+        }
+    }
+    Propagation::Stop
+}
+
+/// Queries the sub elements of the item on a worker thread, so providers
+/// doing network or disk access do not freeze the ui.
+/// A loading row is shown below the item until the result arrives.
+fn request_sub_elements<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    row: &FlowBoxChild,
+    menu_item: MenuItem<T>,
+    modifiers: HashSet<Modifier>,
+) where
+    T: Clone + Send + 'static,
+{
+    if ui.sub_elements_pending.replace(true) {
+        return;
+    }
+    row.add_css_class("loading");
+    let row_content = add_loading_row(row);
+
+    let query = ui.search_text.lock().unwrap().clone();
+    let provider = Arc::clone(&meta.item_provider);
+    let item = menu_item.clone();
+    let handle = gio::spawn_blocking(move || {
+        let mut provider = provider.lock().unwrap();
+        let data = provider.get_sub_elements(&item);
+        (data, provider.expires_in())
+    });
+
+    let ui = Rc::clone(ui);
+    let meta = Rc::clone(meta);
+    let row = row.clone();
+    glib::spawn_future_local(async move {
+        let data = handle.await;
+        ui.sub_elements_pending.set(false);
+        row.remove_css_class("loading");
+        if let Some(content) = row_content {
+            remove_loading_row(&row, &content);
+        }
+
+        let Ok((data, expires_in)) = data else {
+            log::error!("failed to get sub elements of {}", menu_item.label);
+            run_pending_query(&ui, &meta);
+            return;
+        };
+        let query_changed = *ui.search_text.lock().unwrap() != query;
+        if query_changed || ui.submit_guard.is_submitted() {
+            log::debug!("discarding sub elements of {}", menu_item.label);
+            run_pending_query(&ui, &meta);
+            return;
+        }
+        meta.pending_query.set(false);
+        meta.expires_in.set(expires_in);
+        apply_sub_elements(&ui, &meta, data, menu_item, &modifiers);
+    });
+}
+
+/// Asks the provider for the current search, if it was typed while the provider was busy.
+fn run_pending_query<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>)
+where
+    T: Clone + Send + 'static,
+{
+    if meta.pending_query.replace(false) && !ui.submit_guard.is_submitted() {
+        let query = ui.search_text.lock().unwrap().clone();
+        update_view_from_provider(ui, meta, &query);
+    }
+}
+
+/// Shows a "Loading…" sub row below the content of the row.
+/// Returns the content, which is shown alone again by `remove_loading_row`.
+fn add_loading_row(row: &FlowBoxChild) -> Option<Widget> {
+    let content = row.child()?;
+    let loading = Label::new(Some("Loading…"));
+    loading.set_widget_name("text");
+    loading.add_css_class("loading");
+    loading.set_halign(Align::Start);
+    // indented like the sub elements of an expander
+    if let Some(offset) = text_offset(&content, &content) {
+        loading.set_margin_start(offset);
+    }
+
+    row.set_child(None::<&Widget>);
+    let wrapper = gtk4::Box::new(Orientation::Vertical, 0);
+    wrapper.append(&content);
+    wrapper.append(&loading);
+    row.set_child(Some(&wrapper));
+    Some(content)
+}
+
+fn remove_loading_row(row: &FlowBoxChild, content: &Widget) {
+    if let Some(wrapper) = row.child().and_then(|w| w.downcast::<gtk4::Box>().ok()) {
+        wrapper.remove(content);
+    }
+    row.set_child(Some(content));
+}
+
+fn apply_sub_elements<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    data: ProviderData<T>,
+    menu_item: MenuItem<T>,
+    modifiers: &HashSet<Modifier>,
+) where
+    T: Clone + Send + 'static,
+{
+    match data {
+        ProviderData::Items(items) => {
+            build_ui_from_menu_items(ui, meta, items);
+            let query = match meta.expand_mode {
+                ExpandMode::Verbatim => menu_item.label.clone(),
+                ExpandMode::WithSpace => format!("{} ", menu_item.label.clone()),
             };
 
-            if let Some((provider_data, menu_item)) = data {
-                if let Some(items) = provider_data.items {
-                    build_ui_from_menu_items(ui, meta, items);
-                    let query = match meta.expand_mode {
-                        ExpandMode::Verbatim => menu_item.label.clone(),
-                        ExpandMode::WithSpace => format!("{} ", menu_item.label.clone()),
-                    };
-
-                    set_search_text(ui, meta, &query);
-                    if let Ok(new_pos) = i32::try_from(query.len() + 1) {
-                        ui.search.set_position(new_pos);
-                    }
+            set_search_text(ui, meta, &query);
+            if let Ok(new_pos) = i32::try_from(query.len() + 1) {
+                ui.search.set_position(new_pos);
+            }
 
-                    update_view(ui, meta, &query);
-                } else if let Err(e) = handle_selected_item(ui, meta, None, Some(menu_item), None) {
-                    log::error!("{e}");
-                }
+            update_view(ui, meta, &query);
+        }
+        ProviderData::Unchanged => {
+            if let Err(e) = handle_selected_item(
+                ui,
+                meta,
+                None,
+                Some(menu_item),
+                None,
+                SubmitSource::Keyboard,
+                modifiers,
+            ) {
+                log::error!("{e}");
             }
         }
+        other => {
+            apply_provider_data(ui, meta, other);
+        }
+    }
+}
+
+fn handle_key_switch_mode<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) -> Propagation
+where
+    T: Clone + Send + 'static,
+{
+    let Some(mode) = meta
+        .try_provider()
+        .map(|mut provider| provider.switch_mode())
+    else {
+        return Propagation::Stop;
+    };
+    if let Some(mode) = mode {
+        log::debug!("switched to mode {mode}");
+        ui.search.set_placeholder_text(Some(&mode));
+        let query = ui.search_text.lock().unwrap().clone();
+        update_view_from_provider(ui, meta, &query);
+    } else if ui.submit_guard.try_submit() {
+        let query = ui.search_text.lock().unwrap().clone();
+        if let Err(e) = meta.selected_sender.send(Err(Error::SwitchMode(query))) {
+            log::error!("failed to send message {e}");
+        }
+        close_gui(&ui.app);
     }
     Propagation::Stop
 }
@@ -1427,38 +3568,143 @@ fn handle_key_copy<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) -> Propaga
 where
     T: Clone + Send + 'static,
 {
-    if let Some(item) = get_selected_item(ui)
-        && let Some(action) = item.action
+    if ui.submit_guard.is_submitted() {
+        return Propagation::Stop;
+    }
+    let Some(item) = get_selected_item(ui) else {
+        return Propagation::Stop;
+    };
+    if let Some(action) = item.action
         && let Err(e) = desktop::copy_to_clipboard(action, None)
     {
         log::error!("failed to copy to clipboard: {e}");
     }
     if let Err(e) = meta.selected_sender.send(Err(Error::NoSelection)) {
         log::error!("failed to send message {e}");
+        return Propagation::Stop;
     }
+    ui.submit_guard.try_submit();
     close_gui(&ui.app);
     Propagation::Stop
 }
 
-fn handle_key_submit<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>) -> Propagation
+fn handle_key_submit<T>(
+    ui: &Rc<UiElements<T>>,
+    meta: &Rc<MetaData<T>>,
+    modifiers: &HashSet<Modifier>,
+) -> Propagation
 where
     T: Clone + Send + 'static,
 {
+    if meta.config().multi_select() && modifiers.contains(&Modifier::Control) {
+        if let Some(item) = get_selected_item(ui) {
+            toggle_mark(ui, item);
+        }
+        return Propagation::Stop;
+    }
+
     let search_lock = ui.search_text.lock().unwrap();
-    if let Err(e) = handle_selected_item(ui, meta, Some(&search_lock), None, None) {
+    if let Err(e) = handle_selected_item(
+        ui,
+        meta,
+        Some(&search_lock),
+        None,
+        None,
+        SubmitSource::Keyboard,
+        modifiers,
+    ) {
         log::error!("{e}");
     }
     Propagation::Stop
 }
 
+/// Marks the item for `multi_select` or removes its mark.
+fn toggle_mark<T: Clone>(ui: &UiElements<T>, item: MenuItem<T>) {
+    {
+        let mut marked = ui.marked.borrow_mut();
+        if let Some(pos) = marked.iter().position(|m| m.key() == item.key()) {
+            marked.remove(pos);
+        } else {
+            marked.push(item);
+        }
+    }
+    refresh_marks(ui);
+}
+
+/// Gives the rows showing marked items the css class `marked`, rows are reused for other items.
+fn refresh_marks<T: Clone>(ui: &UiElements<T>) {
+    let marked = ui.marked.borrow();
+    for (child, item) in ui.menu_rows.read().unwrap().iter() {
+        if marked.iter().any(|m| m.key() == item.key()) {
+            child.add_css_class("marked");
+        } else {
+            child.remove_css_class("marked");
+        }
+    }
+}
+
 fn handle_key_hide_search<T>(ui: &Rc<UiElements<T>>) -> Propagation
 where
     T: Clone + Send + 'static,
 {
     ui.search.set_visible(!ui.search.is_visible());
+    ui.search_overlay.set_visible(false);
     Propagation::Stop
 }
 
+/// Moves the keys to the search or the list, the focused one has the css class `focused`.
+fn set_focus<T: Clone>(ui: &UiElements<T>, focus: Focus) {
+    ui.focus.set(focus);
+    let (focused, unfocused): (&Widget, &Widget) = match focus {
+        Focus::Search => (ui.search.upcast_ref(), ui.main_box.upcast_ref()),
+        Focus::List => (ui.main_box.upcast_ref(), ui.search.upcast_ref()),
+    };
+    focused.add_css_class("focused");
+    unfocused.remove_css_class("focused");
+}
+
+/// Selects the next visible entry after the selected one whose label starts with `c`.
+fn quick_jump<T: Clone>(ui: &UiElements<T>, c: char) -> Propagation {
+    let menu_rows = ui.menu_rows.read().unwrap();
+    let mut children = Vec::new();
+    let mut index = 0;
+    while let Some(child) = ui.main_box.child_at_index(index) {
+        index += 1;
+        if child.is_visible()
+            && let Some(item) = menu_rows.get(&child)
+        {
+            children.push((child, item.label.as_str()));
+        }
+    }
+
+    let selected = ui
+        .main_box
+        .selected_children()
+        .first()
+        .and_then(|selected| children.iter().position(|(child, _)| child == selected));
+    let labels: Vec<_> = children.iter().map(|(_, label)| *label).collect();
+    if let Some(next) = quick_jump_index(&labels, selected, c) {
+        let child = &children[next].0;
+        ui.main_box.select_child(child);
+        child.grab_focus();
+    }
+    Propagation::Stop
+}
+
+/// Index of the first label starting with `c` after `selected`, wrapping around.
+fn quick_jump_index(labels: &[&str], selected: Option<usize>, c: char) -> Option<usize> {
+    let start = selected.map_or(0, |selected| selected + 1);
+    let starts_with = |label: &&str| {
+        label
+            .chars()
+            .next()
+            .is_some_and(|first| first.to_lowercase().eq(c.to_lowercase()))
+    };
+    (start..labels.len())
+        .chain(0..start.min(labels.len()))
+        .find(|i| starts_with(&labels[*i]))
+}
+
 fn sort_flow_box_childs<T: Clone>(
     child1: &FlowBoxChild,
     child2: &FlowBoxChild,
@@ -1494,10 +3740,14 @@ fn sort_menu_items_by_score<T: Clone>(
                 }
             }
 
-            if menu1.search_sort_score > 0.0 || menu2.search_sort_score > 0.0 {
+            let order = if menu1.search_sort_score > 0.0 || menu2.search_sort_score > 0.0 {
                 compare(menu1.search_sort_score, menu2.search_sort_score)
             } else {
                 compare(menu1.initial_sort_score, menu2.initial_sort_score)
+            };
+            match (order, menu1.rank, menu2.rank) {
+                (Ordering::Equal, Some(rank1), Some(rank2)) => rank1.cmp(&rank2).into(),
+                _ => order,
             }
         }
         (Some(_), None) => Ordering::Larger,
@@ -1507,11 +3757,36 @@ fn sort_menu_items_by_score<T: Clone>(
 }
 
 fn window_show_resize<T: Clone + 'static>(config: &Config, ui: &Rc<UiElements<T>>) {
-    let Some(geometry) = get_monitor_geometry(ui.window.surface().as_ref()) else {
-        return;
-    };
+    if let Some(geometry) = get_monitor_geometry(ui.window.surface().as_ref()) {
+        resize_window(config, ui, geometry);
+    }
+}
+
+/// Sizes the window for the monitor it is most likely shown on before it is presented,
+/// so the first frame already has its final geometry instead of growing from 1x1.
+/// `window_show_resize` corrects the size once the window is active,
+/// in case the compositor picked another monitor.
+fn reserve_window_size<T: Clone + 'static>(config: &Config, ui: &Rc<UiElements<T>>) {
+    let start = Instant::now();
+    ui.window.realize();
+
+    let geometry = get_monitor_geometry(ui.window.surface().as_ref()).or_else(|| {
+        ui.window
+            .display()
+            .monitors()
+            .item(0)
+            .and_then(|item| item.downcast::<Monitor>().ok())
+            .map(|monitor| monitor.geometry())
+    });
+    if let Some(geometry) = geometry {
+        resize_window(config, ui, geometry);
+    }
+    log::debug!("reserved window size after {:?}", start.elapsed());
+}
 
+fn resize_window<T: Clone + 'static>(config: &Config, ui: &Rc<UiElements<T>>, geometry: Rectangle) {
     if !config.blurred_background_fullscreen()
+        && config.blurred_background_margin().is_none()
         && let Some(background) = &ui.background
     {
         background.set_height_request(geometry.height());
@@ -1519,12 +3794,20 @@ fn window_show_resize<T: Clone + 'static>(config: &Config, ui: &Rc<UiElements<T>
     }
 
     // Calculate target width from config, return early if not set
-    let Some(target_width) = percent_or_absolute(&config.width(), geometry.width()) else {
+    let Some(target_width) = (if config.dynamic_width() {
+        Some(0)
+    } else {
+        percent_or_absolute(&config.width(), geometry.width())
+    }) else {
         log::error!("width is not set");
         return;
     };
 
-    let target_height = if let Some(lines) = config.lines() {
+    let target_height = if config.is_bar()
+        || (config.dynamic_width() && config.orientation() == config::Orientation::Horizontal)
+    {
+        Some(0)
+    } else if let Some(lines) = config.lines() {
         Some(calculate_row_height(ui, lines, config))
     } else if config.dynamic_lines() {
         Some(calculate_dynamic_lines_window_height(config, ui, geometry))
@@ -1539,11 +3822,138 @@ fn window_show_resize<T: Clone + 'static>(config: &Config, ui: &Rc<UiElements<T>
         log::debug!("Setting width {target_width}, height {target_height}");
         ui.window.set_height_request(target_height);
         ui.window.set_width_request(target_width);
+
+        if let Some(margin) = config.blurred_background_margin()
+            && let Some(background) = &ui.background
+        {
+            let (width, height) =
+                clipped_background_size(config, target_width, target_height, margin);
+            background.set_width_request(width);
+            background.set_height_request(height);
+        }
     } else {
         log::error!("height is not set");
     }
 }
 
+/// Recomputes the window size when monitors are added, removed or change their resolution
+/// while worf is open. If the monitor the window is shown on disappears,
+/// the compositor is asked to move it to its fallback monitor.
+fn watch_monitor_changes<T: Clone + 'static>(config: &Arc<Config>, ui: &Rc<UiElements<T>>) {
+    // the monitors outlive the window, i.e. in the daemon, so the handlers are disconnected
+    let handlers: SignalHandlers = Rc::new(RefCell::new(Vec::new()));
+    let monitors = ui.window.display().monitors();
+    for position in 0..monitors.n_items() {
+        if let Some(monitor) = monitors
+            .item(position)
+            .and_then(|item| item.downcast::<Monitor>().ok())
+        {
+            watch_monitor(config, ui, &monitor, &handlers);
+        }
+    }
+
+    let cfg = Arc::clone(config);
+    let ui_clone = Rc::clone(ui);
+    let handlers_clone = Rc::clone(&handlers);
+    let id = monitors.connect_items_changed(move |monitors, position, _, added| {
+        for position in position..position + added {
+            if let Some(monitor) = monitors
+                .item(position)
+                .and_then(|item| item.downcast::<Monitor>().ok())
+            {
+                log::debug!("monitor {:?} added", monitor.connector());
+                watch_monitor(&cfg, &ui_clone, &monitor, &handlers_clone);
+            }
+        }
+        schedule_window_resize(&cfg, &ui_clone);
+    });
+    handlers.borrow_mut().push((monitors.upcast(), id));
+
+    let cfg = Arc::clone(config);
+    let ui_clone = Rc::clone(ui);
+    let handlers_clone = Rc::clone(&handlers);
+    ui.window.connect_realize(move |window| {
+        let Some(surface) = window.surface() else {
+            return;
+        };
+        let cfg = Arc::clone(&cfg);
+        let ui = Rc::clone(&ui_clone);
+        let id = surface.connect_enter_monitor(move |_, monitor| {
+            log::debug!("window entered monitor {:?}", monitor.connector());
+            schedule_window_resize(&cfg, &ui);
+        });
+        handlers_clone.borrow_mut().push((surface.upcast(), id));
+    });
+
+    ui.app.connect_shutdown(move |_| {
+        for (object, id) in handlers.borrow_mut().drain(..) {
+            object.disconnect(id);
+        }
+    });
+}
+
+/// Signal handlers with the object they are connected to
+type SignalHandlers = Rc<RefCell<Vec<(glib::Object, SignalHandlerId)>>>;
+
+fn watch_monitor<T: Clone + 'static>(
+    config: &Arc<Config>,
+    ui: &Rc<UiElements<T>>,
+    monitor: &Monitor,
+    handlers: &SignalHandlers,
+) {
+    let cfg = Arc::clone(config);
+    let ui_clone = Rc::clone(ui);
+    let geometry_id = monitor.connect_geometry_notify(move |monitor| {
+        log::debug!(
+            "geometry of monitor {:?} changed to {:?}",
+            monitor.connector(),
+            monitor.geometry()
+        );
+        schedule_window_resize(&cfg, &ui_clone);
+    });
+
+    let cfg = Arc::clone(config);
+    let ui_clone = Rc::clone(ui);
+    let invalidate_id = monitor.connect_invalidate(move |monitor| {
+        log::debug!("monitor {:?} removed", monitor.connector());
+        if ui_clone.window.is_layer_window() && ui_clone.window.monitor().as_ref() == Some(monitor)
+        {
+            ui_clone.window.set_monitor(None);
+        }
+        schedule_window_resize(&cfg, &ui_clone);
+    });
+
+    let mut handlers = handlers.borrow_mut();
+    handlers.push((monitor.clone().upcast(), geometry_id));
+    handlers.push((monitor.clone().upcast(), invalidate_id));
+}
+
+/// Resizes the window once gtk is done processing the current event,
+/// so the surface already reports the new monitor.
+fn schedule_window_resize<T: Clone + 'static>(config: &Arc<Config>, ui: &Rc<UiElements<T>>) {
+    let cfg = Arc::clone(config);
+    let ui = Rc::clone(ui);
+    glib::idle_add_local_once(move || {
+        window_show_resize(&cfg, &ui);
+    });
+}
+
+/// The background extends by `margin` on every side of the window,
+/// except the ones anchored to the screen edge.
+fn clipped_background_size(config: &Config, width: i32, height: i32, margin: i32) -> (i32, i32) {
+    let anchored = |anchor: Anchor| {
+        i32::from(
+            config
+                .location()
+                .is_some_and(|location| location.contains(&anchor)),
+        )
+    };
+    (
+        width + margin * (2 - anchored(Anchor::Left) - anchored(Anchor::Right)),
+        height + margin * (2 - anchored(Anchor::Top) - anchored(Anchor::Bottom)),
+    )
+}
+
 fn calculate_dynamic_lines_window_height<T: Clone + 'static>(
     config: &Config,
     ui: &UiElements<T>,
@@ -1557,6 +3967,133 @@ fn calculate_dynamic_lines_window_height<T: Clone + 'static>(
     }
 }
 
+/// Number of entries printed by `print_widget_tree`, the others look the same.
+const DEBUG_STYLE_ENTRIES: i32 = 3;
+
+/// Prints the widget tree as css selectors, i.e. `flowboxchild#entry.activated`.
+fn print_widget_tree(widget: &Widget, depth: usize) {
+    let classes: String = widget
+        .css_classes()
+        .iter()
+        .map(|class| format!(".{class}"))
+        .collect();
+    println!(
+        "{}{}#{}{classes}",
+        "  ".repeat(depth),
+        widget.css_name(),
+        widget.widget_name()
+    );
+
+    let mut printed = 0;
+    let mut child = widget.first_child();
+    while let Some(c) = child {
+        if widget.is::<FlowBox>() && printed == DEBUG_STYLE_ENTRIES {
+            let mut remaining = 0;
+            let mut rest = Some(c);
+            while let Some(r) = rest {
+                remaining += 1;
+                rest = r.next_sibling();
+            }
+            println!("{}… {remaining} more", "  ".repeat(depth + 1));
+            break;
+        }
+        print_widget_tree(&c, depth + 1);
+        printed += 1;
+        child = c.next_sibling();
+    }
+}
+
+/// Prints the monitor, size and layer shell settings of the mapped window as json.
+fn print_window_info(window: &ApplicationWindow) {
+    let rectangle = |r: Rectangle| serde_json::json!({"x": r.x(), "y": r.y(), "width": r.width(), "height": r.height()});
+
+    let monitor = window
+        .surface()
+        .and_then(|surface| surface.display().monitor_at_surface(&surface))
+        .map(|monitor| {
+            serde_json::json!({
+                "connector": monitor.connector().map(|c| c.to_string()),
+                "model": monitor.model().map(|m| m.to_string()),
+                "scale_factor": monitor.scale_factor(),
+                "geometry": rectangle(monitor.geometry()),
+            })
+        });
+
+    let layer_shell = window.is_layer_window();
+    let edges = [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right];
+    let info = serde_json::json!({
+        "monitor": monitor,
+        "width": window.width(),
+        "height": window.height(),
+        "layer_shell": layer_shell,
+        "layer": window.layer().filter(|_| layer_shell).map(|layer| format!("{layer:?}")),
+        "anchors": edges
+            .iter()
+            .filter(|edge| layer_shell && window.is_anchor(**edge))
+            .map(|edge| format!("{edge:?}"))
+            .collect::<Vec<_>>(),
+        "margins": edges
+            .iter()
+            .filter(|_| layer_shell)
+            .map(|edge| (format!("{edge:?}"), window.margin(*edge)))
+            .collect::<HashMap<_, _>>(),
+        "exclusive_zone": layer_shell.then(|| window.exclusive_zone()),
+        "keyboard_mode": layer_shell.then(|| format!("{:?}", window.keyboard_mode())),
+        "namespace": window
+            .namespace()
+            .filter(|_| layer_shell)
+            .map(|namespace| namespace.to_string()),
+    });
+
+    match serde_json::to_string_pretty(&info) {
+        Ok(json) => println!("{json}"),
+        Err(e) => log::error!("cannot serialize window info {e}"),
+    }
+}
+
+/// Moves the pointer to the center of the window once it is shown, see `pointer_warp`.
+fn setup_pointer_warp(config: &Config, window: &ApplicationWindow) {
+    if config.pointer_warp() != PointerWarp::Center {
+        return;
+    }
+    let warped = Cell::new(false);
+    window.connect_map(move |window| {
+        if warped.replace(true) {
+            return;
+        }
+        // give the compositor time to place the window
+        let window = window.clone();
+        glib::timeout_add_local_once(Duration::from_millis(100), move || {
+            let Some(geometry) = get_monitor_geometry(window.surface().as_ref()) else {
+                return;
+            };
+            let (x, y) = window_center(&window, &geometry);
+            if let Err(e) = desktop::warp_pointer(x, y) {
+                log::warn!("cannot move the pointer to the window: {e}");
+            }
+        });
+    });
+}
+
+/// Center of the window in global coordinates. Only the placement of layer surfaces
+/// is known, other windows are assumed to be centered on their monitor.
+fn window_center(window: &ApplicationWindow, monitor: &Rectangle) -> (i32, i32) {
+    let layer_shell = window.is_layer_window();
+    let axis = |size: i32, monitor_size: i32, start: Edge, end: Edge| {
+        let start_anchored = layer_shell && window.is_anchor(start);
+        let end_anchored = layer_shell && window.is_anchor(end);
+        match (start_anchored, end_anchored) {
+            (true, false) => window.margin(start) + size / 2,
+            (false, true) => monitor_size - window.margin(end) - size / 2,
+            _ => monitor_size / 2,
+        }
+    };
+    (
+        monitor.x() + axis(window.width(), monitor.width(), Edge::Left, Edge::Right),
+        monitor.y() + axis(window.height(), monitor.height(), Edge::Top, Edge::Bottom),
+    )
+}
+
 fn get_monitor_geometry(surface: Option<&gdk4::Surface>) -> Option<Rectangle> {
     surface
         .and_then(|surface| {
@@ -1674,15 +4211,25 @@ fn handle_selected_item<T>(
     query: Option<&str>,
     item: Option<MenuItem<T>>,
     custom_key: Option<&KeyBinding>,
+    source: SubmitSource,
+    modifiers: &HashSet<Modifier>,
 ) -> Result<(), String>
 where
     T: Clone + Send + 'static,
 {
+    let selection = |menu| Selection {
+        menu,
+        custom_key: custom_key.cloned(),
+        modifiers: modifiers.clone(),
+        source,
+        marked: ui.marked.borrow().clone(),
+    };
+
     if let Some(selected_item) = item {
-        send_selected_item(ui, meta, custom_key.cloned(), selected_item);
+        send_selected_item(ui, meta, selection(selected_item));
         return Ok(());
     } else if let Some(item) = get_selected_item(ui) {
-        send_selected_item(ui, meta, custom_key.cloned(), item);
+        send_selected_item(ui, meta, selection(item));
         return Ok(());
     }
 
@@ -1691,7 +4238,7 @@ where
         let label = filtered_query(meta.search_ignored_words.as_ref(), query.unwrap_or(""));
         let item = factory.new_menu_item(label);
         if let Some(item) = item {
-            send_selected_item(ui, meta, custom_key.cloned(), item);
+            send_selected_item(ui, meta, selection(item));
             return Ok(());
         }
     }
@@ -1699,29 +4246,72 @@ where
     Err("selected item cannot be resolved".to_owned())
 }
 
-fn send_selected_item<T>(
-    ui: &Rc<UiElements<T>>,
-    meta: &Rc<MetaData<T>>,
-    custom_key: Option<KeyBinding>,
-    selected_item: MenuItem<T>,
-) where
+fn send_selected_item<T>(ui: &Rc<UiElements<T>>, meta: &Rc<MetaData<T>>, selection: Selection<T>)
+where
     T: Clone + Send + 'static,
 {
-    let ui_clone = Rc::clone(ui);
+    // the window stays open while the activation feedback is shown, ignore further submits.
+    if ui.submit_guard.is_submitted() {
+        return;
+    }
+
+    let (disposition, expires_in) = {
+        let mut provider = meta.item_provider.lock().unwrap();
+        let disposition = provider.on_submit(&selection);
+        (disposition, provider.expires_in())
+    };
+    match disposition {
+        SubmitDisposition::Accept => {}
+        SubmitDisposition::Reject(message) => {
+            set_message(ui, Some(&message));
+            return;
+        }
+        SubmitDisposition::Replace(items) => {
+            meta.expires_in.set(expires_in);
+            // the caller may still hold the search text
+            let ui = Rc::clone(ui);
+            let meta = Rc::clone(meta);
+            glib::idle_add_local_once(move || {
+                set_search_text(&ui, &meta, "");
+                ui.search.set_position(0);
+                apply_provider_data(&ui, &meta, ProviderData::Items(items));
+                update_view(&ui, &meta, "");
+            });
+            return;
+        }
+    }
+
+    if !ui.submit_guard.try_submit() {
+        return;
+    }
+
     let meta_clone = Rc::clone(meta);
     ui.window.connect_hide(move |_| {
-        if let Err(e) = meta_clone.selected_sender.send(Ok(Selection {
-            menu: selected_item.clone(),
-            custom_key: custom_key.clone(),
-        })) {
+        if let Err(e) = meta_clone.selected_sender.send(Ok(selection.clone())) {
             log::error!("failed to send message {e}");
         }
     });
+
+    let feedback = meta.config().activation_feedback_duration();
+    if feedback > 0
+        && let Some(child) = ui.main_box.selected_children().first()
+    {
+        child.add_css_class("activated");
+        let ui_clone = Rc::clone(ui);
+        glib::timeout_add_local_once(Duration::from_millis(feedback), move || {
+            hide_and_close_gui(&ui_clone);
+        });
+    } else {
+        hide_and_close_gui(ui);
+    }
+}
+
+fn hide_and_close_gui<T: Clone>(ui: &UiElements<T>) {
     if let Some(background) = &ui.background {
         background.hide();
     }
     ui.window.hide();
-    close_gui(&ui_clone.app);
+    close_gui(&ui.app);
 }
 
 fn add_menu_item<T: Clone + 'static + Send>(
@@ -1751,6 +4341,30 @@ fn add_menu_item<T: Clone + 'static + Send>(
             list_box.append(&sub_row);
         }
 
+        if meta.config().expander_position() == ExpanderPosition::End {
+            // the arrow follows the text direction of the expander, the content keeps its own.
+            let direction = Widget::default_direction();
+            expander.set_direction(if direction == TextDirection::Rtl {
+                TextDirection::Ltr
+            } else {
+                TextDirection::Rtl
+            });
+            menu_row.set_direction(direction);
+            list_box.set_direction(direction);
+        }
+
+        // hanging indent, sub elements start where the text of their parent starts
+        // and wrap within the remaining width.
+        let parent_row = menu_row.clone();
+        let indented_list = list_box.clone();
+        expander.connect_expanded_notify(move |expander| {
+            if expander.is_expanded()
+                && let Some(offset) = text_offset(&parent_row, expander.upcast_ref())
+            {
+                indented_list.set_margin_start(offset);
+            }
+        });
+
         expander.set_child(Some(&list_box));
         expander.upcast()
     };
@@ -1761,6 +4375,9 @@ fn add_menu_item<T: Clone + 'static + Send>(
 
     let child = FlowBoxChild::new();
     child.set_widget_name("entry");
+    if let Some(class) = &element_to_add.css_class {
+        child.add_css_class(class);
+    }
     child.set_child(Some(&parent));
     child.set_hexpand(true);
     child.set_vexpand(false);
@@ -1769,6 +4386,22 @@ fn add_menu_item<T: Clone + 'static + Send>(
     child
 }
 
+/// Horizontal offset of the text label of a row created by `create_menu_row` relative to `ancestor`.
+#[allow(clippy::cast_possible_truncation)] // pixel offsets, fits easily
+fn text_offset(row: &Widget, ancestor: &Widget) -> Option<i32> {
+    let row_box = row.first_child()?;
+    let mut child = row_box.first_child();
+    while let Some(widget) = child {
+        if matches!(widget.widget_name().as_str(), "text" | "text-box") {
+            return widget
+                .translate_coordinates(ancestor, 0.0, 0.0)
+                .map(|(x, _)| x.round() as i32);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
 fn create_menu_row<T: Clone + 'static + Send>(
     ui: &Rc<UiElements<T>>,
     meta: &Rc<MetaData<T>>,
@@ -1780,22 +4413,33 @@ fn create_menu_row<T: Clone + 'static + Send>(
     row.set_halign(Align::Fill);
     row.set_widget_name("row");
 
-    let row_box = gtk4::Box::new(meta.config.read().unwrap().row_box_orientation().into(), 0);
+    let row_box = gtk4::Box::new(meta.config().row_box_orientation().into(), 0);
     row_box.set_hexpand(true);
     row_box.set_vexpand(false);
     row_box.set_halign(Align::Fill);
+    let padding = i32::from(meta.config().item_padding());
+    row_box.set_margin_top(padding);
+    row_box.set_margin_bottom(padding);
+    row_box.set_margin_start(padding);
+    row_box.set_margin_end(padding);
 
     row.set_child(Some(&row_box));
 
-    let (label_img, label_text) = parse_label(&element_to_add.label);
+    let (label_img, label_glyph, label_text) = parse_label(&element_to_add.label);
 
-    let config = meta.config.read().unwrap();
-    if meta.config.read().unwrap().allow_images() {
+    let config = &meta.config();
+    if let Some(glyph) = element_to_add.glyph.as_ref().or(label_glyph.as_ref()) {
+        let glyph_label = Label::new(Some(glyph));
+        glyph_label.set_widget_name("glyph");
+        glyph_label.set_css_classes(&["glyph"]);
+        row_box.append(&glyph_label);
+    } else if meta.config().allow_images() {
         let img = lookup_icon(
             element_to_add.icon_path.as_ref().map(AsRef::as_ref),
             &config,
         )
-        .or(lookup_icon(label_img.as_ref().map(AsRef::as_ref), &config));
+        .or(lookup_icon(label_img.as_ref().map(AsRef::as_ref), &config))
+        .or_else(|| fallback_icon(config));
 
         if let Some(image) = img {
             image.set_widget_name("img");
@@ -1803,43 +4447,63 @@ fn create_menu_row<T: Clone + 'static + Send>(
         }
     }
 
-    let label = Label::new(label_text.as_ref().map(AsRef::as_ref));
-    label.set_use_markup(meta.config.read().unwrap().allow_markup());
-    label.set_natural_wrap_mode(meta.config.read().unwrap().line_wrap().into());
+    let label = Label::new(None);
+    label.set_use_markup(meta.config().allow_markup());
+    label.set_natural_wrap_mode(meta.config().line_wrap().into());
     label.set_hexpand(true);
     label.set_widget_name("text");
     label.set_wrap(true);
-    if let Some(max_width_chars) = meta.config.read().unwrap().line_max_width_chars() {
+    if let Some(max_width_chars) = meta.config().line_max_width_chars() {
         label.set_max_width_chars(max_width_chars);
     }
 
-    if let Some(max_len) = meta.config.read().unwrap().line_max_chars()
-        && let Some(text) = label_text.as_ref()
-        && text.chars().count() > max_len
-    {
-        let end = text
-            .char_indices()
-            .nth(max_len)
-            .map_or(text.len(), |(idx, _)| idx);
-        label.set_text(&format!("{}...", &text[..end]));
-    }
-
-    row_box.append(&label);
-
-    if meta
-        .config
-        .read()
-        .unwrap()
-        .content_halign()
-        .eq(&config::Align::Start)
-        || meta
-            .config
-            .read()
-            .unwrap()
-            .content_halign()
-            .eq(&config::Align::Fill)
+    set_row_label(row.upcast_ref(), &label, label_text.as_deref(), config);
+
+    let description = element_to_add.description.as_ref().map(|text| {
+        let description = Label::new(Some(text));
+        description.set_widget_name("description");
+        description.set_css_classes(&["description", "dim-label"]);
+        description.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+        description.set_hexpand(true);
+        description
+    });
+    if element_to_add.progress.is_some() || description.is_some() {
+        let text_box = gtk4::Box::new(Orientation::Vertical, 0);
+        text_box.set_widget_name("text-box");
+        text_box.set_hexpand(true);
+        text_box.append(&label);
+
+        if let Some(description) = &description {
+            text_box.append(description);
+        }
+        if let Some(progress) = element_to_add.progress {
+            let bar = ProgressBar::new();
+            bar.set_widget_name("progress");
+            bar.set_css_classes(&["progress"]);
+            bar.set_fraction(progress);
+            text_box.append(&bar);
+        }
+        row_box.append(&text_box);
+    } else {
+        row_box.append(&label);
+    }
+
+    if let Some(badge_text) = &element_to_add.badge {
+        let badge = Label::new(Some(badge_text));
+        badge.set_widget_name("badge");
+        badge.set_css_classes(&badge_css_classes(element_to_add));
+        badge.set_halign(Align::End);
+        badge.set_valign(Align::Center);
+        row_box.append(&badge);
+    }
+
+    if meta.config().content_halign().eq(&config::Align::Start)
+        || meta.config().content_halign().eq(&config::Align::Fill)
     {
         label.set_xalign(0.0);
+        if let Some(description) = &description {
+            description.set_xalign(0.0);
+        }
     }
 
     let click_ui = Rc::clone(ui);
@@ -1849,20 +4513,32 @@ fn create_menu_row<T: Clone + 'static + Send>(
     let click = GestureClick::new();
     click.set_button(gtk4::gdk::BUTTON_PRIMARY);
 
-    let presses = if meta.config.read().unwrap().single_click() {
-        1
-    } else {
-        2
-    };
-
-    click.connect_pressed(move |_gesture, n_press, _x, _y| {
+    let presses = if meta.config().single_click() { 1 } else { 2 };
+
+    click.connect_pressed(move |gesture, n_press, _x, _y| {
+        // top level rows are reused for other items, they submit the one they show
+        let item = gesture
+            .widget()
+            .and_then(|row| row.parent())
+            .and_then(|parent| parent.downcast::<FlowBoxChild>().ok())
+            .and_then(|child| click_ui.menu_rows.read().unwrap().get(&child).cloned())
+            .unwrap_or_else(|| element_clone.clone());
+        let modifiers = modifiers_from_mask(gesture.current_event_state());
+        if click_meta.config().multi_select() && modifiers.contains(&Modifier::Control) {
+            if n_press == 1 {
+                toggle_mark(&click_ui, item);
+            }
+            return;
+        }
         if n_press == presses
             && let Err(e) = handle_selected_item(
                 &click_ui,
                 &click_meta,
                 None,
-                Some(element_clone.clone()),
+                Some(item),
                 None,
+                SubmitSource::Click,
+                &modifiers,
             )
         {
             log::error!("{e}");
@@ -1872,8 +4548,15 @@ fn create_menu_row<T: Clone + 'static + Send>(
 
     row.upcast()
 }
-fn parse_label(label: &str) -> (Option<String>, Option<String>) {
+/// Splits a label of the form `img:<icon>:glyph:<symbol>:text:<text>` into icon, glyph and text.
+pub(crate) fn parse_label(label: &str) -> (Option<String>, Option<String>, Option<String>) {
+    // most labels are plain text, there is nothing to split
+    if !label.contains(':') {
+        return (None, None, Some(label.to_owned()));
+    }
+
     let mut img = None;
+    let mut glyph = None;
     let mut text = None;
 
     let parts: Vec<&str> = label.split(':').collect();
@@ -1889,10 +4572,18 @@ fn parse_label(label: &str) -> (Option<String>, Option<String>) {
                     i += 1;
                 }
             }
+            Some(&"glyph") => {
+                if i + 1 < parts.len() {
+                    glyph = Some(resolve_glyph(parts[i + 1]));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
             Some(&"text") => {
                 i += 1;
                 let mut text_parts = Vec::new();
-                while i < parts.len() && parts[i] != "img" && parts[i] != "text" {
+                while i < parts.len() && !matches!(parts[i], "img" | "glyph" | "text") {
                     text_parts.push(parts[i]);
                     i += 1;
                 }
@@ -1910,42 +4601,74 @@ fn parse_label(label: &str) -> (Option<String>, Option<String>) {
         }
     }
 
-    (img, text)
+    (img, glyph, text)
 }
 
+/// Converts codepoints like `U+F269` into their character, other symbols are kept as they are.
+fn resolve_glyph(glyph: &str) -> String {
+    glyph
+        .strip_prefix("U+")
+        .or_else(|| glyph.strip_prefix("u+"))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+        .map_or_else(|| glyph.to_owned(), String::from)
+}
+
+/// Looks up the icon, returns `None` if it is not set or cannot be found.
 fn lookup_icon(icon_path: Option<&str>, config: &Config) -> Option<Image> {
-    if let Some(image_path) = icon_path {
-        let img_regex = Regex::new(&format!(
-            r"((?i).*{})",
-            known_image_extension_regex_pattern()
-        ));
-        let image = if image_path.starts_with('/') {
-            Image::from_file(image_path)
-        } else if img_regex.unwrap().is_match(image_path) {
-            if let Some(img) = freedesktop_icons::lookup(image_path)
-                .with_size(config.image_size())
-                .with_scale(1)
-                .find()
-            {
-                Image::from_file(img)
-            } else {
-                Image::from_icon_name(image_path)
-            }
-        } else {
-            Image::from_icon_name(image_path)
-        };
+    // compiled once instead of for every row
+    static IMG_REGEX: LazyLock<Regex> = LazyLock::new(known_image_extension_regex_pattern);
 
-        image.set_pixel_size(i32::from(config.image_size()));
-        Some(image)
+    let image_path = icon_path.filter(|path| !path.is_empty())?;
+    let image = if image_path.starts_with('/') {
+        if !Path::new(image_path).exists() {
+            return None;
+        }
+        Image::from_file(image_path)
+    } else if IMG_REGEX.is_match(image_path)
+        && let Some(img) = freedesktop_icons::lookup(image_path)
+            .with_size(config.image_size())
+            .with_scale(1)
+            .find()
+    {
+        Image::from_file(img)
     } else {
-        None
+        let has_icon = Display::default()
+            .is_none_or(|display| IconTheme::for_display(&display).has_icon(image_path));
+        if !has_icon {
+            return None;
+        }
+        Image::from_icon_name(image_path)
+    };
+
+    image.set_pixel_size(i32::from(config.image_size()));
+    Some(image)
+}
+
+/// Image shown according to `icon_fallback` if an entry has no icon.
+fn fallback_icon(config: &Config) -> Option<Image> {
+    match config.icon_fallback() {
+        IconFallback::Placeholder => {
+            let placeholder = config.icon_placeholder();
+            // keep the space if the placeholder itself is missing
+            lookup_icon(Some(&placeholder), config).or_else(|| Some(blank_icon(config)))
+        }
+        IconFallback::Blank => Some(blank_icon(config)),
+        IconFallback::Collapse => None,
     }
 }
 
+fn blank_icon(config: &Config) -> Image {
+    let image = Image::new();
+    let size = i32::from(config.image_size());
+    image.set_size_request(size, size);
+    image
+}
+
 fn set_menu_visibility_for_search<T: Clone>(
     query: &str,
     items: &mut HashMap<FlowBoxChild, MenuItem<T>>,
-    config: &Arc<RwLock<Config>>,
+    config: &Config,
     search_ignored_words: Option<&Vec<Regex>>,
 ) {
     if query.is_empty() {
@@ -1956,62 +4679,43 @@ fn set_menu_visibility_for_search<T: Clone>(
         }
     }
 
-    let mut query = if config.read().unwrap().insensitive() {
-        query.to_owned().to_lowercase()
+    let insensitive = config.insensitive();
+    let query = if insensitive {
+        filtered_query(search_ignored_words, &query.to_lowercase())
     } else {
-        query.to_owned()
+        filtered_query(search_ignored_words, query)
     };
 
-    query = filtered_query(search_ignored_words, &query);
-
+    // the buffers are reused for all items, this runs on every key stroke
+    let mut menu_item_search = String::new();
+    let mut terms_search = String::new();
     for (fb, menu_item) in items.iter_mut() {
-        let menu_item_search = format!(
-            "{} {}",
-            menu_item
-                .action
-                .as_ref()
-                .map(|a| {
-                    if config.read().unwrap().insensitive() {
-                        a.to_lowercase()
-                    } else {
-                        a.clone()
-                    }
-                })
-                .unwrap_or_default(),
-            if config.read().unwrap().insensitive() {
-                menu_item.label.to_lowercase()
-            } else {
-                menu_item.label.clone()
-            }
+        menu_item_search.clear();
+        push_search_text(
+            &mut menu_item_search,
+            menu_item.action.as_deref().unwrap_or_default(),
+            insensitive,
         );
+        menu_item_search.push(' ');
+        push_search_text(&mut menu_item_search, &menu_item.label, insensitive);
 
-        let (search_sort_score, visible) = match config.read().unwrap().match_method() {
-            MatchMethod::Fuzzy => {
-                let mut score = strsim::jaro_winkler(&query, &menu_item_search);
-                if score == 0.0 {
-                    score = -1.0;
-                }
+        let (mut search_sort_score, mut visible) = search_score(&query, &menu_item_search, config);
 
-                (
-                    score,
-                    score > config.read().unwrap().fuzzy_min_score() && score > 0.0,
-                )
-            }
-            MatchMethod::Contains => {
-                if menu_item_search.contains(&query) {
-                    (1.0, true)
-                } else {
-                    (0.0, false)
+        if !menu_item.search_terms.is_empty() {
+            terms_search.clear();
+            for (i, term) in menu_item.search_terms.iter().enumerate() {
+                if i > 0 {
+                    terms_search.push(' ');
                 }
+                push_search_text(&mut terms_search, term, insensitive);
             }
-            MatchMethod::MultiContains => {
-                let contains = query.split(' ').all(|x| menu_item_search.contains(x));
-                (if contains { 1.0 } else { 0.0 }, contains)
-            }
-            MatchMethod::None => {
-                (1.0, true) // items are always shown
+            let (terms_score, terms_visible) = search_score(&query, &terms_search, config);
+            let terms_score = terms_score * config.search_terms_weight();
+            if terms_visible && (!visible || terms_score > search_sort_score) {
+                search_sort_score = terms_score;
+                visible = true;
             }
-        };
+        }
 
         menu_item.search_sort_score = search_sort_score + menu_item.initial_sort_score;
         menu_item.visible = visible;
@@ -2019,15 +4723,56 @@ fn set_menu_visibility_for_search<T: Clone>(
     }
 }
 
+fn push_search_text(buffer: &mut String, text: &str, insensitive: bool) {
+    if insensitive {
+        buffer.extend(text.chars().flat_map(char::to_lowercase));
+    } else {
+        buffer.push_str(text);
+    }
+}
+
+/// Returns the score of `text` for the given query and if it should be visible
+fn search_score(query: &str, text: &str, config: &Config) -> (f64, bool) {
+    match config.match_method() {
+        MatchMethod::Fuzzy => {
+            let mut score = strsim::jaro_winkler(query, text);
+            if score == 0.0 {
+                score = -1.0;
+            }
+
+            (score, score > config.fuzzy_min_score() && score > 0.0)
+        }
+        MatchMethod::Contains => {
+            if text.contains(query) {
+                (1.0, true)
+            } else {
+                (0.0, false)
+            }
+        }
+        MatchMethod::MultiContains => {
+            let contains = query.split(' ').all(|x| text.contains(x));
+            (if contains { 1.0 } else { 0.0 }, contains)
+        }
+        MatchMethod::None => {
+            (1.0, true) // items are always shown
+        }
+    }
+}
+
 #[must_use]
 pub fn filtered_query(search_ignored_words: Option<&Vec<Regex>>, query: &str) -> String {
-    let mut query = query.to_owned();
-    if let Some(s) = search_ignored_words.as_ref() {
-        s.iter().for_each(|rgx| {
-            query = rgx.replace_all(&query, "").to_string();
-        });
+    let mut query = Cow::Borrowed(query);
+    for rgx in search_ignored_words.into_iter().flatten() {
+        // only allocates if something has been replaced
+        let replaced = match rgx.replace_all(&query, "") {
+            Cow::Owned(replaced) => Some(replaced),
+            Cow::Borrowed(_) => None,
+        };
+        if let Some(replaced) = replaced {
+            query = Cow::Owned(replaced);
+        }
     }
-    query
+    query.into_owned()
 }
 
 enum ChildPosition {
@@ -2035,6 +4780,23 @@ enum ChildPosition {
     Back,
 }
 
+/// Position of the best match, which is the one closest to the search entry.
+fn best_match_position(config: &Config) -> ChildPosition {
+    if config.list_direction() == ListDirection::Up {
+        ChildPosition::Back
+    } else {
+        ChildPosition::Front
+    }
+}
+
+fn reverse_order(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Smaller => Ordering::Larger,
+        Ordering::Larger => Ordering::Smaller,
+        other => other,
+    }
+}
+
 fn find_visible_child<T: Clone>(
     items: &HashMap<FlowBoxChild, MenuItem<T>>,
     flow_box: &FlowBox,
@@ -2067,13 +4829,42 @@ fn select_visible_child<T: Clone>(
         flow_box.select_child(&child);
         child.grab_focus();
         child.activate();
+        scroll_to_child(scroll, &child);
+    }
+}
 
-        let vadj = scroll.vadjustment();
-        let new_scroll = match direction {
-            ChildPosition::Front => 0.0,
-            ChildPosition::Back => vadj.upper() - vadj.page_size(),
-        };
-        vadj.set_value(new_scroll);
+/// Scrolls just far enough to show the whole child, so the view follows the selection
+/// instead of jumping to the start or end.
+fn scroll_to_child(scroll: &ScrolledWindow, child: &FlowBoxChild) {
+    let Some(content) = scroll
+        .child()
+        .and_then(|viewport| viewport.downcast::<Viewport>().ok())
+        .and_then(|viewport| viewport.child())
+    else {
+        return;
+    };
+    let Some(bounds) = child.compute_bounds(&content) else {
+        return;
+    };
+
+    scroll_into_view(
+        &scroll.vadjustment(),
+        f64::from(bounds.y()),
+        f64::from(bounds.height()),
+    );
+    // the entries are laid out in a row in horizontal orientation
+    scroll_into_view(
+        &scroll.hadjustment(),
+        f64::from(bounds.x()),
+        f64::from(bounds.width()),
+    );
+}
+
+fn scroll_into_view(adjustment: &Adjustment, start: f64, size: f64) {
+    if start < adjustment.value() {
+        adjustment.set_value(start);
+    } else if start + size > adjustment.value() + adjustment.page_size() {
+        adjustment.set_value(start + size - adjustment.page_size());
     }
 }
 
@@ -2114,5 +4905,344 @@ pub fn apply_sort<T: Clone>(items: &mut [MenuItem<T>], order: &SortOrder) {
 
             items.sort_by(|l, r| into_core_order(sort_menu_items_by_score(Some(l), Some(r))));
         }
+        SortOrder::MostUsed | SortOrder::RecentlyUsed | SortOrder::Frecency => {
+            // the modes store the usage count, recency or frecency as initial score.
+            apply_sort_by(items, |l, r| {
+                r.initial_sort_score
+                    .total_cmp(&l.initial_sort_score)
+                    .then_with(|| l.label.cmp(&r.label))
+            });
+        }
+    }
+}
+
+/// Sorts menu items with the given comparator and keeps their order, see `keep_order`.
+pub fn apply_sort_by<T, F>(items: &mut [MenuItem<T>], compare: F)
+where
+    T: Clone,
+    F: FnMut(&MenuItem<T>, &MenuItem<T>) -> std::cmp::Ordering,
+{
+    items.sort_by(compare);
+    keep_order(items);
+}
+
+/// Shows items with the same score in the given order, the first one at the top.
+/// Items with a higher `initial_sort_score` or search score are still shown above.
+pub fn keep_order<T: Clone>(items: &mut [MenuItem<T>]) {
+    for (rank, item) in items.iter_mut().enumerate() {
+        item.rank = Some(rank);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_guard_accepts_only_first_submit() {
+        let guard = SubmitGuard::default();
+        assert!(!guard.is_submitted());
+        assert!(guard.try_submit());
+        assert!(guard.is_submitted());
+        // i.e. a double enter or an enter bound as custom key and submit key
+        assert!(!guard.try_submit());
+        assert!(!guard.try_submit());
+    }
+
+    /// Returns its items only for an empty query, like drun or dmenu mode.
+    struct StaticProvider;
+
+    impl ItemProvider<()> for StaticProvider {
+        fn get_elements(&mut self, query: Option<&str>) -> ProviderData<()> {
+            if query.is_some() {
+                ProviderData::Unchanged
+            } else {
+                ProviderData::Items(vec![MenuItem::new(
+                    "firefox".to_owned(),
+                    None,
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    None,
+                )])
+            }
+        }
+
+        fn get_sub_elements(&mut self, _: &MenuItem<()>) -> ProviderData<()> {
+            ProviderData::Unchanged
+        }
+    }
+
+    fn test_meta(provider: impl ItemProvider<()> + Send + 'static, config: Config) -> MetaData<()> {
+        let (sender, _receiver) = channel::bounded(1);
+        MetaData {
+            item_provider: Arc::new(Mutex::new(provider)) as ArcProvider<()>,
+            item_factory: None,
+            selected_sender: sender,
+            held_back: Cell::new(config.min_query_length() > 0),
+            expires_in: Cell::new(None),
+            pending_query: Cell::new(false),
+            config: RefCell::new(Arc::new(config)),
+            file_config: RefCell::new(None),
+            style_provider: RefCell::new(None),
+            search_ignored_words: None,
+            expand_mode: ExpandMode::Verbatim,
+        }
+    }
+
+    fn item(label: &str) -> MenuItem<()> {
+        MenuItem::new(label.to_owned(), None, None, vec![], None, 0.0, None)
+    }
+
+    #[test]
+    fn test_provider_elements_fill_once_query_is_long_enough() {
+        let config: Config = toml::from_str("min_query_length = 2").unwrap();
+        let meta = test_meta(StaticProvider, config);
+
+        let count = |data: ProviderData<()>| match data {
+            ProviderData::Items(items) => Some(items.len()),
+            _ => None,
+        };
+        assert_eq!(count(provider_elements(&meta, "f")), Some(0));
+        assert_eq!(count(provider_elements(&meta, "fi")), Some(1));
+        // the ui filters the shown items from now on
+        assert_eq!(count(provider_elements(&meta, "fir")), None);
+        assert_eq!(count(provider_elements(&meta, "f")), Some(0));
+        assert_eq!(count(provider_elements(&meta, "fi")), Some(1));
+    }
+
+    #[test]
+    #[ignore = "rows are widgets, which need a display: cargo test -- --ignored --test-threads=1"]
+    fn test_streamed_items_are_appended_until_items_are_replaced() {
+        gtk4::init().expect("no display to create widgets on");
+        let config = Config::default();
+        let ui = Rc::new(UiElements::new(
+            Application::builder().build(),
+            ApplicationWindow::builder().build(),
+            &config,
+        ));
+        let meta = Rc::new(test_meta(StaticProvider, config));
+        let labels = |ui: &UiElements<()>| {
+            let mut labels: Vec<_> = ui
+                .menu_rows
+                .read()
+                .unwrap()
+                .values()
+                .map(|menu| menu.label.clone())
+                .collect();
+            labels.sort();
+            labels
+        };
+
+        let generation = ui.items_generation.get();
+        assert!(accept_streamed_items(
+            &ui,
+            &meta,
+            generation,
+            vec![item("firefox")]
+        ));
+        assert!(accept_streamed_items(
+            &ui,
+            &meta,
+            generation,
+            vec![item("files"), item("gimp")]
+        ));
+        assert_eq!(labels(&ui), ["files", "firefox", "gimp"]);
+
+        // i.e. the search replaced the shown items while the provider was still streaming
+        ui.items_generation.set(generation + 1);
+        assert!(!accept_streamed_items(
+            &ui,
+            &meta,
+            generation,
+            vec![item("thunderbird")]
+        ));
+        assert_eq!(labels(&ui), ["files", "firefox", "gimp"]);
+
+        let generation = ui.items_generation.get();
+        ui.submit_guard.try_submit();
+        assert!(!accept_streamed_items(
+            &ui,
+            &meta,
+            generation,
+            vec![item("thunderbird")]
+        ));
+        assert_eq!(labels(&ui), ["files", "firefox", "gimp"]);
+    }
+
+    #[test]
+    #[ignore = "rows are widgets, which need a display: cargo test -- --ignored --test-threads=1"]
+    fn test_copy_key_submits_only_with_selection() {
+        gtk4::init().expect("no display to create widgets on");
+        let config = Config::default();
+        let ui = Rc::new(UiElements::new(
+            Application::builder().build(),
+            ApplicationWindow::builder().build(),
+            &config,
+        ));
+        let (sender, receiver) = channel::bounded(1);
+        let mut meta = test_meta(StaticProvider, config);
+        meta.selected_sender = sender;
+        let meta = Rc::new(meta);
+
+        handle_key_copy(&ui, &meta);
+        assert!(!ui.submit_guard.is_submitted());
+        assert!(receiver.try_recv().is_err());
+
+        // without an action there is nothing to copy, the menu is still closed
+        accept_streamed_items(&ui, &meta, ui.items_generation.get(), vec![item("firefox")]);
+        let child = ui.main_box.child_at_index(0).unwrap();
+        ui.main_box.select_child(&child);
+        handle_key_copy(&ui, &meta);
+        assert!(ui.submit_guard.is_submitted());
+        assert!(matches!(receiver.try_recv(), Ok(Err(Error::NoSelection))));
+    }
+
+    /// Returns the same items, which are outdated after a few seconds like one time passwords.
+    struct ExpiringProvider;
+
+    impl ItemProvider<()> for ExpiringProvider {
+        fn get_elements(&mut self, _: Option<&str>) -> ProviderData<()> {
+            ProviderData::Items(vec![item("123 456")])
+        }
+
+        fn get_sub_elements(&mut self, _: &MenuItem<()>) -> ProviderData<()> {
+            ProviderData::Unchanged
+        }
+
+        fn expires_in(&self) -> Option<Duration> {
+            Some(Duration::from_secs(5))
+        }
+    }
+
+    #[test]
+    fn test_provider_elements_remember_expiry() {
+        let meta = test_meta(ExpiringProvider, Config::default());
+        assert_eq!(meta.expires_in.get(), None);
+        provider_elements(&meta, "");
+        assert_eq!(meta.expires_in.get(), Some(Duration::from_secs(5)));
+
+        // the expiry of a provider busy with a background query is kept
+        let _busy = meta.item_provider.lock().unwrap();
+        meta.expires_in.set(None);
+        assert!(matches!(
+            provider_elements(&meta, ""),
+            ProviderData::Unchanged
+        ));
+        assert_eq!(meta.expires_in.get(), None);
+    }
+
+    #[test]
+    fn test_busy_provider_remembers_pending_query() {
+        let meta = test_meta(StaticProvider, Config::default());
+        let busy = meta.item_provider.lock().unwrap();
+        assert!(matches!(
+            provider_elements(&meta, "fire"),
+            ProviderData::Unchanged
+        ));
+        assert!(meta.pending_query.get());
+        drop(busy);
+
+        provider_elements(&meta, "fire");
+        assert!(!meta.pending_query.get());
+    }
+
+    #[test]
+    fn test_parse_label_with_glyph() {
+        assert_eq!(
+            parse_label("glyph:U+F269:text:Firefox"),
+            (
+                None,
+                Some("\u{f269}".to_owned()),
+                Some("Firefox".to_owned())
+            )
+        );
+        assert_eq!(
+            parse_label("img:folder:glyph:*:text:a:b"),
+            (
+                Some("folder".to_owned()),
+                Some("*".to_owned()),
+                Some("a:b".to_owned())
+            )
+        );
+        assert_eq!(resolve_glyph("U+zz"), "U+zz");
+    }
+
+    #[test]
+    fn test_quick_jump_index_cycles_through_matches() {
+        let labels = ["Firefox", "Files", "gimp", "foot"];
+        assert_eq!(quick_jump_index(&labels, None, 'f'), Some(0));
+        assert_eq!(quick_jump_index(&labels, Some(0), 'f'), Some(1));
+        assert_eq!(quick_jump_index(&labels, Some(1), 'F'), Some(3));
+        assert_eq!(quick_jump_index(&labels, Some(3), 'f'), Some(0));
+        assert_eq!(quick_jump_index(&labels, Some(0), 'g'), Some(2));
+        assert_eq!(quick_jump_index(&labels, Some(0), 'x'), None);
+    }
+
+    #[test]
+    fn test_navigation_key_maps_home_and_end_keys() {
+        let config: Config =
+            toml::from_str("key_home = \"Alt+g\"\nkey_end = \"Alt+Shift+g\"").unwrap();
+        let alt = HashSet::from([Modifier::Alt]);
+        let alt_shift = HashSet::from([Modifier::Alt, Modifier::Shift]);
+        let is_g = |key| key == Key::G;
+
+        assert_eq!(navigation_key(&config, &alt, &is_g), Some(gdk4::Key::Home));
+        assert_eq!(
+            navigation_key(&config, &alt_shift, &is_g),
+            Some(gdk4::Key::End)
+        );
+        assert_eq!(navigation_key(&config, &alt, &|key| key == Key::H), None);
+    }
+
+    #[test]
+    fn test_key_conflicts_with_overlapping_modifiers() {
+        let binding = |key, modifiers: &[Modifier], label: &str| KeyBinding {
+            key,
+            modifiers: modifiers.iter().copied().collect(),
+            label: label.to_owned(),
+            visible: true,
+        };
+        let custom_keys = CustomKeys {
+            bindings: vec![
+                binding(Key::Enter, &[Modifier::None], "run"),
+                binding(Key::C, &[Modifier::Alt], "clear"),
+                binding(Key::C, &[Modifier::Alt, Modifier::Control], "copy"),
+                binding(Key::C, &[Modifier::Alt], "close"),
+                binding(Key::K, &[Modifier::None], "kill"),
+                binding(Key::K, &[Modifier::Shift], "shout"),
+            ],
+            hint: None,
+        };
+
+        let conflicts = key_conflicts(&Config::default(), Some(&custom_keys));
+        assert_eq!(
+            conflicts,
+            vec![
+                KeyConflict {
+                    key: Key::Enter,
+                    modifiers: vec![],
+                    action: "custom key run".to_owned(),
+                    shadowed: "submit".to_owned(),
+                },
+                KeyConflict {
+                    key: Key::C,
+                    modifiers: vec![Modifier::Alt],
+                    action: "custom key clear".to_owned(),
+                    shadowed: "custom key close".to_owned(),
+                },
+                KeyConflict {
+                    key: Key::K,
+                    modifiers: vec![Modifier::Shift],
+                    action: "custom key kill".to_owned(),
+                    shadowed: "custom key shout".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts[1].to_string(),
+            "Alt+C triggers 'custom key clear', 'custom key close' is not reachable"
+        );
     }
 }
@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    config::{Config, SortOrder},
+    desktop::load_cache_file,
+};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Usage caches of the modes before the history existed, by namespace.
+/// They are imported once, when the namespace is not in the history yet.
+const LEGACY_CACHES: &[(&str, &str)] = &[("drun", "drun_cache"), ("run", "worf-run")];
+
+/// Usage of a single entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Usage {
+    /// Frecency score at the time of the last use
+    score: f64,
+    /// Unix timestamp of the last use
+    last_used: i64,
+    /// Number of uses
+    #[serde(default)]
+    count: i64,
+}
+
+impl Usage {
+    /// Every use adds one to the score, which halves with every `half_life` seconds passed.
+    fn score_at(&self, now: i64, half_life: f64) -> f64 {
+        self.score * self.recency_at(now, half_life)
+    }
+
+    /// Between 0 and 1, halves with every `half_life` seconds since the last use.
+    fn recency_at(&self, now: i64, half_life: f64) -> f64 {
+        // seconds since the last use, precision is irrelevant
+        #[allow(clippy::cast_precision_loss)]
+        let age = (now - self.last_used).max(0) as f64;
+        0.5_f64.powf(age / half_life)
+    }
+}
+
+/// Frecency history, combines how often and how recently entries were used.
+/// The entries of every mode live in their own namespace of the shared history file.
+#[derive(Debug, Clone)]
+pub struct History {
+    namespace: String,
+    path: Option<PathBuf>,
+    entries: HashMap<String, Usage>,
+    /// Half-life of a use in seconds
+    half_life: f64,
+    max_entries: usize,
+}
+
+impl History {
+    /// Loads the namespace from the history file.
+    /// If the file has none yet, it is imported from the legacy usage cache of the mode,
+    /// otherwise it starts empty.
+    #[must_use]
+    pub fn load(namespace: &str, config: &Config) -> Self {
+        let path = history_path();
+        let stored = path
+            .as_ref()
+            .and_then(|path| load_namespaces(path).remove(namespace));
+        let mut history = Self {
+            namespace: namespace.to_owned(),
+            path,
+            entries: HashMap::new(),
+            half_life: config.history_half_life() * SECONDS_PER_DAY,
+            max_entries: config.history_max_entries(),
+        };
+        if let Some(entries) = stored {
+            history.entries = entries;
+        } else if history.import_legacy_cache(config)
+            && let Err(e) = history.save()
+        {
+            log::warn!("cannot save imported {namespace} history: {e}");
+        }
+        history
+    }
+
+    /// Seeds the history with the usage counts and last usages of the legacy cache files.
+    /// Entries without a last usage count as used at the oldest known one.
+    /// Returns if anything was imported.
+    fn import_legacy_cache(&mut self, config: &Config) -> bool {
+        let Some(cache_path) = LEGACY_CACHES
+            .iter()
+            .find(|(namespace, _)| *namespace == self.namespace)
+            .and_then(|(_, name)| legacy_cache_path(config, name))
+        else {
+            return false;
+        };
+        let counts = load_cache_file(&cache_path).unwrap_or_default();
+        let recent = load_cache_file(&legacy_recent_path(&cache_path)).unwrap_or_default();
+        if counts.is_empty() && recent.is_empty() {
+            return false;
+        }
+
+        let now = now();
+        let oldest = recent.values().min().copied().unwrap_or(now);
+        for key in counts.keys().chain(recent.keys()) {
+            let count = counts.get(key).copied().unwrap_or(0).max(1);
+            // counts are small, precision is irrelevant
+            #[allow(clippy::cast_precision_loss)]
+            let usage = Usage {
+                score: count as f64,
+                last_used: recent.get(key).copied().unwrap_or(oldest),
+                count,
+            };
+            self.entries.insert(key.clone(), usage);
+        }
+        self.trim(now);
+        log::info!(
+            "imported {} entries of {} into the {} history",
+            self.entries.len(),
+            cache_path.display(),
+            self.namespace
+        );
+        true
+    }
+
+    /// Current frecency score of the key, 0 if it was never used.
+    #[must_use]
+    pub fn score(&self, key: &str) -> f64 {
+        self.score_at(key, now())
+    }
+
+    fn score_at(&self, key: &str, now: i64) -> f64 {
+        self.entries
+            .get(key)
+            .map_or(0.0, |usage| usage.score_at(now, self.half_life))
+    }
+
+    /// Score of the key for the sort order, 0 if it was never used:
+    /// the frecency for `Frecency`, the recency of the last use for `RecentlyUsed`
+    /// and the number of uses otherwise.
+    #[must_use]
+    pub fn sort_score(&self, key: &str, order: &SortOrder) -> f64 {
+        self.sort_score_at(key, order, now())
+    }
+
+    #[allow(clippy::cast_precision_loss)] // counts, precision is irrelevant
+    fn sort_score_at(&self, key: &str, order: &SortOrder, now: i64) -> f64 {
+        match (order, self.entries.get(key)) {
+            (_, None) => 0.0,
+            (SortOrder::Frecency, Some(usage)) => usage.score_at(now, self.half_life),
+            (SortOrder::RecentlyUsed, Some(usage)) => usage.recency_at(now, self.half_life),
+            (_, Some(usage)) => usage.count as f64,
+        }
+    }
+
+    /// If the key was used before.
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Moves the usage of `from` to `to`, unless `to` has one already.
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if !self.entries.contains_key(to)
+            && let Some(usage) = self.entries.remove(from)
+        {
+            self.entries.insert(to.to_owned(), usage);
+        }
+    }
+
+    /// Used keys with their current score, highest first.
+    #[must_use]
+    pub fn ranked(&self) -> Vec<(String, f64)> {
+        let now = now();
+        let mut ranked: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(key, usage)| (key.clone(), usage.score_at(now, self.half_life)))
+            .collect();
+        ranked.sort_by(|(_, l), (_, r)| r.total_cmp(l));
+        ranked
+    }
+
+    /// Records a use of the key, drops the lowest scored entries above `history_max_entries`.
+    pub fn record(&mut self, key: &str) {
+        self.record_at(key, now());
+    }
+
+    fn record_at(&mut self, key: &str, now: i64) {
+        let score = self.score_at(key, now) + 1.0;
+        let count = self.entries.get(key).map_or(0, |usage| usage.count) + 1;
+        self.entries.insert(
+            key.to_owned(),
+            Usage {
+                score,
+                last_used: now,
+                count,
+            },
+        );
+        self.trim(now);
+    }
+
+    fn trim(&mut self, now: i64) {
+        if self.entries.len() > self.max_entries {
+            let mut scores: Vec<_> = self
+                .entries
+                .iter()
+                .map(|(key, usage)| (key.clone(), usage.score_at(now, self.half_life)))
+                .collect();
+            scores.sort_by(|(_, l), (_, r)| r.total_cmp(l));
+            for (key, _) in scores.into_iter().skip(self.max_entries) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Stores the namespace, the other namespaces of the file are kept.
+    /// The file is written to a temporary file first and renamed, so concurrent
+    /// instances never read a partially written history.
+    /// # Errors
+    /// * `Error::MissingFile` if there is no state or cache dir
+    /// * `Error::Io` or `Error::ParsingError` if the file cannot be written
+    pub fn save(&self) -> Result<(), Error> {
+        let path = self.path.as_ref().ok_or(Error::MissingFile)?;
+        let mut namespaces = load_namespaces(path);
+        namespaces.insert(self.namespace.clone(), self.entries.clone());
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::Io(e.to_string()))?;
+        }
+        let content =
+            toml::to_string(&namespaces).map_err(|e| Error::ParsingError(e.to_string()))?;
+        let tmp_path = path.with_extension(format!("toml.{}.tmp", process::id()));
+        fs::write(&tmp_path, content)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp_path);
+                Error::Io(e.to_string())
+            })
+    }
+}
+
+/// Records a use of the key in the namespace and saves it, failures are only logged
+/// as the history must never keep an entry from being launched.
+pub fn record_usage(namespace: &str, key: &str, config: &Config) {
+    let mut history = History::load(namespace, config);
+    history.record(key);
+    if let Err(e) = history.save() {
+        log::warn!("cannot save {namespace} history: {e}");
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("worf-history.toml"))
+}
+
+/// The usage cache of a mode, `cache_file` replaces it like it did for the mode.
+fn legacy_cache_path(config: &Config, name: &str) -> Option<PathBuf> {
+    config
+        .cache_file()
+        .map(PathBuf::from)
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join(name)))
+}
+
+/// The last usages were stored as unix timestamps next to the usage cache.
+fn legacy_recent_path(cache_path: &Path) -> PathBuf {
+    let mut path = cache_path.as_os_str().to_owned();
+    path.push("-recent");
+    PathBuf::from(path)
+}
+
+fn load_namespaces(path: &PathBuf) -> HashMap<String, HashMap<String, Usage>> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| match toml::from_str(&content) {
+            Ok(namespaces) => Some(namespaces),
+            Err(e) => {
+                log::warn!("cannot parse history {}: {e}", path.display());
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(max_entries: usize) -> History {
+        History {
+            namespace: "test".to_owned(),
+            path: None,
+            entries: HashMap::new(),
+            half_life: SECONDS_PER_DAY,
+            max_entries,
+        }
+    }
+
+    #[test]
+    fn test_score_decays_with_half_life() {
+        let mut history = history(10);
+        history.record_at("a", 0);
+        history.record_at("a", 0);
+        assert!((history.score_at("a", 0) - 2.0).abs() < f64::EPSILON);
+        assert!((history.score_at("a", 86_400) - 1.0).abs() < f64::EPSILON);
+        assert!(history.score_at("b", 0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_recent_use_beats_old_frequent_use() {
+        let mut history = history(10);
+        for _ in 0..3 {
+            history.record_at("old", 0);
+        }
+        history.record_at("new", 3 * 86_400);
+        assert!(history.score_at("new", 3 * 86_400) > history.score_at("old", 3 * 86_400));
+    }
+
+    #[test]
+    fn test_record_drops_lowest_scores() {
+        let mut history = history(2);
+        history.record_at("a", 0);
+        history.record_at("a", 0);
+        history.record_at("b", 0);
+        history.record_at("c", 86_400);
+        assert_eq!(history.entries.len(), 2);
+        assert!(history.entries.contains_key("a"));
+        assert!(history.entries.contains_key("c"));
+    }
+
+    #[test]
+    fn test_sort_score_follows_sort_order() {
+        let mut history = history(10);
+        history.record_at("a", 100);
+        history.record_at("a", 100);
+        history.record_at("b", 200);
+        let count = |key| history.sort_score_at(key, &SortOrder::MostUsed, 300);
+        let recency = |key| history.sort_score_at(key, &SortOrder::RecentlyUsed, 300);
+        assert!(count("a") > count("b"));
+        assert!(recency("b") > recency("a"));
+        assert!(recency("b") <= 1.0);
+        assert!(recency("c").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rename_keeps_existing_usage() {
+        let mut history = history(10);
+        history.record_at("name", 0);
+        history.rename("name", "id");
+        assert!(history.contains("id"));
+        assert!(!history.contains("name"));
+
+        history.record_at("name", 0);
+        history.record_at("name", 0);
+        history.rename("name", "id");
+        assert_eq!(history.entries["id"].count, 1);
+    }
+}
@@ -1,19 +1,24 @@
-use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::{
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    time::Duration,
+};
 
 use regex::Regex;
 
 use crate::{
     Error,
-    config::Config,
-    desktop::spawn_fork,
+    config::{AutoSuggestionWeights, Config},
+    desktop::{copy_to_clipboard, launch},
     gui::{
         self, ArcProvider, DefaultItemFactory, ExpandMode, ItemProvider, MenuItem, ProviderData,
     },
+    history,
     modes::{
-        drun::{DRunProvider, update_drun_cache_and_run},
+        drun::{self, DRunProvider},
         file::FileItemProvider,
+        info::InfoProvider,
         math::MathProvider,
-        search::SearchProvider,
+        search::{SearchProvider, url_of},
         ssh,
         ssh::SshProvider,
     },
@@ -26,6 +31,8 @@ enum AutoRunType {
     File,
     Ssh,
     WebSearch,
+    Info,
+    Emoji,
     Auto,
 }
 
@@ -36,18 +43,79 @@ struct AutoItemProvider {
     math: MathProvider<AutoRunType>,
     ssh: SshProvider<AutoRunType>,
     search: SearchProvider<AutoRunType>,
+    info: InfoProvider<AutoRunType>,
+    /// Queries which are urls are opened or searched instead of listing applications
+    open_urls: bool,
+    /// Weights of the sources blended for unmatched searches, `None` if disabled
+    suggestion_weights: Option<AutoSuggestionWeights>,
+    /// Recently used emoji, blended into the suggestions
+    recent_emoji: Vec<MenuItem<AutoRunType>>,
     last_mode: Option<AutoRunType>,
+    /// Search of the last query, to tell the periodic refreshes for info commands apart
+    last_search: Option<String>,
+    /// Modes `switch_mode` cycles through
+    switch_modes: Vec<AutoRunType>,
+    /// Mode selected via `switch_mode`, detected from the search if None
+    forced_mode: Option<AutoRunType>,
 }
 
 impl AutoItemProvider {
     fn new(config: &Config) -> Self {
+        let drun = DRunProvider::new(AutoRunType::DRun, config);
+        let file =
+            FileItemProvider::new(AutoRunType::File, config.sort_order(), config.file_places())
+                .with_icon_resolver(drun.icon_resolver());
         AutoItemProvider {
-            drun: DRunProvider::new(AutoRunType::DRun, config),
-            file: FileItemProvider::new(AutoRunType::File, config.sort_order()),
+            drun,
+            file,
             math: MathProvider::new(AutoRunType::Math),
-            ssh: SshProvider::new(AutoRunType::Ssh, &config.sort_order()),
-            search: SearchProvider::new(AutoRunType::WebSearch, config.search_query()),
+            ssh: SshProvider::new(AutoRunType::Ssh, config),
+            search: SearchProvider::new(AutoRunType::WebSearch, config.search_query())
+                .with_open_urls(config.search_open_urls()),
+            open_urls: config.search_open_urls(),
+            suggestion_weights: config
+                .auto_suggestions()
+                .then(|| config.auto_suggestion_weights()),
+            recent_emoji: if config.auto_suggestions() {
+                recent_emoji(config)
+            } else {
+                Vec::new()
+            },
+            info: InfoProvider::new(AutoRunType::Info, config),
             last_mode: None,
+            last_search: None,
+            switch_modes: config
+                .switch_modes()
+                .iter()
+                .filter_map(|mode| match mode.to_lowercase().as_str() {
+                    "drun" => Some(AutoRunType::DRun),
+                    "file" => Some(AutoRunType::File),
+                    "ssh" => Some(AutoRunType::Ssh),
+                    "math" => Some(AutoRunType::Math),
+                    "websearch" => Some(AutoRunType::WebSearch),
+                    _ => {
+                        log::debug!("{mode} is not part of auto mode, not switching to it");
+                        None
+                    }
+                })
+                .collect(),
+            forced_mode: None,
+        }
+    }
+
+    fn elements_of_mode(
+        &mut self,
+        mode: &AutoRunType,
+        search_opt: Option<&str>,
+    ) -> ProviderData<AutoRunType> {
+        match mode {
+            AutoRunType::Math => self.math.get_elements(search_opt),
+            AutoRunType::DRun => self.drun.get_elements(search_opt),
+            AutoRunType::File => self.file.get_elements(search_opt),
+            AutoRunType::Ssh => self.ssh.get_elements(search_opt),
+            AutoRunType::WebSearch => self.search.get_elements(search_opt),
+            AutoRunType::Info => self.info.get_elements(search_opt),
+            AutoRunType::Emoji | AutoRunType::Auto => self.default_auto_elements(),
         }
     }
 
@@ -60,8 +128,8 @@ impl AutoItemProvider {
                 .is_some_and(|t| t != &AutoRunType::Auto)
         {
             let mut data = self.drun.get_elements(None);
-            if let Some(items) = data.items.as_mut()
-                && let Some(mut ssh) = self.ssh.get_elements(None).items
+            if let ProviderData::Items(items) = &mut data
+                && let ProviderData::Items(mut ssh) = self.ssh.get_elements(None)
             {
                 items.append(&mut ssh);
             }
@@ -69,11 +137,54 @@ impl AutoItemProvider {
             self.last_mode = Some(AutoRunType::Auto);
             data
         } else {
-            ProviderData { items: None }
+            ProviderData::Unchanged
+        }
+    }
+
+    /// Ranks the applications, a websearch row and the recently used emoji together,
+    /// the score of every entry is multiplied by the weight of its source.
+    fn suggestion_elements(
+        &mut self,
+        search: &str,
+        weights: &AutoSuggestionWeights,
+    ) -> ProviderData<AutoRunType> {
+        let mut items = match self.drun.get_elements(None) {
+            ProviderData::Items(items) => items,
+            _ => Vec::new(),
+        };
+        for item in &mut items {
+            item.initial_sort_score *= weights.drun;
+        }
+        if let ProviderData::Items(mut ssh) = self.ssh.get_elements(None) {
+            items.append(&mut ssh);
+        }
+        if let ProviderData::Items(search_items) = self.search.get_elements(Some(search)) {
+            // only the row for the query itself, not the previous searches
+            items.extend(search_items.into_iter().take(1).map(|mut item| {
+                item.initial_sort_score = weights.websearch;
+                item
+            }));
         }
+        items.extend(self.recent_emoji.iter().cloned().map(|mut item| {
+            item.initial_sort_score *= weights.emoji;
+            item
+        }));
+        ProviderData::Items(items)
     }
 }
 
+#[cfg(feature = "mode-emoji")]
+fn recent_emoji(config: &Config) -> Vec<MenuItem<AutoRunType>> {
+    /// Number of recently used emoji blended into the suggestions.
+    const RECENT_EMOJI: usize = 10;
+    crate::modes::emoji::recent_items(&AutoRunType::Emoji, config, RECENT_EMOJI)
+}
+
+#[cfg(not(feature = "mode-emoji"))]
+fn recent_emoji(_: &Config) -> Vec<MenuItem<AutoRunType>> {
+    Vec::new()
+}
+
 fn contains_math_functions_or_starts_with_number(input: &str) -> bool {
     // Regex for function names (word boundaries to match whole words)
     static MATH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -100,15 +211,36 @@ fn contains_math_functions_or_starts_with_number(input: &str) -> bool {
 
 impl ItemProvider<AutoRunType> for AutoItemProvider {
     fn get_elements(&mut self, search_opt: Option<&str>) -> ProviderData<AutoRunType> {
+        // the view is refreshed periodically for the info commands, other modes keep their items
+        let refresh = self.last_mode.is_some()
+            && self.last_search.as_deref() == search_opt
+            && self.info.refresh_interval().is_some();
+        self.last_search = search_opt.map(str::to_owned);
+        if refresh && self.last_mode != Some(AutoRunType::Info) {
+            return ProviderData::Unchanged;
+        }
+
+        if let Some(mode) = self.forced_mode.clone() {
+            self.last_mode = Some(mode.clone());
+            return self.elements_of_mode(&mode, search_opt);
+        }
+
         let search = match search_opt {
             Some(s) if !s.trim().is_empty() => s.trim(),
             _ => "",
         };
 
-        let (mode, provider_data) = if contains_math_functions_or_starts_with_number(search) {
+        let (mode, provider_data) = if self.info.matches(search) {
+            (AutoRunType::Info, self.info.get_elements(Some(search)))
+        } else if contains_math_functions_or_starts_with_number(search) {
             (AutoRunType::Math, self.math.get_elements(search_opt))
         } else if search.starts_with('$') || search.starts_with('/') || search.starts_with('~') {
             (AutoRunType::File, self.file.get_elements(search_opt))
+        } else if self.open_urls && url_of(search).is_some() {
+            (
+                AutoRunType::WebSearch,
+                self.search.get_elements(Some(search)),
+            )
         } else if search.starts_with("ssh") {
             (AutoRunType::Ssh, self.ssh.get_elements(search_opt))
         } else if search.starts_with('?') {
@@ -119,6 +251,15 @@ impl ItemProvider<AutoRunType> for AutoItemProvider {
                 AutoRunType::WebSearch,
                 self.search.get_elements(Some(&query)),
             )
+        } else if !search.is_empty()
+            && let Some(weights) = self.suggestion_weights.clone()
+        {
+            // the websearch row changes with every search,
+            // so the default entries are loaded again once the search is cleared
+            (
+                AutoRunType::WebSearch,
+                self.suggestion_elements(search, &weights),
+            )
         } else {
             (AutoRunType::Auto, self.default_auto_elements())
         };
@@ -135,12 +276,56 @@ impl ItemProvider<AutoRunType> for AutoItemProvider {
                 AutoRunType::File => self.file.get_sub_elements(item),
                 AutoRunType::Ssh => self.ssh.get_sub_elements(item),
                 AutoRunType::WebSearch => self.search.get_sub_elements(item),
-                AutoRunType::Auto => ProviderData { items: None },
+                AutoRunType::Info | AutoRunType::Emoji | AutoRunType::Auto => {
+                    ProviderData::Unchanged
+                }
             }
         } else {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         }
     }
+
+    fn allow_auto_select(&self, item: &MenuItem<AutoRunType>) -> bool {
+        item.data != Some(AutoRunType::File) || self.file.allow_auto_select(item)
+    }
+
+    fn preview(&self, item: &MenuItem<AutoRunType>) -> Option<String> {
+        if item.data == Some(AutoRunType::File) {
+            self.file.preview(item)
+        } else {
+            item.preview.clone()
+        }
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.info.refresh_interval()
+    }
+
+    fn switch_mode(&mut self) -> Option<String> {
+        let next = match &self.forced_mode {
+            None => self.switch_modes.first(),
+            Some(current) => self
+                .switch_modes
+                .iter()
+                .skip_while(|mode| *mode != current)
+                .nth(1),
+        };
+        self.forced_mode = next.cloned();
+        // the default elements have to be loaded again after leaving a forced mode
+        self.last_mode = None;
+
+        Some(
+            match &self.forced_mode {
+                Some(AutoRunType::Math) => "math",
+                Some(AutoRunType::DRun) => "drun",
+                Some(AutoRunType::File) => "file",
+                Some(AutoRunType::Ssh) => "ssh",
+                Some(AutoRunType::WebSearch) => "websearch",
+                Some(AutoRunType::Info | AutoRunType::Emoji | AutoRunType::Auto) | None => "auto",
+            }
+            .to_owned(),
+        )
+    }
 }
 
 /// Shows the auto mode
@@ -154,13 +339,11 @@ impl ItemProvider<AutoRunType> for AutoItemProvider {
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
     let provider = Arc::new(Mutex::new(AutoItemProvider::new(&config.read().unwrap())));
     let arc_provider = Arc::clone(&provider) as ArcProvider<AutoRunType>;
-    let cache_path = provider.lock().unwrap().drun.cache_path.clone();
-    let mut cache = provider.lock().unwrap().drun.cache.clone();
 
     loop {
         provider.lock().unwrap().last_mode = None;
         let selection_result = gui::show(
-            config,
+            &config.read().unwrap(),
             Arc::clone(&arc_provider),
             Some(Arc::new(Mutex::new(DefaultItemFactory::new()))),
             Some(
@@ -171,51 +354,65 @@ pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
             ),
             ExpandMode::Verbatim,
             None,
-        );
-
-        if let Ok(selection_result) = selection_result {
-            let mut selection_result = selection_result.menu;
-            if let Some(data) = &selection_result.data {
-                match data {
-                    AutoRunType::Math => {
-                        provider
-                            .lock()
-                            .unwrap()
-                            .math
-                            .elements
-                            .push(selection_result);
-                    }
-                    AutoRunType::DRun => {
-                        update_drun_cache_and_run(&cache_path, &mut cache, selection_result)?;
-                        break;
-                    }
-                    AutoRunType::File => {
-                        if let Some(action) = selection_result.action {
-                            spawn_fork(&action, selection_result.working_dir.as_ref())?;
-                        }
-                        break;
+        )?;
+
+        let mut selection_result = selection_result.menu;
+        if let Some(data) = &selection_result.data {
+            match data {
+                AutoRunType::Math => {
+                    provider
+                        .lock()
+                        .unwrap()
+                        .math
+                        .elements
+                        .push(selection_result);
+                }
+                AutoRunType::DRun => {
+                    drun::update_history_and_run(selection_result, &config.read().unwrap())?;
+                    break;
+                }
+                AutoRunType::File => {
+                    if let Some(action) = selection_result.action {
+                        launch(
+                            &action,
+                            selection_result.working_dir.as_ref(),
+                            &selection_result.env,
+                            &config.read().unwrap(),
+                        )?;
                     }
-                    AutoRunType::Ssh => {
-                        ssh::launch(&selection_result, &config.read().unwrap())?;
-                        break;
+                    break;
+                }
+                AutoRunType::Ssh => {
+                    ssh::launch(&selection_result, &config.read().unwrap())?;
+                    break;
+                }
+                AutoRunType::WebSearch => {
+                    if let Some(action) = selection_result.action {
+                        launch(&action, None, &[], &config.read().unwrap())?;
                     }
-                    AutoRunType::WebSearch => {
-                        if let Some(action) = selection_result.action {
-                            spawn_fork(&action, None)?;
-                        }
+                    break;
+                }
+                AutoRunType::Info => {
+                    // still running or failed, nothing to copy yet
+                    if let Some(action) = selection_result.action {
+                        copy_to_clipboard(action, None)?;
                         break;
                     }
-                    AutoRunType::Auto => {
-                        unreachable!("Auto mode should never be set for show.")
+                }
+                AutoRunType::Emoji => {
+                    if let Some(action) = selection_result.action {
+                        history::record_usage("emoji", &action, &config.read().unwrap());
+                        copy_to_clipboard(action, None)?;
                     }
+                    break;
+                }
+                AutoRunType::Auto => {
+                    unreachable!("Auto mode should never be set for show.")
                 }
-            } else if selection_result.label.starts_with("ssh") {
-                selection_result.label = selection_result.label.chars().skip(4).collect();
-                ssh::launch(&selection_result, &config.read().unwrap())?;
             }
-        } else {
-            log::error!("No item selected");
-            break;
+        } else if selection_result.label.starts_with("ssh") {
+            selection_result.label = selection_result.label.chars().skip(4).collect();
+            ssh::launch(&selection_result, &config.read().unwrap())?;
         }
     }
 
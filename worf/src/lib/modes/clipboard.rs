@@ -0,0 +1,455 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+use regex::Regex;
+
+use crate::{
+    Error,
+    config::{ClipboardBackend, Config, Key, TextOutputMode},
+    desktop::{copy_data_to_clipboard, output_text},
+    gui::{
+        self, CustomKeys, ExpandMode, ItemProvider, KeyBinding, MenuItem, Modifier, ProviderData,
+    },
+};
+
+/// Maximum amount of characters shown for a single history entry.
+const PREVIEW_LENGTH: usize = 100;
+
+/// A single entry in the clipboard history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipboardEntry {
+    /// Backend specific id of the entry, used to retrieve or delete it.
+    pub id: String,
+    /// Single line preview of the content.
+    pub preview: String,
+}
+
+impl ClipboardEntry {
+    /// Image format of binary entries, cliphist previews them as
+    /// `[[ binary data 12 KiB png 100x100 ]]`.
+    #[must_use]
+    pub fn image_format(&self) -> Option<&str> {
+        static RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^\[\[ binary data .* (\w+) \d+x\d+ \]\]$").unwrap());
+        RE.captures(&self.preview)
+            .and_then(|caps| caps.get(1))
+            .map(|format| format.as_str())
+    }
+}
+
+/// Access to the history of a clipboard manager.
+/// Entries are listed newest first.
+pub trait ClipboardHistory {
+    /// List all entries of the history.
+    /// # Errors
+    /// Will return an error if the clipboard manager cannot be queried.
+    fn list(&self) -> Result<Vec<ClipboardEntry>, Error>;
+
+    /// Retrieve the full content of an entry.
+    /// # Errors
+    /// Will return an error if the entry cannot be retrieved.
+    fn get(&self, entry: &ClipboardEntry) -> Result<Vec<u8>, Error>;
+
+    /// Remove an entry from the history.
+    /// # Errors
+    /// Will return an error if the entry cannot be deleted.
+    fn delete(&self, entry: &ClipboardEntry) -> Result<(), Error>;
+}
+
+/// Creates the history implementation for the given backend.
+#[must_use]
+pub fn history(backend: &ClipboardBackend) -> Box<dyn ClipboardHistory> {
+    match backend {
+        ClipboardBackend::Cliphist => Box::new(Cliphist),
+        ClipboardBackend::Clipman => Box::new(Clipman::new()),
+        ClipboardBackend::CopyQ => Box::new(CopyQ),
+    }
+}
+
+/// <https://github.com/sentriz/cliphist>
+struct Cliphist;
+
+impl ClipboardHistory for Cliphist {
+    fn list(&self) -> Result<Vec<ClipboardEntry>, Error> {
+        Ok(cliphist_entries(&run("cliphist", &["list"], None)?))
+    }
+
+    fn get(&self, entry: &ClipboardEntry) -> Result<Vec<u8>, Error> {
+        run("cliphist", &["decode"], Some(&cliphist_line(entry)))
+    }
+
+    fn delete(&self, entry: &ClipboardEntry) -> Result<(), Error> {
+        run("cliphist", &["delete"], Some(&cliphist_line(entry))).map(|_| ())
+    }
+}
+
+/// Parses the output of `cliphist list`, one `id\tpreview` line per entry.
+fn cliphist_entries(output: &[u8]) -> Vec<ClipboardEntry> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(id, preview)| ClipboardEntry {
+            id: id.to_owned(),
+            preview: preview_of(preview),
+        })
+        .collect()
+}
+
+/// cliphist expects the line as printed by `list`, only the id is evaluated though.
+fn cliphist_line(entry: &ClipboardEntry) -> String {
+    format!("{}\t{}\n", entry.id, entry.preview)
+}
+
+/// <https://github.com/chmouel/clipman>
+/// clipman has no command to list the history, so its history file is read directly.
+struct Clipman {
+    history_file: PathBuf,
+}
+
+impl Clipman {
+    fn new() -> Self {
+        Self {
+            history_file: dirs::data_dir().unwrap_or_default().join("clipman.json"),
+        }
+    }
+
+    /// The history as stored by clipman, oldest first.
+    fn load(&self) -> Result<Vec<String>, Error> {
+        let content =
+            fs::read_to_string(&self.history_file).map_err(|e| Error::Io(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| Error::ParsingError(e.to_string()))
+    }
+}
+
+fn clipman_index(entry: &ClipboardEntry) -> Result<usize, Error> {
+    entry
+        .id
+        .parse::<usize>()
+        .map_err(|e| Error::InvalidArgument(e.to_string()))
+}
+
+/// The entries of the clipman history, newest first. The id is the index in the history file.
+fn clipman_entries(history: &[String]) -> Vec<ClipboardEntry> {
+    history
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, content)| ClipboardEntry {
+            id: i.to_string(),
+            preview: preview_of(content),
+        })
+        .collect()
+}
+
+impl ClipboardHistory for Clipman {
+    fn list(&self) -> Result<Vec<ClipboardEntry>, Error> {
+        Ok(clipman_entries(&self.load()?))
+    }
+
+    fn get(&self, entry: &ClipboardEntry) -> Result<Vec<u8>, Error> {
+        self.load()?
+            .get(clipman_index(entry)?)
+            .map(|content| content.as_bytes().to_vec())
+            .ok_or(Error::InvalidSelection)
+    }
+
+    fn delete(&self, entry: &ClipboardEntry) -> Result<(), Error> {
+        let mut history = self.load()?;
+        let index = clipman_index(entry)?;
+        if index >= history.len() {
+            return Err(Error::InvalidSelection);
+        }
+        history.remove(index);
+
+        let content =
+            serde_json::to_string(&history).map_err(|e| Error::ParsingError(e.to_string()))?;
+        fs::write(&self.history_file, content).map_err(|e| Error::Io(e.to_string()))
+    }
+}
+
+/// <https://hluk.github.io/CopyQ/>
+struct CopyQ;
+
+impl ClipboardHistory for CopyQ {
+    fn list(&self) -> Result<Vec<ClipboardEntry>, Error> {
+        let output = run(
+            "copyq",
+            &[
+                "eval",
+                "--",
+                "for (var i = 0; i < size(); ++i) print(i + '\\t' + str(read(i)) + '\\0')",
+            ],
+            None,
+        )?;
+        Ok(copyq_entries(&output))
+    }
+
+    fn get(&self, entry: &ClipboardEntry) -> Result<Vec<u8>, Error> {
+        run("copyq", &["read", &entry.id], None)
+    }
+
+    fn delete(&self, entry: &ClipboardEntry) -> Result<(), Error> {
+        run("copyq", &["remove", &entry.id], None).map(|_| ())
+    }
+}
+
+/// Parses the rows printed by `CopyQ::list` as `row\tcontent`, separated by a null byte,
+/// as content may contain new lines itself.
+fn copyq_entries(output: &[u8]) -> Vec<ClipboardEntry> {
+    String::from_utf8_lossy(output)
+        .split('\0')
+        .filter_map(|row| row.split_once('\t'))
+        .map(|(id, content)| ClipboardEntry {
+            id: id.to_owned(),
+            preview: preview_of(content),
+        })
+        .collect()
+}
+
+/// Collapses the content into a single line and shortens it.
+fn preview_of(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(PREVIEW_LENGTH)
+        .collect()
+}
+
+fn run(cmd: &str, args: &[&str], stdin: Option<&str>) -> Result<Vec<u8>, Error> {
+    let mut child = Command::new(cmd)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::RunFailed(format!("cannot run {cmd}: {e}")))?;
+
+    if let Some(input) = stdin
+        && let Some(mut pipe) = child.stdin.take()
+    {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| Error::Io(e.to_string()))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| Error::RunFailed(e.to_string()))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(Error::RunFailed(format!(
+            "{cmd} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+const DELETE_LABEL: &str = "Delete entry";
+
+#[derive(Clone)]
+struct ClipboardProvider {
+    items: Vec<MenuItem<ClipboardEntry>>,
+}
+
+impl ItemProvider<ClipboardEntry> for ClipboardProvider {
+    fn get_elements(&mut self, _: Option<&str>) -> ProviderData<ClipboardEntry> {
+        ProviderData::Items(self.items.clone())
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<ClipboardEntry>) -> ProviderData<ClipboardEntry> {
+        ProviderData::Unchanged
+    }
+}
+
+/// Decodes the image entries into the cache dir, so they are shown as icon.
+/// Thumbnails of entries which left the history are removed.
+fn thumbnails(history: &dyn ClipboardHistory, entries: &[ClipboardEntry]) -> Vec<Option<String>> {
+    let Some(dir) = dirs::cache_dir().map(|dir| dir.join("worf-clipboard")) else {
+        return vec![None; entries.len()];
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::warn!("cannot create thumbnail dir {}: {e}", dir.display());
+        return vec![None; entries.len()];
+    }
+
+    let paths: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .image_format()
+                .map(|format| dir.join(format!("{}.{format}", entry.id)))
+        })
+        .collect();
+    let current: HashSet<_> = paths.iter().flatten().collect();
+    for file in fs::read_dir(&dir).into_iter().flatten().flatten() {
+        if !current.contains(&file.path()) {
+            let _ = fs::remove_file(file.path());
+        }
+    }
+
+    entries
+        .iter()
+        .zip(&paths)
+        .map(|(entry, path)| {
+            let path = path.as_ref()?;
+            if !path.exists() {
+                let data = history
+                    .get(entry)
+                    .inspect_err(|e| log::warn!("cannot decode clipboard image: {e}"))
+                    .ok()?;
+                fs::write(path, data).ok()?;
+            }
+            Some(path.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// Shift+Delete removes the selected entry from the history.
+#[must_use]
+pub fn custom_keys() -> CustomKeys {
+    CustomKeys {
+        bindings: vec![KeyBinding {
+            key: Key::Delete,
+            modifiers: HashSet::from([Modifier::Shift]),
+            label: DELETE_LABEL.to_owned(),
+            visible: true,
+        }],
+        hint: None,
+    }
+}
+
+/// Shows the clipboard history of the configured backend,
+/// the selected entry is copied again and Shift+Delete removes it from the history.
+/// # Errors
+///
+/// Will return `Err`
+/// * if the clipboard manager cannot be queried
+/// * if the entry cannot be copied or deleted
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let history = history(&config.read().unwrap().clipboard_backend());
+    let custom_keys = custom_keys();
+
+    loop {
+        let entries = history.list()?;
+        let thumbnails = thumbnails(history.as_ref(), &entries);
+        let mut items: Vec<_> = entries
+            .into_iter()
+            .zip(thumbnails)
+            .map(|(entry, thumbnail)| {
+                MenuItem::new(
+                    entry.preview.clone(),
+                    thumbnail,
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    Some(entry),
+                )
+            })
+            .collect();
+        // the newest entry is shown on top, unless the search ranks it lower
+        gui::keep_order(&mut items);
+
+        let selection = gui::show(
+            &config.read().unwrap(),
+            Arc::new(Mutex::new(ClipboardProvider { items })),
+            None,
+            None,
+            ExpandMode::Verbatim,
+            Some(custom_keys.clone()),
+        )?;
+        let entry = selection.menu.data.ok_or(Error::InvalidSelection)?;
+
+        if selection
+            .custom_key
+            .is_some_and(|key| key.label == DELETE_LABEL)
+        {
+            history.delete(&entry)?;
+            continue;
+        }
+
+        let content = history.get(&entry)?;
+        return if entry.image_format().is_some() {
+            copy_data_to_clipboard(content, None)
+        } else {
+            let config = config.read().unwrap();
+            let output_mode = config.action_or(TextOutputMode::Clipboard);
+            output_text(&String::from_utf8_lossy(&content), &output_mode, &config)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, preview: &str) -> ClipboardEntry {
+        ClipboardEntry {
+            id: id.to_owned(),
+            preview: preview.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_cliphist_entries() {
+        let output =
+            b"12\thello  world\n11\t[[ binary data 12 KiB png 100x100 ]]\n10\tcontains\ta tab\n";
+        let entries = cliphist_entries(output);
+        assert_eq!(
+            entries,
+            [
+                entry("12", "hello world"),
+                entry("11", "[[ binary data 12 KiB png 100x100 ]]"),
+                entry("10", "contains a tab"),
+            ]
+        );
+        assert_eq!(entries[0].image_format(), None);
+        assert_eq!(entries[1].image_format(), Some("png"));
+    }
+
+    #[test]
+    fn test_cliphist_entries_skip_lines_without_id() {
+        assert_eq!(
+            cliphist_entries(b"\nno id\n3\tentry\n"),
+            [entry("3", "entry")]
+        );
+    }
+
+    #[test]
+    fn test_clipman_entries_are_listed_newest_first() {
+        let history = ["first".to_owned(), "second\nline".to_owned()];
+        assert_eq!(
+            clipman_entries(&history),
+            [entry("1", "second line"), entry("0", "first")]
+        );
+    }
+
+    #[test]
+    fn test_copyq_entries_keep_multi_line_content_together() {
+        let output = b"0\tfn main() {\n    println!();\n}\n\x001\tplain\0";
+        assert_eq!(
+            copyq_entries(output),
+            [entry("0", "fn main() { println!(); }"), entry("1", "plain")]
+        );
+    }
+
+    #[test]
+    fn test_preview_is_shortened() {
+        let content = "a".repeat(PREVIEW_LENGTH * 2);
+        assert_eq!(preview_of(&content).chars().count(), PREVIEW_LENGTH);
+    }
+}
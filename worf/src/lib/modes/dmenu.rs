@@ -1,17 +1,122 @@
 use std::{
-    io::{self, Read},
+    io::{self, BufRead, Read},
     sync::{Arc, Mutex, RwLock},
+    thread,
+    time::Duration,
 };
 
 use crate::{
     Error,
-    config::{Config, SortOrder},
-    gui::{self, DefaultItemFactory, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    config::{Config, DmenuCustomKey, KeyBindingSpec, SortOrder, TextOutputMode},
+    desktop::output_text,
+    gui::{
+        self, ArcFactory, CustomKeys, DefaultItemFactory, ExpandMode, ItemProvider, KeyBinding,
+        MenuItem, ProviderData,
+    },
 };
 
+/// Exit code of the first custom key without one, the following keys count up.
+const FIRST_CUSTOM_KEY_EXIT_CODE: i32 = 10;
+
+/// An item received via stdin in streaming mode
+#[derive(Clone, Debug, PartialEq)]
+struct StreamEntry {
+    id: String,
+    label: String,
+    icon: Option<String>,
+}
+
+/// Items received via stdin in streaming mode.
+/// Plain lines are appended, their id is the number of the line (starting at 0).
+/// Lines starting with the control prefix modify the list:
+/// * `<prefix>clear` removes all items
+/// * `<prefix>add <id> <label>` adds an item with the given id, or replaces its label
+/// * `<prefix>remove <id>` removes the item with the given id
+/// * `<prefix>label <id> <label>` changes the label of the item with the given id
+/// * `<prefix>icon <id> <icon>` changes the icon of the item with the given id
+#[derive(Default)]
+struct StreamState {
+    entries: Vec<StreamEntry>,
+    next_id: usize,
+    dirty: bool,
+}
+
+impl StreamState {
+    fn apply_line(&mut self, line: &str, control_prefix: &str) {
+        let Some(control) = line.strip_prefix(control_prefix) else {
+            let id = self.next_id.to_string();
+            self.next_id += 1;
+            self.entries.push(StreamEntry {
+                id,
+                label: line.to_owned(),
+                icon: None,
+            });
+            self.dirty = true;
+            return;
+        };
+
+        let mut parts = control.splitn(3, ' ');
+        let command = parts.next().unwrap_or_default();
+        let id = parts.next().unwrap_or_default();
+        let value = parts.next().unwrap_or_default();
+        match command {
+            "clear" => self.entries.clear(),
+            "add" => {
+                if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+                    value.clone_into(&mut entry.label);
+                } else {
+                    self.entries.push(StreamEntry {
+                        id: id.to_owned(),
+                        label: value.to_owned(),
+                        icon: None,
+                    });
+                }
+            }
+            "remove" => self.entries.retain(|e| e.id != id),
+            "label" | "icon" => {
+                let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) else {
+                    log::warn!("no dmenu item with id {id}");
+                    return;
+                };
+                if command == "label" {
+                    value.clone_into(&mut entry.label);
+                } else {
+                    entry.icon = Some(value.to_owned()).filter(|icon| !icon.is_empty());
+                }
+            }
+            _ => {
+                log::warn!("unknown dmenu control command {control}");
+                return;
+            }
+        }
+        self.dirty = true;
+    }
+
+    fn menu_items(&self) -> Vec<MenuItem<String>> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|e| {
+                MenuItem::new(
+                    e.label.clone(),
+                    e.icon.clone(),
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    None,
+                )
+                .with_id(e.id.clone())
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone)]
 struct DMenuProvider {
     items: Vec<MenuItem<String>>,
+    stream: Option<Arc<Mutex<StreamState>>>,
+    sort_order: SortOrder,
 }
 
 impl DMenuProvider {
@@ -22,56 +127,380 @@ impl DMenuProvider {
             .read_to_string(&mut input)
             .expect("Failed to read from stdin");
 
-        let mut items: Vec<MenuItem<String>> = input
-            .lines()
+        let provider = Self::from_lines(&input.lines().collect::<Vec<_>>(), sort_order);
+        log::debug!("parsed stdin");
+        provider
+    }
+
+    /// Uses the items given via `--items` instead of reading stdin,
+    /// empty items are skipped.
+    fn from_args(items: &str, delimiter: &str, sort_order: &SortOrder) -> DMenuProvider {
+        let items: Vec<_> = items
+            .split(delimiter)
+            .filter(|item| !item.is_empty())
+            .collect();
+        Self::from_lines(&items, sort_order)
+    }
+
+    fn from_lines(lines: &[&str], sort_order: &SortOrder) -> DMenuProvider {
+        let mut items: Vec<MenuItem<String>> = lines
+            .iter()
+            .enumerate()
             .rev()
-            .map(|s| MenuItem::new(s.to_string(), None, None, vec![], None, 0.0, None))
+            .map(|(i, s)| {
+                MenuItem::new((*s).to_string(), None, None, vec![], None, 0.0, None)
+                    .with_id(i.to_string())
+            })
             .collect();
-        log::debug!("parsed stdin");
         gui::apply_sort(&mut items, sort_order);
-        Self { items }
+        Self {
+            items,
+            stream: None,
+            sort_order: sort_order.clone(),
+        }
+    }
+
+    /// Keeps reading stdin in the background while the menu is shown.
+    fn new_streaming(sort_order: &SortOrder, control_prefix: String) -> DMenuProvider {
+        let stream = Arc::new(Mutex::new(StreamState::default()));
+        let state = Arc::clone(&stream);
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                match line {
+                    Ok(line) => state.lock().unwrap().apply_line(&line, &control_prefix),
+                    Err(e) => {
+                        log::error!("failed to read from stdin {e}");
+                        break;
+                    }
+                }
+            }
+            log::debug!("stdin closed");
+        });
+
+        Self {
+            items: Vec::new(),
+            stream: Some(stream),
+            sort_order: sort_order.clone(),
+        }
     }
 }
 impl ItemProvider<String> for DMenuProvider {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<String> {
+        if let Some(stream) = &self.stream {
+            let mut state = stream.lock().unwrap();
+            if state.dirty || query.is_none() {
+                state.dirty = false;
+                self.items = state.menu_items();
+                gui::apply_sort(&mut self.items, &self.sort_order);
+                return ProviderData::Items(self.items.clone());
+            }
+            return ProviderData::Unchanged;
+        }
+
         if query.is_some() {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
-            ProviderData {
-                items: Some(self.items.clone()),
-            }
+            ProviderData::Items(self.items.clone())
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<String>) -> ProviderData<String> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.stream.as_ref().map(|_| Duration::from_millis(100))
+    }
+
+    /// The full text of the line, long lines are cut or wrapped in the list.
+    fn preview(&self, item: &MenuItem<String>) -> Option<String> {
+        let (_, _, text) = gui::parse_label(&item.label);
+        text
     }
 }
 
+/// Bindings of the configured custom keys with their exit codes, invalid keys are skipped.
+fn custom_key_bindings(custom_keys: &[DmenuCustomKey]) -> Vec<(KeyBinding, i32)> {
+    custom_keys
+        .iter()
+        .zip(FIRST_CUSTOM_KEY_EXIT_CODE..)
+        .filter_map(
+            |(custom_key, default_code)| match custom_key.key.parse::<KeyBindingSpec>() {
+                Ok(KeyBindingSpec { key, modifiers }) => Some((
+                    KeyBinding {
+                        key,
+                        modifiers,
+                        label: custom_key.label.clone(),
+                        visible: true,
+                    },
+                    custom_key.exit_code.unwrap_or(default_code),
+                )),
+                Err(e) => {
+                    log::warn!("ignoring custom key {}: {e}", custom_key.label);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// Keys of the `[[custom_key]]` entries of the config, `None` if there are none.
+#[must_use]
+pub fn custom_keys(config: &Config) -> Option<CustomKeys> {
+    custom_keys_of(&custom_key_bindings(&config.custom_keys()))
+}
+
+fn custom_keys_of(bindings: &[(KeyBinding, i32)]) -> Option<CustomKeys> {
+    (!bindings.is_empty()).then(|| CustomKeys {
+        bindings: bindings
+            .iter()
+            .map(|(binding, _)| binding.clone())
+            .collect(),
+        hint: None,
+    })
+}
+
 /// Shows the dmenu mode
 /// # Errors
 ///
 /// Forwards errors from the gui. See `gui::show` for details.
+/// Returns `Error::CustomKey` with the exit code of the key
+/// if the selection was made with a custom key.
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let provider = Arc::new(Mutex::new(DMenuProvider::new(
-        &config.read().unwrap().sort_order(),
-    )));
-
-    let selection_result = gui::show(
-        config,
-        provider,
-        Some(Arc::new(Mutex::new(DefaultItemFactory::new()))),
-        None,
-        ExpandMode::Verbatim,
-        None,
-    );
-    match selection_result {
-        Ok(s) => {
-            println!("{}", s.menu.label);
-            Ok(())
+    let provider = {
+        let cfg = config.read().unwrap();
+        if let Some(items) = cfg.dmenu_items() {
+            DMenuProvider::from_args(&items, &cfg.dmenu_items_delimiter(), &cfg.sort_order())
+        } else if cfg.dmenu_stream() {
+            DMenuProvider::new_streaming(&cfg.sort_order(), cfg.dmenu_control_prefix())
+        } else {
+            DMenuProvider::new(&cfg.sort_order())
         }
-        Err(_) => Err(Error::InvalidSelection),
+    };
+
+    let config = config.read().unwrap();
+    let provider = Arc::new(Mutex::new(provider));
+    let factory = Some(Arc::new(Mutex::new(DefaultItemFactory::new())) as ArcFactory<String>);
+    let bindings = custom_key_bindings(&config.custom_keys());
+    let custom_keys = custom_keys_of(&bindings);
+    let selections = if config.multi_select() {
+        gui::show_multi(
+            &config,
+            provider,
+            factory,
+            None,
+            ExpandMode::Verbatim,
+            custom_keys,
+        )?
+    } else {
+        vec![gui::show(
+            &config,
+            provider,
+            factory,
+            None,
+            ExpandMode::Verbatim,
+            custom_keys,
+        )?]
+    };
+    let exit_code = selections
+        .first()
+        .and_then(|selection| selection.custom_key.as_ref())
+        .and_then(|key| bindings.iter().find(|(binding, _)| binding == key))
+        .map(|(_, code)| *code);
+
+    let output: Vec<_> = selections
+        .iter()
+        .map(|selection| {
+            if config.dmenu_index() {
+                // items created from the search text have no id
+                selection.menu.id.as_deref().unwrap_or("-1")
+            } else {
+                selection.menu.label.as_str()
+            }
+        })
+        .collect();
+    let output_mode = config.action_or(TextOutputMode::StandardOutput);
+    output_text(&output.join("\n"), &output_mode, &config)?;
+    match exit_code {
+        Some(code) => Err(Error::CustomKey(code)),
+        None => Ok(()),
+    }
+}
+
+/// Translates the command line of dmenu into worf arguments,
+/// so worf can be used by scripts calling dmenu, i.e. via a `dmenu` symlink.
+/// Options worf shares with dmenu and unknown arguments are kept.
+/// Colors and fonts are ignored, use the style sheet instead.
+#[must_use]
+pub fn compat_args(args: &[String]) -> Vec<String> {
+    let mut iter = args.iter();
+    let mut translated: Vec<String> = iter.next().into_iter().cloned().collect();
+    translated.extend(["--show".to_owned(), "dmenu".to_owned()]);
+
+    while let Some(arg) = iter.next() {
+        let replacement: &[&str] = match arg.as_str() {
+            "-i" => &["--insensitive", "true"],
+            "-b" => &["--location", "bottom"],
+            "-l" => &["--lines"],
+            "-format" => {
+                // rofi style, `i` prints the index
+                if iter.next().is_some_and(|format| format == "i") {
+                    translated.extend(["--dmenu-index".to_owned(), "true".to_owned()]);
+                }
+                continue;
+            }
+            "-nb" | "-nf" | "-sb" | "-sf" | "-fn" | "-m" | "-w" => {
+                log::debug!("ignoring dmenu option {arg} {:?}", iter.next());
+                continue;
+            }
+            "-f" => continue,
+            _ => {
+                translated.push(arg.clone());
+                continue;
+            }
+        };
+        translated.extend(replacement.iter().map(|&s| s.to_owned()));
+    }
+    translated
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{config::Key, gui::Modifier};
+
+    #[test]
+    fn test_stream_control_lines() {
+        let mut state = StreamState::default();
+        for line in [
+            "first",
+            "second",
+            "worf::label 0 renamed",
+            "worf::icon 1 audio-card",
+            "worf::add dl download 10%",
+            "worf::add dl download 50%",
+            "worf::remove 0",
+        ] {
+            state.apply_line(line, "worf::");
+        }
+
+        assert_eq!(
+            state.entries,
+            vec![
+                StreamEntry {
+                    id: "1".to_owned(),
+                    label: "second".to_owned(),
+                    icon: Some("audio-card".to_owned()),
+                },
+                StreamEntry {
+                    id: "dl".to_owned(),
+                    label: "download 50%".to_owned(),
+                    icon: None,
+                },
+            ]
+        );
+
+        state.apply_line("worf::clear", "worf::");
+        assert!(state.entries.is_empty());
+    }
+
+    #[test]
+    fn test_items_from_args() {
+        let provider = DMenuProvider::from_args("a,b,,c,", ",", &SortOrder::Default);
+        let items: Vec<_> = provider
+            .items
+            .iter()
+            .map(|item| (item.label.as_str(), item.id.as_deref()))
+            .collect();
+        // like stdin lines, the ids are the 0-based positions
+        assert_eq!(
+            items,
+            vec![("c", Some("2")), ("b", Some("1")), ("a", Some("0"))]
+        );
+
+        let provider = DMenuProvider::from_args("one two|three", "|", &SortOrder::Default);
+        let labels: Vec<_> = provider.items.iter().map(|item| &item.label).collect();
+        assert_eq!(labels, vec!["three", "one two"]);
+    }
+
+    #[test]
+    fn test_custom_key_bindings() {
+        let custom_key = |key: &str, exit_code| DmenuCustomKey {
+            key: key.to_owned(),
+            label: key.to_owned(),
+            exit_code,
+        };
+        let bindings = custom_key_bindings(&[
+            custom_key("Alt+1", None),
+            custom_key("Hyper+x", None),
+            custom_key("ctrl+shift+e", Some(3)),
+            custom_key("Alt++", None),
+            custom_key("F5", None),
+        ]);
+        let keys: Vec<_> = bindings
+            .iter()
+            .map(|(binding, code)| (binding.key, binding.modifiers.clone(), *code))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (Key::Num1, HashSet::from([Modifier::Alt]), 10),
+                (
+                    Key::E,
+                    HashSet::from([Modifier::Control, Modifier::Shift]),
+                    3
+                ),
+                (Key::Plus, HashSet::from([Modifier::Alt]), 13),
+                (Key::F5, HashSet::from([Modifier::None]), 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_keys_conflict_with_built_in_keys() {
+        let config: Config = toml::from_str(
+            "[[custom_key]]\nkey = \"Enter\"\nlabel = \"edit\"\n\n\
+             [[custom_key]]\nkey = \"Alt+1\"\nlabel = \"copy\"",
+        )
+        .unwrap();
+        let conflicts: Vec<_> = gui::key_conflicts(&config, custom_keys(&config).as_ref())
+            .iter()
+            .map(|conflict| (conflict.key, conflict.action.clone()))
+            .collect();
+        assert_eq!(conflicts, vec![(Key::Enter, "custom key edit".to_owned())]);
+
+        assert!(custom_keys(&Config::default()).is_none());
+    }
+
+    #[test]
+    fn test_compat_args() {
+        let args: Vec<String> = [
+            "dmenu", "-i", "-l", "10", "-p", "Run:", "-sb", "#ff0000", "-b", "-format", "i",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(
+            compat_args(&args),
+            [
+                "dmenu",
+                "--show",
+                "dmenu",
+                "--insensitive",
+                "true",
+                "--lines",
+                "10",
+                "-p",
+                "Run:",
+                "--location",
+                "bottom",
+                "--dmenu-index",
+                "true",
+            ]
+        );
     }
 }
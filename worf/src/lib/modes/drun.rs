@@ -1,180 +1,580 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::PathBuf,
-    sync::{Arc, Mutex, RwLock},
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
     time::Instant,
 };
 
-use freedesktop_file_parser::EntryType;
+use crossbeam::channel::Sender;
+
+use freedesktop_file_parser::{DesktopFile, EntryType};
 use rayon::prelude::*;
 
 use crate::{
     Error,
-    config::{Config, SortOrder},
+    config::{Config, Key, LaunchMethod, SortOrder},
     desktop::{
-        find_desktop_files, get_locale_variants, lookup_name_with_locale, save_cache_file,
-        spawn_fork,
+        IconResolver, find_desktop_files_with_path, get_locale_variants, launch_app,
+        lookup_name_with_locale, save_cache_file,
+    },
+    gui::{
+        self, ArcProvider, CustomKeys, ExpandMode, ItemProvider, KeyBinding, MenuItem, Modifier,
+        ProviderData, Selection, SubmitDisposition,
     },
-    gui::{self, ArcProvider, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    history::History,
     modes::load_cache,
 };
 
+/// Css class of the badge showing why an entry is hidden, see `drun_include_hidden`.
+const HIDDEN_CLASS: &str = "hidden";
+
+const UNHIDE_LABEL: &str = "Unhide";
+
+/// Css class of the rows of pinned entries, see `key_pin`.
+const PINNED_CLASS: &str = "pinned";
+
+const PIN_LABEL: &str = "Pin";
+
+const HIDE_LABEL: &str = "Hide";
+
+/// Reason of entries hidden via `key_hide_entry`.
+const BLOCKED_REASON: &str = "Blacklisted";
+
+/// Entries per batch sent while the desktop files are read, see `get_elements_stream`.
+const STREAM_BATCH_SIZE: usize = 50;
+
+/// File listing the ids of the entries hidden via `key_hide_entry`, one per line.
+fn blocklist_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("worf").join("drun-blacklist"))
+}
+
+/// Ids of the hidden entries, lines starting with `#` are ignored.
+fn load_blocklist() -> HashSet<String> {
+    blocklist_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse_blocklist(&content))
+        .unwrap_or_default()
+}
+
+fn parse_blocklist(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Adds the id to the blocklist or removes it.
+fn set_blocked(id: &str, blocked: bool) -> Result<(), Error> {
+    let path = blocklist_path().ok_or(Error::MissingFile)?;
+    let mut ids = load_blocklist();
+    if blocked {
+        ids.insert(id.to_owned());
+    } else {
+        ids.remove(id);
+    }
+    let mut ids: Vec<_> = ids.into_iter().collect();
+    ids.sort();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::Io(e.to_string()))?;
+    }
+    let content: String = ids.iter().map(|id| format!("{id}\n")).collect();
+    fs::write(&path, content).map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Why the desktop file is not listed, `None` if it is.
+/// `desktops` are the current desktops if `desktop_env_filter` is enabled.
+fn hidden_reason(file: &DesktopFile, desktops: Option<&[String]>) -> Option<&'static str> {
+    if file.entry.hidden.unwrap_or(false) {
+        Some("Hidden")
+    } else if file.entry.no_display.unwrap_or(false) {
+        Some("NoDisplay")
+    } else if let Some(desktops) = desktops {
+        desktop_env_reason(
+            file.entry.only_show_in.as_deref(),
+            file.entry.not_show_in.as_deref(),
+            desktops,
+        )
+        .or_else(|| match &file.entry.entry_type {
+            EntryType::Application(app) => app
+                .try_exec
+                .as_ref()
+                .filter(|try_exec| find_binary(try_exec).is_none())
+                .map(|_| "TryExec not found"),
+            _ => None,
+        })
+    } else {
+        None
+    }
+}
+
+/// The desktops of `$XDG_CURRENT_DESKTOP`, which is a colon separated list.
+fn current_desktops() -> Vec<String> {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Checks `OnlyShowIn` and `NotShowIn` against the current desktops.
+/// Like glib, entries restricted to some desktops are hidden if the desktop is unknown.
+fn desktop_env_reason(
+    only_show_in: Option<&[String]>,
+    not_show_in: Option<&[String]>,
+    desktops: &[String],
+) -> Option<&'static str> {
+    let matches = |list: &[String]| {
+        list.iter()
+            .any(|entry| desktops.iter().any(|d| d.eq_ignore_ascii_case(entry)))
+    };
+    if only_show_in.is_some_and(|list| !matches(list)) {
+        Some("OnlyShowIn")
+    } else if not_show_in.is_some_and(matches) {
+        Some("NotShowIn")
+    } else {
+        None
+    }
+}
+
+/// Resolves a command as absolute path or via `$PATH`.
+fn find_binary(cmd: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(cmd);
+    if path.exists() {
+        Some(path)
+    } else {
+        which::which(cmd).ok()
+    }
+}
+
+/// Where an application comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Origin {
+    Native,
+    /// Native 32-bit application, i.e. multilib packages
+    Native32,
+    Flatpak,
+    Snap,
+}
+
+impl Origin {
+    fn detect(desktop_file: &Path, binary: Option<&Path>) -> Self {
+        let path = desktop_file.to_string_lossy();
+        if path.contains("/flatpak/") {
+            Origin::Flatpak
+        } else if path.contains("/snapd/") {
+            Origin::Snap
+        } else if binary.is_some_and(is_32bit_binary) {
+            Origin::Native32
+        } else {
+            Origin::Native
+        }
+    }
+
+    fn badge(self) -> Option<&'static str> {
+        match self {
+            Origin::Native => None,
+            Origin::Native32 => Some("32-bit"),
+            Origin::Flatpak => Some("flatpak"),
+            Origin::Snap => Some("snap"),
+        }
+    }
+}
+
+/// Checks the ELF header of the binary, scripts and unreadable files count as 64-bit.
+fn is_32bit_binary(path: &Path) -> bool {
+    const ELF_CLASS_32: u8 = 1;
+    let mut header = [0u8; 5];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header[..4] == *b"\x7fELF" && header[4] == ELF_CLASS_32)
+}
+
 #[derive(Clone)]
 pub(crate) struct DRunProvider<T: Clone> {
-    items: Option<Vec<MenuItem<T>>>,
-    pub(crate) cache_path: PathBuf,
-    pub(crate) cache: HashMap<String, i64>,
+    /// Shared with the thread loading them, see `get_elements_stream`
+    items: Arc<Mutex<Option<Vec<MenuItem<T>>>>>,
+    /// Set while the thread of `get_elements_stream` reads the desktop files
+    loading: Arc<AtomicBool>,
+    /// Pinned entries by id, the value is the order they were pinned in
+    pins: HashMap<String, i64>,
+    pins_path: PathBuf,
+    history: History,
     data: T,
     no_actions: bool,
     sort_order: SortOrder,
     terminal: Option<String>,
+    hidden_origins: HashSet<Origin>,
+    show_origin: bool,
+    include_hidden: bool,
+    /// Ids of the entries hidden via `key_hide_entry`
+    blocklist: HashSet<String>,
+    /// Current desktops if `desktop_env_filter` is enabled
+    desktops: Option<Vec<String>>,
+    icons: Arc<IconResolver>,
 }
 
-impl<T: Clone + Send + Sync> ItemProvider<T> for DRunProvider<T> {
+impl<T: Clone + Send + Sync + 'static> ItemProvider<T> for DRunProvider<T> {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<T> {
-        if self.items.is_none() {
-            self.items = Some(self.load().clone());
-        }
         if query.is_some() {
-            ProviderData { items: None }
-        } else {
-            ProviderData {
-                items: self.items.clone(),
+            return ProviderData::Unchanged;
+        }
+        let mut items = self.items.lock().unwrap();
+        if items.is_none() {
+            if self.loading.load(Ordering::Acquire) {
+                // streamed by get_elements_stream
+                return ProviderData::Unchanged;
             }
+            *items = Some(self.load());
         }
+        items
+            .clone()
+            .map_or(ProviderData::Unchanged, ProviderData::Items)
+    }
+
+    /// Reads the desktop files in the background, so the window opens right away.
+    /// The entries are sent in batches while they are read.
+    fn get_elements_stream(
+        &mut self,
+        query: Option<&str>,
+        sink: Sender<Vec<MenuItem<T>>>,
+    ) -> ProviderData<T> {
+        if query.is_some() || self.items.lock().unwrap().is_some() {
+            return self.get_elements(query);
+        }
+
+        let provider = self.clone();
+        self.loading.store(true, Ordering::Release);
+        thread::spawn(move || {
+            let items = provider.load_streaming(Some(&sink));
+            *provider.items.lock().unwrap() = Some(items);
+            provider.loading.store(false, Ordering::Release);
+            // ends the stream once the sorted items are available
+            drop(sink);
+        });
+        ProviderData::Items(Vec::new())
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<T>) -> ProviderData<T> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
+    }
+
+    /// The pin and hide keys change the entry and keep the window open.
+    fn on_submit(&mut self, selection: &Selection<T>) -> SubmitDisposition<T>
+    where
+        T: Send,
+    {
+        let id = selection.menu.key();
+        match selection.custom_key.as_ref().map(|key| key.label.as_str()) {
+            Some(PIN_LABEL) => self.toggle_pin(id),
+            Some(HIDE_LABEL) => {
+                if let Err(e) = set_blocked(id, true) {
+                    return SubmitDisposition::Reject(format!("Cannot hide {id}: {e}"));
+                }
+                self.blocklist.insert(id.to_owned());
+            }
+            _ => return SubmitDisposition::Accept,
+        }
+        let items = self.load();
+        *self.items.lock().unwrap() = Some(items.clone());
+        SubmitDisposition::Replace(items)
     }
 }
 
-impl<T: Clone + Send + Sync> DRunProvider<T> {
+impl<T: Clone + Send + Sync + 'static> DRunProvider<T> {
     pub(crate) fn new(menu_item_data: T, config: &Config) -> Self {
-        let (cache_path, d_run_cache) = load_cache("drun_cache", config).unwrap();
+        let (pins_path, pins) = load_cache("drun_pins", config).unwrap();
         DRunProvider {
-            items: None,
-            cache_path,
-            cache: d_run_cache,
+            items: Arc::new(Mutex::new(None)),
+            loading: Arc::new(AtomicBool::new(false)),
+            pins,
+            pins_path,
+            history: History::load("drun", config),
             data: menu_item_data,
             no_actions: config.no_actions(),
             sort_order: config.sort_order(),
             terminal: config.term(),
+            hidden_origins: [
+                (config.drun_hide_flatpak(), Origin::Flatpak),
+                (config.drun_hide_snap(), Origin::Snap),
+                (config.drun_hide_32bit(), Origin::Native32),
+            ]
+            .into_iter()
+            .filter_map(|(hide, origin)| hide.then_some(origin))
+            .collect(),
+            show_origin: config.drun_show_origin(),
+            include_hidden: config.drun_include_hidden(),
+            blocklist: load_blocklist(),
+            desktops: config.desktop_env_filter().then(current_desktops),
+            icons: Arc::new(IconResolver::load(config)),
         }
     }
 
     fn load(&self) -> Vec<MenuItem<T>> {
+        self.load_streaming(None)
+    }
+
+    /// Reads the desktop files, the entries are sent to `sink` in batches while they are read.
+    /// The returned entries are deduplicated, sorted and pinned, which needs all of them.
+    fn load_streaming(&self, mut sink: Option<&Sender<Vec<MenuItem<T>>>>) -> Vec<MenuItem<T>> {
         let locale_variants = get_locale_variants();
         let default_icon = "application-x-executable".to_string();
         let start = Instant::now();
 
-        let entries: Vec<MenuItem<T>> = find_desktop_files()
-            .into_par_iter()
-            .filter(|file| {
-                !file.entry.no_display.unwrap_or(false) && !file.entry.hidden.unwrap_or(false)
-            })
-            .filter_map(|file| {
-                let name = lookup_name_with_locale(
-                    &locale_variants,
-                    &file.entry.name.variants,
-                    &file.entry.name.default,
-                )?;
-
-                let (action, working_dir, in_terminal) = match &file.entry.entry_type {
-                    EntryType::Application(app) => (
-                        app.exec.clone(),
-                        app.path.clone(),
-                        app.terminal.unwrap_or(false),
-                    ),
-                    _ => return None,
-                };
-
-                let cmd_exists = action
-                    .as_ref()
-                    .and_then(|a| {
-                        a.split(' ')
-                            .next()
-                            .map(|cmd| cmd.replace('"', ""))
-                            .map(|cmd| PathBuf::from(&cmd).exists() || which::which(&cmd).is_ok())
-                    })
-                    .unwrap_or(false);
-
-                if !cmd_exists {
-                    log::warn!(
-                        "Skipping desktop entry for {name:?} because action {action:?} does not \
-                         exist"
-                    );
-                    return None;
-                }
-
-                let icon = file
-                    .entry
-                    .icon
-                    .as_ref()
-                    .map(|s| s.content.clone())
-                    .or(Some(default_icon.clone()));
-
-                #[allow(clippy::cast_precision_loss)] // we won't deal with enough values anyways
-                let sort_score = *self.cache.get(&name).unwrap_or(&0) as f64;
-
-                let mut entry = MenuItem::new(
-                    name.clone(),
-                    icon.clone(),
-                    self.get_action(in_terminal, action, &name),
-                    Vec::new(),
-                    working_dir.clone(),
-                    sort_score,
-                    Some(self.data.clone()),
-                );
-
-                if !self.no_actions {
-                    for action in file.actions.values() {
-                        if let Some(action_name) = lookup_name_with_locale(
-                            &locale_variants,
-                            &action.name.variants,
-                            &action.name.default,
-                        ) {
-                            let action_icon = action
-                                .icon
-                                .as_ref()
-                                .map(|s| s.content.clone())
-                                .or(icon.clone())
-                                .unwrap_or("application-x-executable".to_string());
-
-                            let action =
-                                self.get_action(in_terminal, action.exec.clone(), &action_name);
-
-                            entry.sub_elements.push(MenuItem::new(
-                                action_name,
-                                Some(action_icon),
-                                action,
-                                Vec::new(),
-                                working_dir.clone(),
-                                0.0,
-                                Some(self.data.clone()),
-                            ));
-                        }
-                    }
+        let files = find_desktop_files_with_path();
+        let batch_size = if sink.is_some() {
+            STREAM_BATCH_SIZE
+        } else {
+            files.len().max(1)
+        };
+        let mut entries: Vec<MenuItem<T>> = Vec::with_capacity(files.len());
+        let mut streamed = HashSet::new();
+        for batch in files.chunks(batch_size) {
+            let batch: Vec<MenuItem<T>> = batch
+                .par_iter()
+                .filter_map(|(path, file)| {
+                    self.menu_item(path, file, &locale_variants, &default_icon)
+                })
+                .collect();
+            if let Some(stream) = sink {
+                // hidden entries and duplicates are sorted out once all entries are known
+                let shown: Vec<_> = batch
+                    .iter()
+                    .filter(|entry| entry.badge_class.as_deref() != Some(HIDDEN_CLASS))
+                    .filter(|entry| streamed.insert(entry.key().to_owned()))
+                    .cloned()
+                    .collect();
+                if !shown.is_empty() && stream.send(shown).is_err() {
+                    sink = None;
                 }
-                Some(entry)
-            })
-            .collect();
+            }
+            entries.extend(batch);
+        }
 
+        // hidden entries must not replace a listed one with the same action
+        entries.sort_by_key(|entry| entry.badge_class.as_deref() == Some(HIDDEN_CLASS));
+        let mut seen_ids = HashSet::new();
         let mut seen_actions = HashSet::new();
         let mut entries: Vec<MenuItem<T>> = entries
             .into_iter()
-            .filter(|entry| seen_actions.insert(entry.action.clone()))
+            .filter(|entry| {
+                seen_ids.insert(entry.key().to_owned()) && seen_actions.insert(entry.action.clone())
+            })
             .collect();
 
         log::info!(
             "parsing desktop files took {}ms",
             start.elapsed().as_millis()
         );
+        if let Err(e) = self.icons.save() {
+            log::warn!("cannot save icon cache {e}");
+        }
 
         gui::apply_sort(&mut entries, &self.sort_order);
+        self.apply_pins(&mut entries);
         entries
     }
 
+    fn menu_item(
+        &self,
+        path: &Path,
+        file: &DesktopFile,
+        locale_variants: &[String],
+        default_icon: &str,
+    ) -> Option<MenuItem<T>> {
+        let blocked = path
+            .file_name()
+            .is_some_and(|id| self.blocklist.contains(&*id.to_string_lossy()));
+        let mut hidden = if blocked {
+            Some(BLOCKED_REASON)
+        } else {
+            hidden_reason(file, self.desktops.as_deref())
+        };
+        if hidden.is_some() && !self.include_hidden {
+            return None;
+        }
+
+        let name = lookup_name_with_locale(
+            locale_variants,
+            &file.entry.name.variants,
+            &file.entry.name.default,
+        )?;
+
+        let (action, working_dir, in_terminal) = match &file.entry.entry_type {
+            EntryType::Application(app) => (
+                app.exec.clone(),
+                app.path.clone(),
+                app.terminal.unwrap_or(false),
+            ),
+            _ => return None,
+        };
+
+        let binary = action.as_ref().and_then(|a| {
+            a.split(' ')
+                .next()
+                .map(|cmd| cmd.replace('"', ""))
+                .and_then(|cmd| find_binary(&cmd))
+        });
+
+        if binary.is_none() {
+            if !self.include_hidden {
+                log::warn!(
+                    "Skipping desktop entry for {name:?} because action {action:?} does \
+                     not exist"
+                );
+                return None;
+            }
+            hidden = hidden.or(Some("Exec not found"));
+        }
+
+        // reading the binary is only worth it if the origin is used
+        let origin = if self.show_origin || !self.hidden_origins.is_empty() {
+            Origin::detect(path, binary.as_deref())
+        } else {
+            Origin::Native
+        };
+        if self.hidden_origins.contains(&origin) {
+            if !self.include_hidden {
+                log::debug!("Hiding {name} from origin {origin:?}");
+                return None;
+            }
+            hidden = hidden.or(origin.badge());
+        }
+
+        let icon = file
+            .entry
+            .icon
+            .as_ref()
+            .map(|s| s.content.clone())
+            .or(Some(default_icon.to_owned()))
+            .map(|name| self.icons.resolve(&name).unwrap_or(name));
+
+        // caches written before items had ids use the name
+        let id = path.file_name()?.to_string_lossy().into_owned();
+        let usage_key = if self.history.contains(&id) {
+            &id
+        } else {
+            &name
+        };
+        let sort_score = self.history.sort_score(usage_key, &self.sort_order);
+
+        let mut entry = MenuItem::new(
+            name.clone(),
+            icon.clone(),
+            self.get_action(in_terminal, action, &name),
+            Vec::new(),
+            working_dir.clone(),
+            sort_score,
+            Some(self.data.clone()),
+        )
+        .with_id(id);
+
+        let preview = [&file.entry.generic_name, &file.entry.comment]
+            .into_iter()
+            .flatten()
+            .filter_map(|text| {
+                lookup_name_with_locale(locale_variants, &text.variants, &text.default)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !preview.is_empty() {
+            entry = entry.with_preview(preview);
+        }
+
+        if let Some(reason) = hidden {
+            entry = entry
+                .with_badge(reason.to_owned())
+                .with_badge_class(HIDDEN_CLASS.to_owned());
+        } else if self.show_origin
+            && let Some(badge) = origin.badge()
+        {
+            entry = entry.with_badge(badge.to_owned());
+        }
+
+        if !self.no_actions {
+            for action in file.actions.values() {
+                if let Some(action_name) = lookup_name_with_locale(
+                    locale_variants,
+                    &action.name.variants,
+                    &action.name.default,
+                ) {
+                    let action_icon = action
+                        .icon
+                        .as_ref()
+                        .map(|s| s.content.clone())
+                        .map(|name| self.icons.resolve(&name).unwrap_or(name))
+                        .or(icon.clone())
+                        .unwrap_or("application-x-executable".to_string());
+
+                    let action = self.get_action(in_terminal, action.exec.clone(), &action_name);
+
+                    entry.sub_elements.push(MenuItem::new(
+                        action_name,
+                        Some(action_icon),
+                        action,
+                        Vec::new(),
+                        working_dir.clone(),
+                        0.0,
+                        Some(self.data.clone()),
+                    ));
+                }
+            }
+        }
+        Some(entry)
+    }
+
+    /// Resolver of the icons, to be shared with other providers shown at the same time.
+    pub(crate) fn icon_resolver(&self) -> Arc<IconResolver> {
+        Arc::clone(&self.icons)
+    }
+
+    /// Moves the pinned entries to the top, in the order they were pinned, and marks them.
+    fn apply_pins(&self, entries: &mut [MenuItem<T>]) {
+        let top_score = entries
+            .iter()
+            .map(|entry| entry.initial_sort_score)
+            .fold(0.0, f64::max);
+        let mut pinned: Vec<_> = self.pins.iter().collect();
+        pinned.sort_by_key(|(_, order)| **order);
+
+        for entry in entries.iter_mut() {
+            if let Some(rank) = pinned.iter().position(|(id, _)| *id == entry.key()) {
+                // there are never enough pins for this to matter
+                #[allow(clippy::cast_precision_loss)]
+                let score = top_score + (pinned.len() - rank) as f64;
+                entry.initial_sort_score = score;
+                entry.css_class = Some(PINNED_CLASS.to_owned());
+            }
+        }
+        // the order of the other entries is kept
+        entries.sort_by(
+            |l, r| match (l.css_class.is_some(), r.css_class.is_some()) {
+                (true, true) => r.initial_sort_score.total_cmp(&l.initial_sort_score),
+                (l_pinned, r_pinned) => r_pinned.cmp(&l_pinned),
+            },
+        );
+    }
+
+    /// Pins the entry with the given id below the other pins, or unpins it.
+    fn toggle_pin(&mut self, id: &str) {
+        if self.pins.remove(id).is_none() {
+            let next = self.pins.values().max().map_or(0, |last| last + 1);
+            self.pins.insert(id.to_owned(), next);
+        }
+        if let Err(e) = save_cache_file(&self.pins_path, &self.pins) {
+            log::warn!("cannot save drun pins {e:?}");
+        }
+    }
+
     fn get_action(
         &self,
         in_terminal: bool,
@@ -195,23 +595,115 @@ impl<T: Clone + Send + Sync> DRunProvider<T> {
     }
 }
 
-pub(crate) fn update_drun_cache_and_run<T: Clone>(
-    cache_path: &PathBuf,
-    cache: &mut HashMap<String, i64>,
+pub(crate) fn update_history_and_run<T: Clone>(
     selection_result: MenuItem<T>,
+    config: &Config,
 ) -> Result<(), crate::Error> {
-    *cache.entry(selection_result.label).or_insert(0) += 1;
-    if let Err(e) = save_cache_file(cache_path, cache) {
-        log::warn!("cannot save drun cache {e:?}");
+    // a dry run only prints the command, it does not count as use
+    if !config.dry_run() {
+        let key = selection_result.key();
+        let mut history = History::load("drun", config);
+        // usage stored by name before items had ids is carried over
+        history.rename(&selection_result.label, key);
+        history.record(key);
+        if let Err(e) = history.save() {
+            log::warn!("cannot save drun history: {e}");
+        }
     }
 
+    // actions of an entry cannot be started from the desktop file, they have no id
+    let desktop_file = if config.launch_method() == LaunchMethod::Gio
+        && let Some(id) = selection_result.id.as_deref()
+    {
+        find_desktop_files_with_path()
+            .into_iter()
+            .find(|(path, _)| path.file_name().is_some_and(|name| name == id))
+            .map(|(path, _)| path)
+    } else {
+        None
+    };
+
     if let Some(action) = selection_result.action {
-        spawn_fork(&action, selection_result.working_dir.as_ref())
+        launch_app(
+            &action,
+            selection_result.working_dir.as_ref(),
+            &selection_result.env,
+            desktop_file.as_deref(),
+            config,
+        )
     } else {
         Err(Error::MissingAction)
     }
 }
 
+/// Content of the desktop file without the keys hiding it.
+fn unhidden_content(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let key = line.split('=').next().unwrap_or_default().trim();
+            key != "NoDisplay" && key != "Hidden"
+        })
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Writes a user override of the desktop file with the given id, which is listed again.
+/// An override of the user that only hid the entry is removed instead.
+fn unhide(id: &str) -> Result<(), Error> {
+    let (path, _) = find_desktop_files_with_path()
+        .into_iter()
+        .find(|(path, _)| path.file_name().is_some_and(|name| name == id))
+        .ok_or(Error::MissingFile)?;
+    let content = fs::read_to_string(&path).map_err(|e| Error::Io(e.to_string()))?;
+    let unhidden = unhidden_content(&content);
+
+    let user_dir = dirs::data_dir()
+        .ok_or(Error::MissingFile)?
+        .join("applications");
+    let override_path = user_dir.join(id);
+    if path == override_path && !unhidden.lines().any(|line| line.starts_with("Exec")) {
+        log::info!("removing override {}", path.display());
+        return fs::remove_file(&path).map_err(|e| Error::Io(e.to_string()));
+    }
+
+    log::info!("writing override {}", override_path.display());
+    fs::create_dir_all(&user_dir).map_err(|e| Error::Io(e.to_string()))?;
+    fs::write(&override_path, unhidden).map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Alt plus `key_pin` or `key_hide_entry` pin or hide the selected entry,
+/// Alt+U unhides it if hidden entries are included.
+#[must_use]
+pub fn custom_keys(config: &Config) -> CustomKeys {
+    let mut bindings = vec![
+        KeyBinding {
+            key: config.key_pin(),
+            modifiers: HashSet::from([Modifier::Alt]),
+            label: PIN_LABEL.to_owned(),
+            visible: false,
+        },
+        KeyBinding {
+            key: config.key_hide_entry(),
+            modifiers: HashSet::from([Modifier::Alt]),
+            label: HIDE_LABEL.to_owned(),
+            visible: false,
+        },
+    ];
+    if config.drun_include_hidden() {
+        bindings.push(KeyBinding {
+            key: Key::U,
+            modifiers: HashSet::from([Modifier::Alt]),
+            label: UNHIDE_LABEL.to_owned(),
+            visible: true,
+        });
+    }
+    CustomKeys {
+        bindings,
+        hint: None,
+    }
+}
+
 /// Shows the drun mode
 /// # Errors
 ///
@@ -219,18 +711,38 @@ pub(crate) fn update_drun_cache_and_run<T: Clone>(
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let provider = Arc::new(Mutex::new(DRunProvider::new((), &config.read().unwrap())));
-    let arc_provider = Arc::clone(&provider) as ArcProvider<()>;
-    let selection_result = gui::show(config, arc_provider, None, None, ExpandMode::Verbatim, None);
-    match selection_result {
-        Ok(s) => {
-            let p = provider.lock().unwrap();
-            update_drun_cache_and_run(&p.cache_path, &mut p.cache.clone(), s.menu)?;
+    let provider: ArcProvider<()> =
+        Arc::new(Mutex::new(DRunProvider::new((), &config.read().unwrap())));
+    let custom_keys = Some(custom_keys(&config.read().unwrap()));
+    let selection = gui::show(
+        &config.read().unwrap(),
+        provider,
+        None,
+        None,
+        ExpandMode::Verbatim,
+        custom_keys,
+    )?;
+
+    if selection
+        .custom_key
+        .is_some_and(|key| key.label == UNHIDE_LABEL)
+    {
+        let hidden_by = selection
+            .menu
+            .badge
+            .as_deref()
+            .filter(|_| selection.menu.badge_class.as_deref() == Some(HIDDEN_CLASS));
+        if hidden_by == Some(BLOCKED_REASON) {
+            return set_blocked(selection.menu.key(), false);
         }
-        Err(_) => {
-            log::error!("No item selected");
+        // entries without binary or of a hidden origin are not hidden by their desktop file
+        let unhideable = matches!(hidden_by, Some("Hidden" | "NoDisplay"));
+        if !unhideable {
+            log::warn!("{} is not hidden by its desktop file", selection.menu.label);
+            return Ok(());
         }
+        return unhide(selection.menu.key());
     }
 
-    Ok(())
+    update_history_and_run(selection.menu, &config.read().unwrap())
 }
@@ -2,9 +2,10 @@ use std::sync::{Arc, Mutex, RwLock};
 
 use crate::{
     Error,
-    config::{Config, SortOrder, TextOutputMode},
-    desktop::copy_to_clipboard,
+    config::{Config, SortOrder},
+    desktop::output_text,
     gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    history::{self, History},
 };
 
 #[derive(Clone)]
@@ -13,7 +14,10 @@ pub(crate) struct EmojiProvider {
 }
 
 impl EmojiProvider {
-    pub(crate) fn new(sort_order: &SortOrder, hide_label: bool) -> Self {
+    pub(crate) fn new(config: &Config) -> Self {
+        let sort_order = config.sort_order();
+        let hide_label = config.emoji_hide_label();
+        let history = (sort_order == SortOrder::Frecency).then(|| History::load("emoji", config));
         let emoji = emoji::search::search_annotation_all("");
         let mut menus = emoji
             .into_iter()
@@ -31,30 +35,51 @@ impl EmojiProvider {
                     )),
                     vec![],
                     None,
-                    0.0,
+                    history.as_ref().map_or(0.0, |h| h.score(e.glyph)),
                     Some(e.glyph.to_string()),
                 )
             })
             .collect::<Vec<_>>();
-        gui::apply_sort(&mut menus, sort_order);
+        gui::apply_sort(&mut menus, &sort_order);
 
         Self { elements: menus }
     }
 }
 
+/// Rows of the most frecently used emoji, highest score first.
+/// Their action is the glyph.
+pub(crate) fn recent_items<T: Clone>(data: &T, config: &Config, limit: usize) -> Vec<MenuItem<T>> {
+    let all = emoji::search::search_annotation_all("");
+    History::load("emoji", config)
+        .ranked()
+        .into_iter()
+        .filter_map(|(glyph, score)| {
+            let e = all.iter().find(|e| e.glyph == glyph)?;
+            Some(MenuItem::new(
+                format!("{} — Category: {} — Name: {}", e.glyph, e.group, e.name),
+                None,
+                Some(e.glyph.to_owned()),
+                vec![],
+                None,
+                score,
+                Some(data.clone()),
+            ))
+        })
+        .take(limit)
+        .collect()
+}
+
 impl ItemProvider<String> for EmojiProvider {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<String> {
         if query.is_some() {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
-            ProviderData {
-                items: Some(self.elements.clone()),
-            }
+            ProviderData::Items(self.elements.clone())
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<String>) -> ProviderData<String> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
     }
 }
 
@@ -65,26 +90,23 @@ impl ItemProvider<String> for EmojiProvider {
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let cfg = config.read().unwrap();
-    let provider = Arc::new(Mutex::new(EmojiProvider::new(
-        &cfg.sort_order(),
-        cfg.emoji_hide_label(),
-    )));
-    drop(cfg);
+    let provider = Arc::new(Mutex::new(EmojiProvider::new(&config.read().unwrap())));
 
-    let selection_result = gui::show(config, provider, None, None, ExpandMode::Verbatim, None)?;
+    let selection_result = gui::show(
+        &config.read().unwrap(),
+        provider,
+        None,
+        None,
+        ExpandMode::Verbatim,
+        None,
+    )?;
     match selection_result.menu.data {
         None => Err(Error::MissingAction),
-        Some(action) => match config.read().unwrap().text_output_mode() {
-            TextOutputMode::Clipboard => {
-                copy_to_clipboard(action, None)?;
-                Ok(())
-            }
-            TextOutputMode::StandardOutput => {
-                println!("{action}");
-                Ok(())
-            }
-            TextOutputMode::None => Ok(()),
-        },
+        Some(action) => {
+            history::record_usage("emoji", &action, &config.read().unwrap());
+            let config = config.read().unwrap();
+            let output_mode = config.action_or(config.text_output_mode());
+            output_text(&action, &output_mode, &config)
+        }
     }
 }
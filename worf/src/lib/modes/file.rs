@@ -1,33 +1,271 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs,
     os::unix::fs::FileTypeExt,
     path::{Path, PathBuf},
-    sync::{Arc, LazyLock, Mutex, RwLock},
+    sync::{
+        Arc, LazyLock, Mutex, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, SystemTime},
 };
 
 use regex::Regex;
 
 use crate::{
     Error,
-    config::{Config, SortOrder, expand_path},
-    desktop::spawn_fork,
-    gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    config::{Config, Key, SortOrder, expand_path},
+    desktop::{IconResolver, launch, percent_decode},
+    gui::{self, ExpandMode, ItemProvider, MenuItem, Modifier, ProviderData, SearchEdit},
 };
 
+/// Search showing only the places, i.e. bookmarks, user directories and volumes.
+const PLACES_QUERY: &str = "@bookmarks";
+
+/// Score of places, shown below the current directory and above its entries.
+const PLACE_SORT_SCORE: f64 = 50.0;
+
+/// Interval in which computed details are shown.
+const DETAILS_REFRESH: Duration = Duration::from_millis(250);
+
+/// Levels of subdirectories included in the size of a directory.
+const DETAILS_MAX_DEPTH: usize = 3;
+
+/// Details of the listed entries by label.
+type Details = Arc<Mutex<HashMap<String, String>>>;
+
+/// Thumbnail files of the listed entries by label.
+type Thumbnails = Arc<Mutex<HashMap<String, String>>>;
+
 #[derive(Clone)]
 pub(crate) struct FileItemProvider<T: Clone> {
     last_result: Option<Vec<MenuItem<T>>>,
+    /// Search the last result was created for
+    last_search: Option<String>,
+    /// Details of the entries of `details_dir`, `None` if they are disabled.
+    details: Option<Details>,
+    details_dir: Option<PathBuf>,
+    /// Number of details already applied to the last result.
+    applied_details: usize,
+    /// Thumbnails of the entries of `thumbnails_dir`, `None` if they are disabled.
+    thumbnails: Option<Thumbnails>,
+    thumbnails_dir: Option<PathBuf>,
+    /// Number of thumbnails already applied to the last result.
+    applied_thumbnails: usize,
+    /// Threads computing details or thumbnails.
+    workers: Arc<AtomicUsize>,
+    /// Label of the item representing the searched directory itself
+    current_dir: Option<String>,
     menu_item_data: T,
     sort_order: SortOrder,
+    /// Pin places on top of the initial view
+    show_places: bool,
+    /// Searches left with the back mouse button, restored by the forward button
+    forward: Vec<String>,
+    /// Resolves the icon names to files if set, see `with_icon_resolver`
+    icons: Option<Arc<IconResolver>>,
+}
+
+/// A shortcut to a directory, shown on top of the file mode
+#[derive(Debug, PartialEq)]
+struct Place {
+    path: PathBuf,
+    name: Option<String>,
+    icon: &'static str,
 }
 
 impl<T: Clone> FileItemProvider<T> {
-    pub(crate) fn new(menu_item_data: T, sort_order: SortOrder) -> Self {
+    pub(crate) fn new(menu_item_data: T, sort_order: SortOrder, show_places: bool) -> Self {
         FileItemProvider {
             last_result: None,
+            last_search: None,
+            details: None,
+            details_dir: None,
+            applied_details: 0,
+            thumbnails: None,
+            thumbnails_dir: None,
+            applied_thumbnails: 0,
+            workers: Arc::new(AtomicUsize::new(0)),
+            current_dir: None,
             menu_item_data,
             sort_order,
+            show_places,
+            forward: Vec::new(),
+            icons: None,
+        }
+    }
+
+    /// Resolves the icons of the entries via the given resolver, which keeps them across runs.
+    pub(crate) fn with_icon_resolver(mut self, icons: Arc<IconResolver>) -> Self {
+        self.icons = Some(icons);
+        self
+    }
+
+    /// Icon of the file, resolved to a file of the icon theme if possible.
+    fn icon_for(&self, path: &Path) -> String {
+        let name = FileItemProvider::<T>::resolve_icon_for_name(path);
+        self.icons
+            .as_ref()
+            .and_then(|icons| icons.resolve(&name))
+            .unwrap_or(name)
+    }
+
+    /// Shows the size and modification time of files and the number of entries
+    /// and size of directories as description. They are computed in the background.
+    pub(crate) fn with_details(mut self) -> Self {
+        self.details = Some(Details::default());
+        self
+    }
+
+    /// Starts computing the details of the entries if `dir` is not the one they are known for.
+    fn request_details(&mut self, dir: &Path, entries: Vec<(String, PathBuf)>) {
+        if self.details.is_none() || self.details_dir.as_deref() == Some(dir) {
+            return;
+        }
+
+        let details = Details::default();
+        self.details = Some(Arc::clone(&details));
+        self.details_dir = Some(dir.to_path_buf());
+        self.applied_details = 0;
+        let workers = Arc::clone(&self.workers);
+        workers.fetch_add(1, Ordering::AcqRel);
+        thread::spawn(move || {
+            let now = SystemTime::now();
+            for (label, path) in entries {
+                // another directory is shown, nobody waits for these anymore
+                if Arc::strong_count(&details) == 1 {
+                    break;
+                }
+                let detail = file_details(&path, now);
+                details.lock().unwrap().insert(label, detail);
+            }
+            workers.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
+
+    /// Shows thumbnails of images and documents instead of their icon.
+    /// They are looked up or created in the background.
+    pub(crate) fn with_thumbnails(mut self) -> Self {
+        self.thumbnails = Some(Thumbnails::default());
+        self
+    }
+
+    /// Starts looking up the thumbnails of the entries if `dir` is not the one they are known for.
+    fn request_thumbnails(&mut self, dir: &Path, entries: Vec<(String, PathBuf)>) {
+        if self.thumbnails.is_none() || self.thumbnails_dir.as_deref() == Some(dir) {
+            return;
         }
+
+        let thumbnails = Thumbnails::default();
+        self.thumbnails = Some(Arc::clone(&thumbnails));
+        self.thumbnails_dir = Some(dir.to_path_buf());
+        self.applied_thumbnails = 0;
+        let workers = Arc::clone(&self.workers);
+        workers.fetch_add(1, Ordering::AcqRel);
+        thread::spawn(move || {
+            for (label, path) in entries {
+                // another directory is shown, nobody waits for these anymore
+                if Arc::strong_count(&thumbnails) == 1 {
+                    break;
+                }
+                if path.is_dir() {
+                    continue;
+                }
+                let Some(mime) = tree_magic_mini::from_filepath(&path) else {
+                    continue;
+                };
+                if let Some(thumbnail) = super::thumbnail::thumbnail(&path, mime) {
+                    thumbnails
+                        .lock()
+                        .unwrap()
+                        .insert(label, thumbnail.display().to_string());
+                }
+            }
+            workers.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
+
+    /// The last result with the details and thumbnails computed since it was created,
+    /// if it was created for the same search.
+    /// Re-reading the directory on every refresh would cost more than them.
+    fn cached_result(&mut self, search: Option<&str>) -> Option<Vec<MenuItem<T>>> {
+        if (self.details.is_none() && self.thumbnails.is_none())
+            || self.last_search.as_deref() != search
+        {
+            return None;
+        }
+
+        let last_result = self.last_result.as_mut()?;
+        if let Some(details) = &self.details {
+            let details = details.lock().unwrap();
+            if details.len() != self.applied_details {
+                self.applied_details = details.len();
+                for item in last_result.iter_mut() {
+                    if let Some(detail) = details.get(&item.label) {
+                        item.description = Some(detail.clone());
+                    }
+                }
+            }
+        }
+        if let Some(thumbnails) = &self.thumbnails {
+            let thumbnails = thumbnails.lock().unwrap();
+            if thumbnails.len() != self.applied_thumbnails {
+                self.applied_thumbnails = thumbnails.len();
+                for item in last_result.iter_mut() {
+                    if let Some(thumbnail) = thumbnails.get(&item.label) {
+                        item.icon_path = Some(thumbnail.clone());
+                    }
+                }
+            }
+        }
+        Some(last_result.clone())
+    }
+
+    fn place_items(&self) -> Vec<MenuItem<T>> {
+        let home = dirs::home_dir();
+        let mut places: Vec<Place> = [
+            (dirs::desktop_dir(), "user-desktop"),
+            (dirs::document_dir(), "folder-documents"),
+            (dirs::download_dir(), "folder-download"),
+            (dirs::picture_dir(), "folder-pictures"),
+            (dirs::audio_dir(), "folder-music"),
+            (dirs::video_dir(), "folder-videos"),
+        ]
+        .into_iter()
+        .filter_map(|(path, icon)| {
+            path.map(|path| Place {
+                path,
+                name: None,
+                icon,
+            })
+        })
+        .collect();
+        places.extend(gtk_bookmarks());
+        places.extend(mounted_volumes());
+
+        let mut seen = HashSet::new();
+        places
+            .into_iter()
+            .filter(|place| home.as_ref() != Some(&place.path) && place.path.is_dir())
+            .filter(|place| seen.insert(place.path.clone()))
+            .map(|place| {
+                let label = format!("{}/", place.path.display());
+                let mut item = MenuItem::new(
+                    label.clone(),
+                    Some(place.icon.to_owned()),
+                    Some(format!("xdg-open {label}")),
+                    vec![],
+                    None,
+                    PLACE_SORT_SCORE,
+                    Some(self.menu_item_data.clone()),
+                );
+                if let Some(name) = place.name {
+                    item = item.with_search_terms(vec![name.clone()]).with_badge(name);
+                }
+                item
+            })
+            .collect()
     }
 
     fn resolve_icon_for_name(path: &Path) -> String {
@@ -105,6 +343,17 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
             "/".to_string()
         };
 
+        if search == Some(PLACES_QUERY) {
+            self.current_dir = None;
+            let items = self.place_items();
+            self.last_result = Some(items.clone());
+            return ProviderData::Items(items);
+        }
+
+        if let Some(items) = self.cached_result(search) {
+            return ProviderData::Items(items);
+        }
+
         let mut trimmed_search = search.unwrap_or(&default_path).to_owned();
         if !trimmed_search.starts_with('/')
             && !trimmed_search.starts_with('~')
@@ -115,15 +364,18 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
 
         let path = expand_path(&trimmed_search);
         let mut items: Vec<MenuItem<T>> = Vec::new();
+        let mut detail_labels = HashSet::new();
 
         if !path.exists() {
-            return ProviderData { items: None };
+            return ProviderData::Unchanged;
         }
 
+        self.current_dir = None;
         if path.is_dir() {
+            self.current_dir = Some(trimmed_search.clone());
             items.push(MenuItem::new(
                 trimmed_search.clone(),
-                Some(FileItemProvider::<T>::resolve_icon_for_name(&path)),
+                Some(self.icon_for(&path)),
                 Some(format!("xdg-open {}", path.display())),
                 vec![],
                 None,
@@ -131,6 +383,7 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
                 Some(self.menu_item_data.clone()),
             ));
 
+            let mut detail_entries = Vec::new();
             if let Ok(entries) = path.read_dir() {
                 for entry in entries.flatten() {
                     if let Some(mut path_str) =
@@ -149,23 +402,25 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
 
                         items.push(MenuItem::new(
                             path_str.clone(),
-                            Some(FileItemProvider::<T>::resolve_icon_for_name(&entry.path())),
+                            Some(self.icon_for(&entry.path())),
                             Some(format!("xdg-open {path_str}")),
                             vec![],
                             None,
                             0.0,
                             Some(self.menu_item_data.clone()),
                         ));
+                        detail_labels.insert(path_str.clone());
+                        detail_entries.push((path_str, entry.path()));
                     }
                 }
             }
+            self.request_thumbnails(&path, detail_entries.clone());
+            self.request_details(&path, detail_entries);
         } else {
             items.push({
                 MenuItem::new(
                     trimmed_search.clone(),
-                    Some(FileItemProvider::<T>::resolve_icon_for_name(
-                        &PathBuf::from(&trimmed_search),
-                    )),
+                    Some(self.icon_for(&PathBuf::from(&trimmed_search))),
                     Some(format!("xdg-open {trimmed_search}")),
                     vec![],
                     None,
@@ -175,19 +430,256 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
             });
         }
 
+        if search.is_none() && self.show_places {
+            items.extend(self.place_items());
+        }
+
         gui::apply_sort(&mut items, &self.sort_order);
 
+        if let Some(details) = &self.details {
+            let details = details.lock().unwrap();
+            self.applied_details = details.len();
+            for item in items
+                .iter_mut()
+                .filter(|item| detail_labels.contains(&item.label))
+            {
+                let detail = details.get(&item.label).map_or("…", String::as_str);
+                item.description = Some(detail.to_owned());
+            }
+        }
+
+        if let Some(thumbnails) = &self.thumbnails {
+            let thumbnails = thumbnails.lock().unwrap();
+            self.applied_thumbnails = thumbnails.len();
+            for item in &mut items {
+                if let Some(thumbnail) = thumbnails.get(&item.label) {
+                    item.icon_path = Some(thumbnail.clone());
+                }
+            }
+        }
+
+        if let Some(icons) = &self.icons
+            && let Err(e) = icons.save()
+        {
+            log::warn!("cannot save icon cache {e}");
+        }
+
+        self.last_search = search.map(ToOwned::to_owned);
         self.last_result = Some(items.clone());
-        ProviderData { items: Some(items) }
+        ProviderData::Items(items)
     }
 
     fn get_sub_elements(&mut self, item: &MenuItem<T>) -> ProviderData<T> {
         if self.last_result.as_ref().is_some_and(|lr| lr.len() == 1) {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
             self.get_elements(Some(&item.label))
         }
     }
+
+    fn allow_auto_select(&self, item: &MenuItem<T>) -> bool {
+        // the searched directory is always shown, it is not a real match.
+        self.current_dir.as_ref() != Some(&item.label)
+    }
+
+    /// Path, mime type and details of the file, only read for the selected entry.
+    fn preview(&self, item: &MenuItem<T>) -> Option<String> {
+        let path = expand_path(&item.label);
+        if !path.exists() {
+            return None;
+        }
+        let mime = tree_magic_mini::from_filepath(&path).unwrap_or("unknown");
+        Some(format!(
+            "{}\n{mime}\n{}",
+            path.display(),
+            file_details(&path, SystemTime::now())
+        ))
+    }
+
+    /// Refreshes while details or thumbnails are computed or not shown yet,
+    /// and before the first directory is listed.
+    fn refresh_interval(&self) -> Option<Duration> {
+        let unapplied = |computed: Option<&Details>, applied: usize| {
+            computed.is_some_and(|computed| computed.lock().unwrap().len() != applied)
+        };
+        let listed = self.details_dir.is_some() || self.thumbnails_dir.is_some();
+        let pending = !listed
+            || self.workers.load(Ordering::Acquire) > 0
+            || unapplied(self.details.as_ref(), self.applied_details)
+            || unapplied(self.thumbnails.as_ref(), self.applied_thumbnails);
+        ((self.details.is_some() || self.thumbnails.is_some()) && pending)
+            .then_some(DETAILS_REFRESH)
+    }
+
+    fn handle_search_key(
+        &mut self,
+        key: Key,
+        _modifiers: &HashSet<Modifier>,
+        search: &str,
+        _cursor: usize,
+    ) -> Option<SearchEdit> {
+        let text = match key {
+            Key::MouseBack => {
+                let home = dirs::home_dir().map(|home| home.display().to_string());
+                let current = if search.is_empty() {
+                    home.as_deref()?
+                } else {
+                    search
+                };
+                let parent = parent_search(current)?;
+                self.forward.push(search.to_owned());
+                parent
+            }
+            Key::MouseForward => self.forward.pop()?,
+            _ => return None,
+        };
+        Some(SearchEdit {
+            cursor: text.chars().count(),
+            text,
+        })
+    }
+}
+
+/// Search listing the parent directory of the searched path, `None` for the root.
+fn parent_search(search: &str) -> Option<String> {
+    let (parent, _) = search.trim_end_matches('/').rsplit_once('/')?;
+    Some(format!("{parent}/"))
+}
+
+/// Size and age of files, directories also show their number of entries.
+/// The size of directories only includes `DETAILS_MAX_DEPTH` levels of subdirectories,
+/// so this stays cheap for large trees. Sizes missing deeper files end with a `+`.
+fn file_details(path: &Path, now: SystemTime) -> String {
+    let Ok(meta) = fs::metadata(path) else {
+        return "?".to_owned();
+    };
+    let age = meta
+        .modified()
+        .ok()
+        .and_then(|modified| now.duration_since(modified).ok())
+        .map(|age| format!(" · {}", format_age(age.as_secs())))
+        .unwrap_or_default();
+    if !meta.is_dir() {
+        return format!("{}{age}", format_size(meta.len()));
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        return "?".to_owned();
+    };
+    let count = match entries.count() {
+        1 => "1 item".to_owned(),
+        count => format!("{count} items"),
+    };
+    let (size, complete) = dir_size(path, DETAILS_MAX_DEPTH);
+    let incomplete = if complete { "" } else { "+" };
+    format!("{count} · {}{incomplete}{age}", format_size(size))
+}
+
+/// Size of the files in the directory and in its subdirectories up to `depth` levels below,
+/// and if no file was left out. Symlinks are not followed.
+fn dir_size(path: &Path, depth: usize) -> (u64, bool) {
+    let Ok(entries) = fs::read_dir(path) else {
+        return (0, false);
+    };
+    let mut size = 0;
+    let mut complete = true;
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            complete = false;
+            continue;
+        };
+        if !meta.is_dir() {
+            size += meta.len();
+        } else if depth == 0 {
+            complete = false;
+        } else {
+            let (dir_size, dir_complete) = dir_size(&entry.path(), depth - 1);
+            size += dir_size;
+            complete &= dir_complete;
+        }
+    }
+    (size, complete)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    // only one decimal is shown, the precision does not matter
+    #[allow(clippy::cast_precision_loss)]
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_owned(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86_400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+/// Bookmarks of the GTK file chooser, lines are an uri optionally followed by a name.
+fn gtk_bookmarks() -> Vec<Place> {
+    let Some(path) = dirs::config_dir().map(|dir| dir.join("gtk-3.0").join("bookmarks")) else {
+        return Vec::new();
+    };
+    fs::read_to_string(path)
+        .map(|content| parse_gtk_bookmarks(&content))
+        .unwrap_or_default()
+}
+
+fn parse_gtk_bookmarks(content: &str) -> Vec<Place> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (uri, name) = line
+                .split_once(' ')
+                .map_or((line, None), |(uri, name)| (uri, Some(name.trim())));
+            let path = uri.strip_prefix("file://")?;
+            Some(Place {
+                path: PathBuf::from(percent_decode(path)),
+                name: name.filter(|n| !n.is_empty()).map(ToOwned::to_owned),
+                icon: "user-bookmarks",
+            })
+        })
+        .collect()
+}
+
+/// Removable and network volumes mounted by the user.
+fn mounted_volumes() -> Vec<Place> {
+    fs::read_to_string("/proc/mounts")
+        .map(|content| parse_mounts(&content))
+        .unwrap_or_default()
+}
+
+fn parse_mounts(content: &str) -> Vec<Place> {
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        // spaces and other special chars are escaped as octal numbers
+        .map(|mount_point| mount_point.replace("\\040", " ").replace("\\011", "\t"))
+        .filter(|mount_point| {
+            ["/media/", "/run/media/", "/mnt/"]
+                .iter()
+                .any(|prefix| mount_point.starts_with(prefix))
+        })
+        .map(|mount_point| Place {
+            name: Path::new(&mount_point)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            path: PathBuf::from(mount_point),
+            icon: "drive-removable-media",
+        })
+        .collect()
 }
 
 /// Shows the file browser mode
@@ -201,13 +693,22 @@ impl<T: Clone> ItemProvider<T> for FileItemProvider<T> {
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
     static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\$\w+").unwrap());
 
-    let provider = Arc::new(Mutex::new(FileItemProvider::new(
+    let mut provider = FileItemProvider::new(
         0,
         config.read().unwrap().sort_order(),
-    )));
+        config.read().unwrap().file_places(),
+    );
+    if config.read().unwrap().file_details() {
+        provider = provider.with_details();
+    }
+    if config.read().unwrap().file_thumbnails() {
+        provider = provider.with_thumbnails();
+    }
+    provider = provider.with_icon_resolver(Arc::new(IconResolver::load(&config.read().unwrap())));
+    let provider = Arc::new(Mutex::new(provider));
 
     let selection_result = gui::show(
-        config,
+        &config.read().unwrap(),
         provider,
         None,
         Some(vec![RE.clone()]),
@@ -215,8 +716,98 @@ pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
         None,
     )?;
     if let Some(action) = selection_result.menu.action {
-        spawn_fork(&action, selection_result.menu.working_dir.as_ref())
+        launch(
+            &action,
+            selection_result.menu.working_dir.as_ref(),
+            &selection_result.menu.env,
+            &config.read().unwrap(),
+        )
     } else {
         Err(Error::MissingAction)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_search() {
+        assert_eq!(
+            parent_search("/home/user/Doc"),
+            Some("/home/user/".to_owned())
+        );
+        assert_eq!(parent_search("/home/user/"), Some("/home/".to_owned()));
+        assert_eq!(parent_search("~/Downloads/"), Some("~/".to_owned()));
+        assert_eq!(parent_search("/home"), Some("/".to_owned()));
+        assert_eq!(parent_search("/"), None);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+    }
+
+    #[test]
+    fn test_dir_size_stops_at_depth() {
+        let root = std::env::temp_dir().join(format!("worf-dir-size-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("top"), [0; 10]).unwrap();
+        fs::write(root.join("a").join("mid"), [0; 20]).unwrap();
+        fs::write(nested.join("deep"), [0; 40]).unwrap();
+
+        assert_eq!(dir_size(&root, 2), (70, true));
+        assert_eq!(dir_size(&root, 1), (30, false));
+        assert_eq!(dir_size(&root, 0), (10, false));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(30), "just now");
+        assert_eq!(format_age(125), "2m ago");
+        assert_eq!(format_age(7200), "2h ago");
+        assert_eq!(format_age(3 * 86_400), "3d ago");
+    }
+
+    #[test]
+    fn test_parse_gtk_bookmarks() {
+        let places = parse_gtk_bookmarks(
+            "file:///home/user/My%20Projects Projects\nfile:///tmp\nsftp://host/data Remote\n",
+        );
+        assert_eq!(
+            places,
+            vec![
+                Place {
+                    path: PathBuf::from("/home/user/My Projects"),
+                    name: Some("Projects".to_owned()),
+                    icon: "user-bookmarks",
+                },
+                Place {
+                    path: PathBuf::from("/tmp"),
+                    name: None,
+                    icon: "user-bookmarks",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mounts_only_user_volumes() {
+        let places = parse_mounts(
+            "/dev/nvme0n1p2 / ext4 rw 0 0\n\
+             /dev/sda1 /run/media/user/USB\\040Stick vfat rw 0 0\n",
+        );
+        assert_eq!(
+            places,
+            vec![Place {
+                path: PathBuf::from("/run/media/user/USB Stick"),
+                name: Some("USB Stick".to_owned()),
+                icon: "drive-removable-media",
+            }]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    process::Command,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use regex::{Captures, Regex};
+
+use crate::{
+    config::{Config, InfoCommand},
+    gui::{ItemProvider, MenuItem, ProviderData},
+};
+
+/// Interval in which finished commands are shown.
+const RESULT_REFRESH: Duration = Duration::from_millis(250);
+
+/// Time the search has to stay unchanged before a command is run,
+/// so typing does not start a command for every key press.
+const RUN_DELAY: Duration = Duration::from_millis(400);
+
+/// Output of the commands by index and search, `None` while the command runs
+/// and `Err` with the reason it failed.
+type InfoResults = Arc<Mutex<HashMap<(usize, String), Option<Result<String, String>>>>>;
+
+#[derive(Clone)]
+pub(crate) struct InfoProvider<T: Clone> {
+    menu_item_data: T,
+    commands: Vec<(Regex, InfoCommand)>,
+    results: InfoResults,
+    /// When a search was first seen, commands run once it is older than `RUN_DELAY`.
+    waiting: HashMap<(usize, String), Instant>,
+}
+
+impl<T: Clone> InfoProvider<T> {
+    pub(crate) fn new(menu_item_data: T, config: &Config) -> Self {
+        let commands = config
+            .info_commands()
+            .into_iter()
+            .filter_map(|command| match Regex::new(&command.pattern) {
+                Ok(re) => Some((re, command)),
+                Err(e) => {
+                    log::warn!("invalid pattern of info command {}: {e}", command.name);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            menu_item_data,
+            commands,
+            results: InfoResults::default(),
+            waiting: HashMap::new(),
+        }
+    }
+
+    /// True if the search matches the pattern of a command.
+    pub(crate) fn matches(&self, search: &str) -> bool {
+        self.commands.iter().any(|(re, _)| re.is_match(search))
+    }
+
+    fn row(&mut self, index: usize, search: &str) -> Option<MenuItem<T>> {
+        let (re, command) = &self.commands[index];
+        let captures = re.captures(search)?;
+        let key = (index, search.to_owned());
+
+        let result = self.results.lock().unwrap().get(&key).cloned();
+        let (label, action) = match &result {
+            Some(Some(Ok(output))) => (output.clone(), Some(output.clone())),
+            Some(Some(Err(e))) => (format!("{}: {e}", command.name), None),
+            Some(None) | None => (format!("{} …", command.name), None),
+        };
+
+        if result.is_none() {
+            let first_seen = *self.waiting.entry(key.clone()).or_insert_with(Instant::now);
+            if first_seen.elapsed() >= RUN_DELAY {
+                self.waiting.remove(&key);
+                start(
+                    command.command.clone(),
+                    command_args(&captures),
+                    key,
+                    &self.results,
+                );
+            }
+        }
+
+        Some(MenuItem::new(
+            label,
+            command.icon.clone(),
+            action,
+            vec![],
+            None,
+            0.0,
+            Some(self.menu_item_data.clone()),
+        ))
+    }
+}
+
+impl<T: Clone> ItemProvider<T> for InfoProvider<T> {
+    fn get_elements(&mut self, search: Option<&str>) -> ProviderData<T> {
+        let search = search.unwrap_or_default().trim();
+        self.waiting.retain(|(_, waiting), _| waiting == search);
+        let items = (0..self.commands.len())
+            .filter_map(|index| self.row(index, search))
+            .collect();
+        ProviderData::Items(items)
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<T>) -> ProviderData<T> {
+        ProviderData::Unchanged
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        (!self.commands.is_empty()).then_some(RESULT_REFRESH)
+    }
+}
+
+/// Capture groups passed as arguments, the whole match if there are none.
+fn command_args(captures: &Captures) -> Vec<String> {
+    let skip = usize::from(captures.len() > 1);
+    captures
+        .iter()
+        .skip(skip)
+        .map(|group| group.map_or_else(String::new, |m| m.as_str().to_owned()))
+        .collect()
+}
+
+/// Runs the command in the background, the result is stored once it finished.
+fn start(command: String, args: Vec<String>, key: (usize, String), results: &InfoResults) {
+    results.lock().unwrap().insert(key.clone(), None);
+    let results = Arc::clone(results);
+    thread::spawn(move || {
+        let result = run(&command, &args);
+        log::debug!("info command {command} returned {result:?}");
+        results.lock().unwrap().insert(key, Some(result));
+    });
+}
+
+fn run(command: &str, args: &[String]) -> Result<String, String> {
+    // `worf` becomes `$0`, so the arguments start at `$1`
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("worf")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(first_line(&stderr).unwrap_or_else(|| output.status.to_string()));
+    }
+    first_line(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| "no output".to_owned())
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_args_of_groups() {
+        let re = Regex::new(r"^weather(?: in (\w+))?$").unwrap();
+        let captures = re.captures("weather in tokyo").unwrap();
+        assert_eq!(command_args(&captures), vec!["tokyo"]);
+        let captures = re.captures("weather").unwrap();
+        assert_eq!(command_args(&captures), vec![""]);
+
+        let re = Regex::new(r"^ip$").unwrap();
+        let captures = re.captures("ip").unwrap();
+        assert_eq!(command_args(&captures), vec!["ip"]);
+    }
+}
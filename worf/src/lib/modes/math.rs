@@ -1,5 +1,7 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
+    fs,
+    path::Path,
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
@@ -7,13 +9,15 @@ use regex::Regex;
 
 use crate::{
     Error,
-    config::{Config, TextOutputMode},
+    config::{Config, Key, TextOutputMode},
     gui::{
-        self, ArcFactory, ArcProvider, DefaultItemFactory, ExpandMode, ItemProvider, MenuItem,
-        ProviderData,
+        self, ArcFactory, ArcProvider, CustomKeys, DefaultItemFactory, ExpandMode, ItemProvider,
+        KeyBinding, MenuItem, Modifier, ProviderData, SearchEdit,
     },
 };
 
+const EXPORT_LABEL: &str = "Export history";
+
 #[derive(Clone)]
 pub(crate) struct MathProvider<T: Clone> {
     menu_item_data: T,
@@ -48,17 +52,102 @@ impl<T: Clone> ItemProvider<T> for MathProvider<T> {
             );
             let mut result = vec![item];
             result.append(&mut self.elements.clone());
-            ProviderData {
-                items: Some(result),
-            }
+            ProviderData::Items(result)
         } else {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<T>) -> ProviderData<T> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
+    }
+
+    /// Up and down step the number at the cursor, with control held in steps of 10.
+    fn handle_search_key(
+        &mut self,
+        key: Key,
+        modifiers: &HashSet<Modifier>,
+        search: &str,
+        cursor: usize,
+    ) -> Option<SearchEdit> {
+        let step = if modifiers.contains(&Modifier::Control) {
+            10
+        } else {
+            1
+        };
+        match key {
+            Key::Up => step_number(search, cursor, step),
+            Key::Down => step_number(search, cursor, -step),
+            _ => None,
+        }
+    }
+}
+
+/// Adds `delta` to the number at or right before the cursor.
+/// Decimals of the number are kept, i.e. 1.50 + 1 = 2.50.
+/// Returns `None` if there is no decimal number at the cursor.
+fn step_number(search: &str, cursor: usize, delta: i64) -> Option<SearchEdit> {
+    let is_number_char = |c: &char| c.is_ascii_digit() || *c == '.';
+    let chars: Vec<char> = search.chars().collect();
+    let cursor = cursor.min(chars.len());
+
+    let at = if chars.get(cursor).is_some_and(is_number_char) {
+        cursor
+    } else if cursor > 0 && is_number_char(&chars[cursor - 1]) {
+        cursor - 1
+    } else {
+        return None;
+    };
+
+    let mut start = at;
+    while start > 0 && is_number_char(&chars[start - 1]) {
+        start -= 1;
     }
+    let mut end = at;
+    while end < chars.len() && is_number_char(&chars[end]) {
+        end += 1;
+    }
+
+    // part of a word or a literal like 0x1f, which can't be stepped
+    let is_word_char = |c: &char| c.is_alphanumeric() || *c == '_';
+    if (start > 0 && is_word_char(&chars[start - 1])) || chars.get(end).is_some_and(is_word_char) {
+        return None;
+    }
+
+    // a minus is a sign if there is no operand before it
+    let signed = start > 0
+        && chars[start - 1] == '-'
+        && (start == 1 || "(+-*/^% ".contains(chars[start - 2]));
+    if signed {
+        start -= 1;
+    }
+
+    let number: String = chars[start..end].iter().collect();
+    let stepped = if let Some((_, decimals)) = number.split_once('.') {
+        let value = number.parse::<f64>().ok()?;
+        #[allow(clippy::cast_precision_loss)] // steps are small
+        let stepped = value + delta as f64;
+        format!("{stepped:.prec$}", prec = decimals.len())
+    } else {
+        number.parse::<i64>().ok()?.checked_add(delta)?.to_string()
+    };
+
+    // i.e. 5-3 stepped down 4 times, the minus in front is an operator
+    let stepped = if !signed && stepped.starts_with('-') && start > 0 {
+        format!("({stepped})")
+    } else {
+        stepped
+    };
+
+    let text: String = chars[..start]
+        .iter()
+        .chain(stepped.chars().collect::<Vec<_>>().iter())
+        .chain(chars[end..].iter())
+        .collect();
+    Some(SearchEdit {
+        cursor: start + stepped.chars().count(),
+        text,
+    })
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -344,6 +433,170 @@ fn calc(input: &str) -> String {
     }
 }
 
+/// A calculation of the history, the menu item shows the result
+/// and keeps the expression as action.
+#[derive(Debug, Clone, PartialEq)]
+struct Calculation {
+    expression: String,
+    result: String,
+}
+
+impl Calculation {
+    fn from_item<T: Clone>(item: &MenuItem<T>) -> Option<Self> {
+        Some(Self {
+            expression: item.action.clone()?,
+            result: item.label.clone(),
+        })
+    }
+
+    fn into_item(self) -> MenuItem<()> {
+        MenuItem::new(
+            self.result,
+            None,
+            Some(self.expression),
+            vec![],
+            None,
+            0.0,
+            Some(()),
+        )
+    }
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Cells of a markdown table row, `\|` is an escaped pipe, i.e. of a bitwise or.
+fn parse_markdown_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                chars.next();
+                cells.last_mut().unwrap().push('|');
+            }
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_owned()).collect()
+}
+
+fn format_history(calculations: &[Calculation], csv: bool) -> String {
+    let mut out = String::new();
+    if csv {
+        out.push_str("expression,result\n");
+        for calculation in calculations {
+            out.push_str(&format!(
+                "{},{}\n",
+                csv_field(&calculation.expression),
+                csv_field(&calculation.result)
+            ));
+        }
+    } else {
+        out.push_str("| Expression | Result |\n|---|---|\n");
+        for calculation in calculations {
+            out.push_str(&format!(
+                "| {} | {} |\n",
+                calculation.expression.replace('|', "\\|"),
+                calculation.result.replace('|', "\\|")
+            ));
+        }
+    }
+    out
+}
+
+fn parse_history(content: &str, csv: bool) -> Vec<Calculation> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            if csv {
+                parse_csv_line(line)
+            } else {
+                parse_markdown_row(line)
+            }
+        })
+        .filter_map(|cells| match cells.as_slice() {
+            [expression, result] => Some((expression.clone(), result.clone())),
+            _ => None,
+        })
+        .filter(|(expression, _)| {
+            !expression.eq_ignore_ascii_case("expression")
+                && !expression.chars().all(|c| c == '-' || c == ':')
+        })
+        .map(|(expression, result)| Calculation { expression, result })
+        .collect()
+}
+
+/// Loads the calculations of `math_history_file`, starts empty if there is none.
+fn load_history(path: &Path) -> Vec<MenuItem<()>> {
+    match fs::read_to_string(path) {
+        Ok(content) => parse_history(&content, is_csv(path))
+            .into_iter()
+            .map(Calculation::into_item)
+            .collect(),
+        Err(e) => {
+            log::debug!("no math history in {}: {e}", path.display());
+            vec![]
+        }
+    }
+}
+
+fn export_history(path: &Path, items: &[MenuItem<()>]) -> Result<(), Error> {
+    let calculations: Vec<_> = items.iter().filter_map(Calculation::from_item).collect();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::Io(e.to_string()))?;
+    }
+    fs::write(path, format_history(&calculations, is_csv(path)))
+        .map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Alt+S exports the history of the calculations.
+#[must_use]
+pub fn custom_keys() -> CustomKeys {
+    CustomKeys {
+        bindings: vec![KeyBinding {
+            key: Key::S,
+            modifiers: HashSet::from([Modifier::Alt]),
+            label: EXPORT_LABEL.to_owned(),
+            visible: true,
+        }],
+        hint: None,
+    }
+}
+
 /// Shows the math mode
 /// # Panics
 /// When failing to unwrap the arc lock
@@ -352,38 +605,107 @@ fn calc(input: &str) -> String {
 /// Forwards the errors from `crate::desktop::copy_to_clipboard`
 /// if the text output mode is set to `Clipboard`.
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let mut calc: Vec<MenuItem<()>> = vec![];
+    let history_file = config.read().unwrap().math_history_file();
+    let mut calc: Vec<MenuItem<()>> = load_history(&history_file);
     let provider = Arc::new(Mutex::new(MathProvider::new(())));
     let factory: ArcFactory<()> = Arc::new(Mutex::new(DefaultItemFactory::new()));
     let arc_provider = Arc::clone(&provider) as ArcProvider<()>;
+    let custom_keys = custom_keys();
     loop {
-        provider.lock().unwrap().add_elements(&mut calc.clone());
-        let selection_result = gui::show(
-            config,
+        {
+            let mut provider = provider.lock().unwrap();
+            provider.elements.clear();
+            provider.add_elements(&mut calc.clone());
+        }
+        let mi = gui::show(
+            &config.read().unwrap(),
             Arc::clone(&arc_provider),
             Some(Arc::clone(&factory)),
             None,
             ExpandMode::Verbatim,
-            None,
-        );
+            Some(custom_keys.clone()),
+        )?;
 
-        if let Ok(mi) = selection_result {
-            match config.read().unwrap().text_output_mode() {
-                TextOutputMode::Clipboard => {
-                    crate::desktop::copy_to_clipboard(mi.menu.label, None)?;
-                    break;
-                }
-                TextOutputMode::StandardOutput => {
-                    println!("{}", mi.menu.label);
-                    break;
-                }
-                TextOutputMode::None => calc.push(mi.menu),
+        if mi.custom_key.is_some_and(|key| key.label == EXPORT_LABEL) {
+            // the shown calculation is part of the export, unless it is one of the history
+            if !calc
+                .iter()
+                .any(|item| item.action == mi.menu.action && item.label == mi.menu.label)
+            {
+                calc.push(mi.menu);
             }
+            if let Err(e) = export_history(&history_file, &calc) {
+                log::error!("cannot export math history: {e}");
+            }
+            continue;
+        }
+
+        let output_mode = config.read().unwrap().text_output_mode();
+        if matches!(output_mode, TextOutputMode::None) {
+            calc.push(mi.menu);
         } else {
-            log::error!("No item selected");
+            crate::desktop::output_text(&mi.menu.label, &output_mode, &config.read().unwrap())?;
             break;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(search: &str, cursor: usize, delta: i64) -> Option<(String, usize)> {
+        step_number(search, cursor, delta).map(|edit| (edit.text, edit.cursor))
+    }
+
+    #[test]
+    fn test_step_number_at_cursor() {
+        assert_eq!(step("12+3", 1, 1), Some(("13+3".to_owned(), 2)));
+        assert_eq!(step("12+3", 4, -1), Some(("12+2".to_owned(), 4)));
+        assert_eq!(step("12+3", 2, 10), Some(("22+3".to_owned(), 2)));
+        assert_eq!(step("1.50*2", 0, 1), Some(("2.50*2".to_owned(), 4)));
+    }
+
+    #[test]
+    fn test_step_number_signs() {
+        assert_eq!(step("-1", 2, 2), Some(("1".to_owned(), 1)));
+        assert_eq!(step("0", 1, -2), Some(("-2".to_owned(), 2)));
+        assert_eq!(step("5-1", 3, -2), Some(("5-(-1)".to_owned(), 6)));
+    }
+
+    #[test]
+    fn test_step_number_ignores_non_decimals() {
+        assert_eq!(step("sqrt", 2, 1), None);
+        assert_eq!(step("0x1f", 3, 1), None);
+        assert_eq!(step("1 + ", 4, 1), None);
+    }
+
+    fn calculations() -> Vec<Calculation> {
+        vec![
+            Calculation {
+                expression: "1+2".to_owned(),
+                result: "3 (0x3) (0b11)".to_owned(),
+            },
+            Calculation {
+                expression: "5|2".to_owned(),
+                result: "7, \"seven\"".to_owned(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_history_markdown_round_trip() {
+        let markdown = format_history(&calculations(), false);
+        assert!(markdown.contains("| 5\\|2 |"));
+        assert_eq!(parse_history(&markdown, false), calculations());
+    }
+
+    #[test]
+    fn test_history_csv_round_trip() {
+        let csv = format_history(&calculations(), true);
+        assert!(csv.contains("5|2,\"7, \"\"seven\"\"\""));
+        assert_eq!(parse_history(&csv, true), calculations());
+    }
+}
@@ -6,15 +6,31 @@ use crate::{
     desktop::{cache_file_path, create_file_if_not_exists, load_cache_file},
 };
 
+#[cfg(feature = "mode-auto")]
 pub mod auto;
+pub mod clipboard;
 pub mod dmenu;
 pub mod drun;
+#[cfg(feature = "mode-emoji")]
 pub mod emoji;
+#[cfg(feature = "mode-file")]
 pub mod file;
+#[cfg(feature = "mode-auto")]
+pub mod info;
+#[cfg(feature = "mode-math")]
 pub mod math;
+pub mod notes;
+pub mod open;
+pub mod otp;
+pub mod palette;
 pub mod run;
+#[cfg(feature = "mode-search")]
 pub mod search;
 pub mod ssh;
+#[cfg(feature = "mode-file")]
+mod thumbnail;
+pub mod timer;
+pub mod window;
 
 pub(crate) fn load_cache(
     name: &str,
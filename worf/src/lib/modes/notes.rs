@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use crate::{
+    Error,
+    config::Config,
+    gui::{
+        self, ArcFactory, ArcProvider, ExpandMode, ItemFactory, ItemProvider, MenuItem,
+        ProviderData,
+    },
+};
+
+const OPEN_PREFIX: &str = "- [ ] ";
+const DONE_PREFIX: &str = "- [x] ";
+
+#[derive(Debug, Clone, PartialEq)]
+enum NoteAction {
+    /// Append a new entry with the given text
+    Add(String),
+    /// Toggle the done state of the entry at the given line
+    Toggle(usize),
+    /// Remove the entry at the given line
+    Delete(usize),
+}
+
+#[derive(Clone)]
+struct NotesProvider {
+    path: PathBuf,
+    items: Vec<MenuItem<NoteAction>>,
+}
+
+impl NotesProvider {
+    fn new(path: PathBuf) -> Self {
+        let items = load_entries(&path);
+        Self { path, items }
+    }
+
+    fn reload(&mut self) {
+        self.items = load_entries(&self.path);
+    }
+
+    fn add_item(&self, text: &str) -> MenuItem<NoteAction> {
+        // acceptable, we won't have enough notes for this to matter
+        #[allow(clippy::cast_precision_loss)]
+        let score = (self.items.len() + 1) as f64;
+        MenuItem::new(
+            format!("Add {text}"),
+            Some("list-add".to_owned()),
+            None,
+            vec![],
+            None,
+            score,
+            Some(NoteAction::Add(text.to_owned())),
+        )
+    }
+}
+
+impl ItemProvider<NoteAction> for NotesProvider {
+    fn get_elements(&mut self, query: Option<&str>) -> ProviderData<NoteAction> {
+        let mut items = self.items.clone();
+        if let Some(query) = query.map(str::trim).filter(|q| !q.is_empty()) {
+            items.push(self.add_item(query));
+        }
+        ProviderData::Items(items)
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<NoteAction>) -> ProviderData<NoteAction> {
+        ProviderData::Unchanged
+    }
+}
+
+impl ItemFactory<NoteAction> for NotesProvider {
+    fn new_menu_item(&self, label: String) -> Option<MenuItem<NoteAction>> {
+        let text = label.trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(self.add_item(text))
+        }
+    }
+}
+
+fn load_entries(path: &Path) -> Vec<MenuItem<NoteAction>> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let (text, done) = if let Some(text) = line.strip_prefix(OPEN_PREFIX) {
+                (text, false)
+            } else if let Some(text) = line.strip_prefix(DONE_PREFIX) {
+                (text, true)
+            } else {
+                return None;
+            };
+
+            let toggle_label = if done { "Mark open" } else { "Mark done" };
+            let sub_elements = vec![
+                MenuItem::new(
+                    toggle_label.to_owned(),
+                    Some("object-select".to_owned()),
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    Some(NoteAction::Toggle(idx)),
+                ),
+                MenuItem::new(
+                    "Delete".to_owned(),
+                    Some("edit-delete".to_owned()),
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    Some(NoteAction::Delete(idx)),
+                ),
+            ];
+
+            // newest entries are at the end of the file, show them first.
+            #[allow(clippy::cast_precision_loss)]
+            let score = idx as f64;
+            Some(MenuItem::new(
+                text.to_owned(),
+                Some(if done { "checkbox-checked" } else { "checkbox" }.to_owned()),
+                None,
+                sub_elements,
+                None,
+                score,
+                Some(NoteAction::Toggle(idx)),
+            ))
+        })
+        .collect()
+}
+
+/// Current local time formatted as `YYYY-MM-DD HH:MM`
+fn timestamp() -> String {
+    // SAFETY: `time` and `localtime_r` only write into the provided, zeroed struct.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    )
+}
+
+fn append_entry(path: &Path, text: &str) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::Io(e.to_string()))?;
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| Error::Io(e.to_string()))?;
+    writeln!(file, "{OPEN_PREFIX}{} {text}", timestamp()).map_err(|e| Error::Io(e.to_string()))
+}
+
+fn update_entry(path: &Path, line_idx: usize, delete: bool) -> Result<(), Error> {
+    let content = fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+    let mut lines: Vec<String> = content.lines().map(ToOwned::to_owned).collect();
+    let Some(line) = lines.get_mut(line_idx) else {
+        return Err(Error::InvalidSelection);
+    };
+
+    if delete {
+        lines.remove(line_idx);
+    } else if let Some(text) = line.strip_prefix(OPEN_PREFIX) {
+        *line = format!("{DONE_PREFIX}{text}");
+    } else if let Some(text) = line.strip_prefix(DONE_PREFIX) {
+        *line = format!("{OPEN_PREFIX}{text}");
+    } else {
+        return Err(Error::InvalidSelection);
+    }
+
+    let mut new_content = lines.join("\n");
+    new_content.push('\n');
+    fs::write(path, new_content).map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Shows the notes mode.
+/// Entered text is appended with a timestamp to the configured notes file,
+/// existing entries can be marked as done or deleted.
+/// # Errors
+///
+/// Will return `Err` if the notes file cannot be written.
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let path = config.read().unwrap().notes_file();
+    let provider = Arc::new(Mutex::new(NotesProvider::new(path.clone())));
+
+    loop {
+        provider.lock().unwrap().reload();
+        let selection = gui::show(
+            &config.read().unwrap(),
+            Arc::clone(&provider) as ArcProvider<NoteAction>,
+            Some(Arc::clone(&provider) as ArcFactory<NoteAction>),
+            None,
+            ExpandMode::Verbatim,
+            None,
+        )?;
+
+        match selection.menu.data {
+            Some(NoteAction::Add(text)) => {
+                append_entry(&path, &text)?;
+                break;
+            }
+            Some(NoteAction::Toggle(idx)) => update_entry(&path, idx, false)?,
+            Some(NoteAction::Delete(idx)) => update_entry(&path, idx, true)?,
+            None => return Err(Error::MissingAction),
+        }
+    }
+
+    Ok(())
+}
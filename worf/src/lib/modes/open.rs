@@ -0,0 +1,261 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    process::Command,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use freedesktop_file_parser::EntryType;
+
+use crate::{
+    Error,
+    config::{Config, CustomKeyHintLocation},
+    desktop::{find_desktop_files_with_path, get_locale_variants, launch, lookup_name_with_locale},
+    gui::{
+        self, CustomKeyHint, CustomKeys, ExpandMode, ItemProvider, MenuItem, Modifier, ProviderData,
+    },
+};
+
+#[derive(Clone)]
+struct OpenProvider {
+    items: Vec<MenuItem<String>>,
+}
+
+impl ItemProvider<String> for OpenProvider {
+    fn get_elements(&mut self, _: Option<&str>) -> ProviderData<String> {
+        ProviderData::Items(self.items.clone())
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<String>) -> ProviderData<String> {
+        ProviderData::Unchanged
+    }
+}
+
+/// Mime type used to look up applications for the uri.
+/// Uris with a scheme other than `file` use `x-scheme-handler/<scheme>`,
+/// the type of files is queried from `xdg-mime`.
+fn mime_type(uri: &str) -> Result<String, Error> {
+    if let Some((scheme, _)) = uri.split_once(':')
+        && !scheme.is_empty()
+        && scheme != "file"
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    {
+        return Ok(format!("x-scheme-handler/{}", scheme.to_lowercase()));
+    }
+
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let output = Command::new("xdg-mime")
+        .args(["query", "filetype", path])
+        .output()
+        .map_err(|e| Error::RunFailed(format!("cannot run xdg-mime: {e}")))?;
+    let mime = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if mime.is_empty() {
+        Err(Error::InvalidArgument(format!(
+            "cannot detect the type of {uri}"
+        )))
+    } else {
+        Ok(mime)
+    }
+}
+
+/// Replaces the field codes of a desktop file exec line with the uri,
+/// the uri is appended if there are none.
+fn exec_with_uri(exec: &str, uri: &str) -> String {
+    let quote = |value: &str| {
+        if value.contains('\'') {
+            format!("\"{value}\"")
+        } else {
+            format!("'{value}'")
+        }
+    };
+
+    let mut replaced = false;
+    let cmd = exec
+        .split(' ')
+        .map(|part| match part {
+            "%u" | "%U" => {
+                replaced = true;
+                quote(uri)
+            }
+            "%f" | "%F" => {
+                replaced = true;
+                quote(uri.strip_prefix("file://").unwrap_or(uri))
+            }
+            _ => part.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if replaced {
+        cmd
+    } else {
+        format!("{cmd} {}", quote(uri))
+    }
+}
+
+/// Applications handling the mime type, the data of the items is the desktop file id.
+fn applications_for(mime: &str, uri: &str, config: &Config) -> Vec<MenuItem<String>> {
+    let locale_variants = get_locale_variants();
+    let mut items: Vec<_> = find_desktop_files_with_path()
+        .into_iter()
+        .filter_map(|(path, file)| {
+            let EntryType::Application(app) = &file.entry.entry_type else {
+                return None;
+            };
+            if !app
+                .mime_type
+                .as_ref()
+                .is_some_and(|types| types.iter().any(|t| t == mime))
+            {
+                return None;
+            }
+
+            let id = path.file_name()?.to_string_lossy().into_owned();
+            // worf itself may be registered as handler
+            if id.starts_with("worf") {
+                return None;
+            }
+
+            let name = lookup_name_with_locale(
+                &locale_variants,
+                &file.entry.name.variants,
+                &file.entry.name.default,
+            )?;
+            let mut exec = exec_with_uri(app.exec.as_ref()?, uri);
+            if app.terminal.unwrap_or(false) {
+                exec = format!("{} {exec}", config.term()?);
+            }
+
+            Some(MenuItem::new(
+                name,
+                file.entry.icon.as_ref().map(|icon| icon.content.clone()),
+                Some(exec),
+                vec![],
+                app.path.clone(),
+                0.0,
+                Some(id),
+            ))
+        })
+        .collect();
+    gui::apply_sort(&mut items, &config.sort_order());
+    items
+}
+
+/// Applications chosen to always open a mime type, stored in the state dir.
+fn choices_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("worf-open-choices.toml"))
+}
+
+fn load_choices() -> HashMap<String, String> {
+    choices_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_choice(mime: &str, id: &str) -> Result<(), Error> {
+    let path = choices_path().ok_or(Error::MissingFile)?;
+    let mut choices = load_choices();
+    choices.insert(mime.to_owned(), id.to_owned());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::Io(e.to_string()))?;
+    }
+    let content = toml::to_string(&choices).map_err(|e| Error::ParsingError(e.to_string()))?;
+    fs::write(path, content).map_err(|e| Error::Io(e.to_string()))
+}
+
+fn open_with(item: &MenuItem<String>, config: &Config) -> Result<(), Error> {
+    let action = item.action.as_ref().ok_or(Error::MissingAction)?;
+    launch(action, item.working_dir.as_ref(), &item.env, config)
+}
+
+/// Shows the applications able to open `open_uri` and opens it with the selected one.
+/// Submitting with Ctrl held remembers the application for the scheme or file type,
+/// it is used without asking from then on.
+/// # Errors
+///
+/// Will return `Err`
+/// * if no uri is given or no application can open it
+/// * if it was not able to spawn the process
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let uri = config
+        .read()
+        .unwrap()
+        .open_uri()
+        .ok_or_else(|| Error::InvalidArgument("open mode requires --open-uri".to_owned()))?;
+    let mime = mime_type(&uri)?;
+    let items = applications_for(&mime, &uri, &config.read().unwrap());
+    if items.is_empty() {
+        return Err(Error::InvalidArgument(format!(
+            "no application can open {mime}"
+        )));
+    }
+
+    if let Some(id) = load_choices().get(&mime)
+        && let Some(item) = items.iter().find(|item| item.data.as_ref() == Some(id))
+    {
+        log::debug!("opening {uri} with remembered {id}");
+        return open_with(item, &config.read().unwrap());
+    }
+
+    let selection = gui::show(
+        &config.read().unwrap(),
+        Arc::new(Mutex::new(OpenProvider { items })),
+        None,
+        None,
+        ExpandMode::Verbatim,
+        Some(CustomKeys {
+            bindings: vec![],
+            hint: Some(CustomKeyHint {
+                label: format!("Hold Ctrl to always open {mime} with the selection"),
+                location: CustomKeyHintLocation::Top,
+            }),
+        }),
+    )?;
+
+    if selection.modifiers.contains(&Modifier::Control)
+        && let Some(id) = &selection.menu.data
+        && let Err(e) = save_choice(&mime, id)
+    {
+        log::error!("cannot remember application for {mime}: {e}");
+    }
+    open_with(&selection.menu, &config.read().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type_of_scheme() {
+        assert_eq!(
+            mime_type("https://example.com"),
+            Ok("x-scheme-handler/https".to_owned())
+        );
+        assert_eq!(
+            mime_type("mailto:user@example.com"),
+            Ok("x-scheme-handler/mailto".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_exec_with_uri() {
+        assert_eq!(
+            exec_with_uri("firefox %u", "https://example.com"),
+            "firefox 'https://example.com'"
+        );
+        assert_eq!(
+            exec_with_uri("evince %F", "file:///tmp/a b.pdf"),
+            "evince '/tmp/a b.pdf'"
+        );
+        assert_eq!(exec_with_uri("app", "x://it's"), "app \"x://it's\"");
+    }
+}
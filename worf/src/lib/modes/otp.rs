@@ -0,0 +1,393 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    process::Command,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use gtk4::glib::{self, ChecksumType};
+
+use crate::{
+    Error,
+    config::Config,
+    desktop::{output_text, percent_decode},
+    gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+};
+
+/// A TOTP account of the secrets file.
+#[derive(Debug, Clone, PartialEq)]
+struct Account {
+    label: String,
+    secret: String,
+    /// Hash algorithm of the HMAC, `sha1`, `sha256` or `sha512`
+    algorithm: String,
+    digits: u32,
+    period: u64,
+}
+
+impl Account {
+    fn new(label: String, secret: String) -> Self {
+        Self {
+            label,
+            secret,
+            algorithm: "sha1".to_owned(),
+            digits: 6,
+            period: 30,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct OtpProvider {
+    accounts: Vec<Account>,
+    /// Generated codes by account label, with the time step they are valid for.
+    codes: HashMap<String, (u64, String)>,
+}
+
+impl OtpProvider {
+    fn new(accounts: Vec<Account>) -> Self {
+        Self {
+            accounts,
+            codes: HashMap::new(),
+        }
+    }
+
+    fn code(&mut self, account: &Account, now: u64) -> Option<String> {
+        let step = now / account.period;
+        if let Some((cached_step, code)) = self.codes.get(&account.label)
+            && *cached_step == step
+        {
+            return Some(code.clone());
+        }
+
+        match generate_code(account, now) {
+            Ok(code) => {
+                self.codes
+                    .insert(account.label.clone(), (step, code.clone()));
+                Some(code)
+            }
+            Err(e) => {
+                log::error!("failed to generate code for {}: {e}", account.label);
+                None
+            }
+        }
+    }
+}
+
+impl ItemProvider<String> for OtpProvider {
+    fn get_elements(&mut self, _: Option<&str>) -> ProviderData<String> {
+        let now = now_secs();
+        let accounts = self.accounts.clone();
+        let items = accounts
+            .iter()
+            .map(|account| {
+                let code = self.code(account, now);
+                let remaining = account.period - now % account.period;
+                // periods are a few seconds, precision does not matter
+                #[allow(clippy::cast_precision_loss)]
+                let progress = remaining as f64 / account.period as f64;
+                MenuItem::new(
+                    format!(
+                        "{} — {}",
+                        code.as_deref().map_or("error".to_owned(), format_code),
+                        account.label
+                    ),
+                    Some("dialog-password".to_owned()),
+                    Some(account.label.clone()),
+                    vec![],
+                    None,
+                    0.0,
+                    code,
+                )
+                .with_badge(format!("{remaining}s"))
+                .with_progress(progress)
+            })
+            .collect();
+
+        ProviderData::Items(items)
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<String>) -> ProviderData<String> {
+        ProviderData::Unchanged
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
+    /// Until the first code changes, so the codes are replaced right when they expire.
+    fn expires_in(&self) -> Option<Duration> {
+        codes_valid_for(&self.accounts, now_secs())
+    }
+}
+
+/// Time until the code of one of the accounts changes.
+fn codes_valid_for(accounts: &[Account], now: u64) -> Option<Duration> {
+    accounts
+        .iter()
+        .map(|account| account.period - now % account.period)
+        .min()
+        .map(Duration::from_secs)
+}
+
+/// Groups the digits for readability, i.e. `123 456`.
+fn format_code(code: &str) -> String {
+    let mid = code.len() / 2;
+    format!("{} {}", &code[..mid], &code[mid..])
+}
+
+/// Computes the TOTP code of the account at `now` as described in RFC 6238.
+/// The secret never leaves the process, i.e. as command line argument of a helper.
+fn generate_code(account: &Account, now: u64) -> Result<String, Error> {
+    let checksum_type = match account.algorithm.as_str() {
+        "sha1" => ChecksumType::Sha1,
+        "sha256" => ChecksumType::Sha256,
+        "sha512" => ChecksumType::Sha512,
+        algorithm => {
+            return Err(Error::InvalidArgument(format!(
+                "unsupported algorithm {algorithm}"
+            )));
+        }
+    };
+    let key = decode_base32(&account.secret)
+        .ok_or_else(|| Error::InvalidArgument("secret is not valid base32".to_owned()))?;
+
+    let counter = now / account.period;
+    let hmac = glib::compute_hmac_for_data(checksum_type, &key, &counter.to_be_bytes());
+    let hmac = decode_hex(&hmac).unwrap_or_default();
+
+    // dynamic truncation, the last nibble is the offset of the 31 bit code
+    let offset = usize::from(hmac.last().copied().unwrap_or_default() & 0x0f);
+    let bytes: [u8; 4] = hmac
+        .get(offset..offset + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::RunFailed("cannot compute hmac".to_owned()))?;
+    let binary = u64::from(u32::from_be_bytes(bytes) & 0x7fff_ffff);
+    let digits = account.digits.clamp(1, 10);
+    Ok(format!(
+        "{:0width$}",
+        binary % 10_u64.pow(digits),
+        width = usize::try_from(digits).unwrap_or(10)
+    ))
+}
+
+/// Decodes RFC 4648 base32, case insensitive, spaces and padding are ignored.
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => u64::from(c) - u64::from('A'),
+            c @ '2'..='7' => u64::from(c) - u64::from('2') + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            // only the lowest 8 bits are taken
+            #[allow(clippy::cast_possible_truncation)]
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Reads the secrets file, files ending with `.gpg` are decrypted with gpg.
+fn read_secrets(path: &Path) -> Result<String, Error> {
+    if path.extension().is_some_and(|ext| ext == "gpg") {
+        let output = Command::new("gpg")
+            .args(["--quiet", "--decrypt"])
+            .arg(path)
+            .output()
+            .map_err(|e| Error::RunFailed(format!("cannot run gpg: {e}")))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(Error::RunFailed(format!(
+                "cannot decrypt {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    } else {
+        fs::read_to_string(path).map_err(|e| Error::Io(format!("{}: {e}", path.display())))
+    }
+}
+
+/// Parses one account per line, either as `otpauth://totp/...` uri
+/// or as label followed by the base32 secret.
+/// Empty lines and lines starting with `#` are ignored.
+fn parse_accounts(content: &str) -> Vec<Account> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            if line.starts_with("otpauth://") {
+                parse_otpauth_uri(line)
+            } else {
+                let (label, secret) = line.rsplit_once(char::is_whitespace)?;
+                Some(Account::new(label.trim().to_owned(), secret.to_owned()))
+            }
+        })
+        .collect()
+}
+
+fn parse_otpauth_uri(uri: &str) -> Option<Account> {
+    let rest = uri.strip_prefix("otpauth://totp/")?;
+    let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut label = percent_decode(label);
+
+    let params: HashMap<_, _> = query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_lowercase(), percent_decode(value)))
+        .collect();
+
+    if let Some(issuer) = params.get("issuer")
+        && !label.starts_with(issuer.as_str())
+    {
+        label = format!("{issuer}: {label}");
+    }
+
+    let mut account = Account::new(label, params.get("secret")?.clone());
+    if let Some(algorithm) = params.get("algorithm") {
+        account.algorithm = algorithm.to_lowercase();
+    }
+    if let Some(digits) = params.get("digits").and_then(|d| d.parse().ok()) {
+        account.digits = digits;
+    }
+    if let Some(period) = params
+        .get("period")
+        .and_then(|p| p.parse().ok())
+        .filter(|p| *p > 0)
+    {
+        account.period = period;
+    }
+    Some(account)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Shows the otp mode, which lists the current codes of the accounts
+/// in `otp_file` and copies the selected one.
+/// # Errors
+///
+/// Will return `Err`
+/// * if the secrets file cannot be read or decrypted
+/// * if the code cannot be copied
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let path = config.read().unwrap().otp_file();
+    let accounts = parse_accounts(&read_secrets(&path)?);
+    if accounts.is_empty() {
+        return Err(Error::InvalidArgument(format!(
+            "no accounts found in {}",
+            path.display()
+        )));
+    }
+
+    let selection = gui::show(
+        &config.read().unwrap(),
+        Arc::new(Mutex::new(OtpProvider::new(accounts))),
+        None,
+        None,
+        ExpandMode::Verbatim,
+        None,
+    )?;
+
+    let code = selection.menu.data.ok_or(Error::InvalidSelection)?;
+    let config = config.read().unwrap();
+    output_text(&code, &config.text_output_mode(), &config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accounts() {
+        let content = "# comment\n\
+                       GitHub JBSWY3DPEHPK3PXP\n\
+                       otpauth://totp/ACME%20Co:john@example.com?secret=HXDMVJECJJWSRB3H&issuer=ACME%20Co&digits=8&period=60\n\
+                       otpauth://totp/mail?secret=ABC&issuer=Mail&algorithm=SHA256\n";
+        let accounts = parse_accounts(content);
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(
+            accounts[0],
+            Account::new("GitHub".to_owned(), "JBSWY3DPEHPK3PXP".to_owned())
+        );
+        assert_eq!(accounts[1].label, "ACME Co:john@example.com");
+        assert_eq!(accounts[1].digits, 8);
+        assert_eq!(accounts[1].period, 60);
+        assert_eq!(accounts[2].label, "Mail: mail");
+        assert_eq!(accounts[2].algorithm, "sha256");
+    }
+
+    #[test]
+    fn test_generate_code_rfc6238() {
+        // the secret of the test vectors is `12345678901234567890`
+        let mut account = Account::new(
+            "test".to_owned(),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_owned(),
+        );
+        account.digits = 8;
+        assert_eq!(generate_code(&account, 59).unwrap(), "94287082");
+        assert_eq!(generate_code(&account, 1_111_111_109).unwrap(), "07081804");
+        assert_eq!(generate_code(&account, 2_000_000_000).unwrap(), "69279037");
+
+        account.digits = 6;
+        assert_eq!(generate_code(&account, 59).unwrap(), "287082");
+        account.algorithm = "md5".to_owned();
+        assert!(generate_code(&account, 59).is_err());
+    }
+
+    #[test]
+    fn test_codes_valid_until_first_period_ends() {
+        let mut slow = Account::new("slow".to_owned(), "JBSWY3DPEHPK3PXP".to_owned());
+        slow.period = 45;
+        let default = Account::new("default".to_owned(), "JBSWY3DPEHPK3PXP".to_owned());
+
+        assert_eq!(codes_valid_for(&[], 100), None);
+        assert_eq!(
+            codes_valid_for(&[slow.clone()], 100),
+            Some(Duration::from_secs(35))
+        );
+        assert_eq!(
+            codes_valid_for(&[slow, default.clone()], 100),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            codes_valid_for(&[default], 119),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_decode_base32() {
+        assert_eq!(decode_base32("mzxw6ytb oi======").unwrap(), b"foobar");
+        assert!(decode_base32("MZXW1").is_none());
+    }
+
+    #[test]
+    fn test_format_code() {
+        assert_eq!(format_code("123456"), "123 456");
+        assert_eq!(format_code("12345678"), "1234 5678");
+    }
+}
@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::{
+    Error,
+    config::Config,
+    gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    modes,
+};
+
+/// Columns used when switching to the grid layout
+const GRID_COLUMNS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaletteAction {
+    Drun,
+    Run,
+    #[cfg(feature = "mode-file")]
+    File,
+    #[cfg(feature = "mode-math")]
+    Math,
+    Ssh,
+    #[cfg(feature = "mode-emoji")]
+    Emoji,
+    #[cfg(feature = "mode-search")]
+    WebSearch,
+    Notes,
+    Timer,
+    Window,
+    Clipboard,
+    #[cfg(feature = "mode-auto")]
+    Auto,
+    ToggleHideSearch,
+    ToggleGrid,
+    ToggleImages,
+}
+
+#[derive(Clone)]
+struct PaletteProvider {
+    items: Vec<MenuItem<PaletteAction>>,
+}
+
+impl PaletteProvider {
+    fn new(config: &Config) -> Self {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        let modes = [
+            ("Launch an application", "system-run", PaletteAction::Drun),
+            ("Run a command", "utilities-terminal", PaletteAction::Run),
+            #[cfg(feature = "mode-file")]
+            (
+                "Open file browser",
+                "system-file-manager",
+                PaletteAction::File,
+            ),
+            #[cfg(feature = "mode-math")]
+            ("Calculate", "accessories-calculator", PaletteAction::Math),
+            ("Connect via ssh", "network-server", PaletteAction::Ssh),
+            #[cfg(feature = "mode-emoji")]
+            ("Pick an emoji", "face-smile", PaletteAction::Emoji),
+            #[cfg(feature = "mode-search")]
+            ("Search the web", "web-browser", PaletteAction::WebSearch),
+            (
+                "Take a note",
+                "accessories-text-editor",
+                PaletteAction::Notes,
+            ),
+            ("Start a timer", "alarm-symbolic", PaletteAction::Timer),
+            (
+                "Switch window",
+                "preferences-system-windows",
+                PaletteAction::Window,
+            ),
+            ("Clipboard history", "edit-paste", PaletteAction::Clipboard),
+            #[cfg(feature = "mode-auto")]
+            ("Auto mode", "system-search", PaletteAction::Auto),
+        ]
+        .map(|(label, icon, action)| (label.to_owned(), icon, action));
+
+        let toggles = [
+            (
+                format!("Toggle hide search ({})", on_off(config.hide_search())),
+                "edit-find",
+                PaletteAction::ToggleHideSearch,
+            ),
+            (
+                if config.columns() > 1 {
+                    "Switch to list layout".to_owned()
+                } else {
+                    "Switch to grid layout".to_owned()
+                },
+                "view-grid",
+                PaletteAction::ToggleGrid,
+            ),
+            (
+                format!("Toggle images ({})", on_off(config.allow_images())),
+                "image-x-generic",
+                PaletteAction::ToggleImages,
+            ),
+        ];
+
+        let mut items: Vec<_> = modes
+            .into_iter()
+            .chain(toggles)
+            .map(|(label, icon, action)| {
+                MenuItem::new(
+                    label,
+                    Some(icon.to_owned()),
+                    None,
+                    vec![],
+                    None,
+                    0.0,
+                    Some(action),
+                )
+            })
+            .collect();
+        // shown in the order above, unless the search ranks them differently
+        gui::keep_order(&mut items);
+
+        Self { items }
+    }
+}
+
+impl ItemProvider<PaletteAction> for PaletteProvider {
+    fn get_elements(&mut self, _: Option<&str>) -> ProviderData<PaletteAction> {
+        ProviderData::Items(self.items.clone())
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<PaletteAction>) -> ProviderData<PaletteAction> {
+        ProviderData::Unchanged
+    }
+}
+
+/// Shows the palette of worf's own modes and settings.
+/// Selecting a mode opens it, toggling a setting applies it
+/// to this session and shows the palette again.
+/// # Errors
+///
+/// Forwards the errors of the selected mode.
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    loop {
+        let provider = Arc::new(Mutex::new(PaletteProvider::new(&config.read().unwrap())));
+        let selection = gui::show(
+            &config.read().unwrap(),
+            provider,
+            None,
+            None,
+            ExpandMode::Verbatim,
+            None,
+        )?;
+        let Some(action) = selection.menu.data else {
+            return Err(Error::MissingAction);
+        };
+
+        let mut cfg = config.write().unwrap();
+        match action {
+            PaletteAction::ToggleHideSearch => {
+                let hide_search = !cfg.hide_search();
+                cfg.set_hide_search(hide_search);
+                continue;
+            }
+            PaletteAction::ToggleGrid => {
+                let columns = if cfg.columns() > 1 { 1 } else { GRID_COLUMNS };
+                cfg.set_columns(columns);
+                continue;
+            }
+            PaletteAction::ToggleImages => {
+                let allow_images = !cfg.allow_images();
+                cfg.set_allow_images(allow_images);
+                continue;
+            }
+            _ => {}
+        }
+
+        // the prompt of the palette does not fit the selected mode
+        cfg.set_prompt(format!("{action:?}").to_lowercase());
+        drop(cfg);
+
+        return match action {
+            PaletteAction::Drun => modes::drun::show(config),
+            PaletteAction::Run => modes::run::show(config),
+            #[cfg(feature = "mode-file")]
+            PaletteAction::File => modes::file::show(config),
+            #[cfg(feature = "mode-math")]
+            PaletteAction::Math => modes::math::show(config),
+            PaletteAction::Ssh => modes::ssh::show(config),
+            #[cfg(feature = "mode-emoji")]
+            PaletteAction::Emoji => modes::emoji::show(config),
+            #[cfg(feature = "mode-search")]
+            PaletteAction::WebSearch => modes::search::show(config),
+            PaletteAction::Notes => modes::notes::show(config),
+            PaletteAction::Timer => modes::timer::show(config),
+            PaletteAction::Window => modes::window::show(config),
+            PaletteAction::Clipboard => modes::clipboard::show(config),
+            #[cfg(feature = "mode-auto")]
+            PaletteAction::Auto => modes::auto::show(config),
+            PaletteAction::ToggleHideSearch
+            | PaletteAction::ToggleGrid
+            | PaletteAction::ToggleImages => Ok(()),
+        };
+    }
+}
@@ -1,18 +1,18 @@
 use std::{
-    collections::{HashMap, HashSet},
-    env,
-    ffi::CString,
-    fs,
+    collections::HashSet,
+    env, fs,
+    os::unix::process::CommandExt,
     path::PathBuf,
+    process::Command,
     sync::{Arc, Mutex, RwLock},
 };
 
 use crate::{
     Error,
-    config::{Config, SortOrder},
-    desktop::{is_executable, save_cache_file},
+    config::{Config, SortOrder, expand_path},
+    desktop::{apply_launch_method, describe_command, is_executable},
     gui::{self, ArcProvider, ExpandMode, ItemProvider, MenuItem, ProviderData},
-    modes::load_cache,
+    history::{self, History},
 };
 
 impl ItemProvider<()> for RunProvider {
@@ -21,84 +21,81 @@ impl ItemProvider<()> for RunProvider {
             self.items = Some(self.load().clone());
         }
         if query.is_some() {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
-            ProviderData {
-                items: self.items.clone(),
-            }
+            self.items
+                .clone()
+                .map_or(ProviderData::Unchanged, ProviderData::Items)
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<()>) -> ProviderData<()> {
-        ProviderData {
-            items: self.items.clone(),
-        }
+        self.items
+            .clone()
+            .map_or(ProviderData::Unchanged, ProviderData::Items)
     }
 }
 
 #[derive(Clone)]
 struct RunProvider {
     items: Option<Vec<MenuItem<()>>>,
-    cache_path: PathBuf,
-    cache: HashMap<String, i64>,
+    history: History,
     sort_order: SortOrder,
+    all_instances: bool,
 }
 
 impl RunProvider {
-    fn new(config: &Config) -> Result<Self, Error> {
-        let (cache_path, d_run_cache) = load_cache("worf-run", config)?;
-        Ok(RunProvider {
+    fn new(config: &Config) -> Self {
+        RunProvider {
             items: None,
-            cache_path,
-            cache: d_run_cache,
+            history: History::load("run", config),
             sort_order: config.sort_order(),
-        })
+            all_instances: config.run_all_instances(),
+        }
     }
 
     fn load(&self) -> Vec<MenuItem<()>> {
-        let path_var = env::var("PATH").unwrap_or_default();
-        let paths = env::split_paths(&path_var);
-
-        let entries: Vec<_> = paths
-            .filter(|dir| dir.is_dir())
-            .flat_map(|dir| {
-                fs::read_dir(dir)
-                    .into_iter()
-                    .flatten()
-                    .filter_map(Result::ok)
-                    .filter_map(|entry| {
-                        let path = entry.path();
-                        if !is_executable(&path) {
-                            return None;
-                        }
-
-                        let label = path.file_name()?.to_str()?.to_string();
-                        // acceptable, because we most likely have not enough items anyways
-                        #[allow(clippy::cast_precision_loss)]
-                        let sort_score = *self.cache.get(&label).unwrap_or(&0) as f64;
-
-                        Some(MenuItem::new(
-                            label,
-                            None,
-                            path.to_str().map(ToString::to_string),
-                            vec![],
-                            None,
-                            sort_score,
-                            None,
-                        ))
-                    })
-            })
-            .collect();
+        let mut instances: Vec<(String, PathBuf)> = Vec::new();
+        for dir in path_dirs() {
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut binaries: Vec<_> = read_dir
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| is_executable(path))
+                .filter_map(|path| Some((path.file_name()?.to_str()?.to_string(), path)))
+                .collect();
+            binaries.sort_unstable();
+            instances.extend(binaries);
+        }
 
-        let mut seen_actions = HashSet::new();
-        let mut entries: Vec<MenuItem<()>> = entries
-            .into_iter()
-            .filter(|entry| {
-                entry
-                    .action
-                    .as_ref()
-                    .and_then(|action| action.split('/').next_back())
-                    .is_some_and(|cmd| seen_actions.insert(cmd.to_string()))
+        // the first instance in `$PATH` order is the one the shell runs
+        let mut seen = HashSet::new();
+        let mut entries: Vec<MenuItem<()>> = instances
+            .iter()
+            .filter_map(|(label, path)| {
+                if !seen.insert(label.as_str()) && !self.all_instances {
+                    return None;
+                }
+
+                let sort_score = self.history.sort_score(label, &self.sort_order);
+                // the path also tells the instances of `run_all_instances` apart
+                let full_path = path.to_string_lossy().to_string();
+                Some(
+                    MenuItem::new(
+                        label.clone(),
+                        None,
+                        Some(full_path.clone()),
+                        vec![],
+                        None,
+                        sort_score,
+                        None,
+                    )
+                    .with_id(full_path.clone())
+                    .with_description(full_path.clone())
+                    .with_preview(full_path),
+                )
             })
             .collect();
 
@@ -107,23 +104,39 @@ impl RunProvider {
     }
 }
 
-fn update_run_cache_and_run<T: Clone>(
-    cache_path: &PathBuf,
-    cache: &mut HashMap<String, i64>,
+/// The directories of `$PATH` in the order the shell searches them.
+/// Relative and empty entries are skipped and directories listed twice,
+/// also through symlinks, are only returned once.
+fn path_dirs() -> Vec<PathBuf> {
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    let mut seen = HashSet::new();
+    env::split_paths(&path_var)
+        .filter(|dir| dir.is_absolute() && dir.is_dir())
+        .filter(|dir| seen.insert(fs::canonicalize(dir).unwrap_or_else(|_| dir.clone())))
+        .collect()
+}
+
+fn update_history_and_run<T: Clone>(
     selection_result: MenuItem<T>,
+    config: &Config,
 ) -> Result<(), Error> {
-    *cache.entry(selection_result.label).or_insert(0) += 1;
-    if let Err(e) = save_cache_file(cache_path, cache) {
-        log::warn!("cannot save run cache {e:?}");
-    }
-
     if let Some(action) = selection_result.action {
-        let program = CString::new(action).unwrap();
-        let args = [program.clone()];
+        let mut cmd = Command::new(action);
+        cmd.envs(selection_result.env);
+        if let Some(dir) = selection_result.working_dir {
+            cmd.current_dir(expand_path(&dir));
+        }
+
+        let mut cmd = apply_launch_method(cmd, config.launch_method(), None);
+
+        if config.dry_run() {
+            println!("{}", describe_command(&cmd));
+            return Ok(());
+        }
 
-        // This replaces the current process image
-        nix::unistd::execvp(&program, &args).map_err(|e| Error::RunFailed(e.to_string()))?;
-        Ok(())
+        history::record_usage("run", &selection_result.label, config);
+        // This replaces the current process image and only returns on failure
+        Err(Error::RunFailed(cmd.exec().to_string()))
     } else {
         Err(Error::MissingAction)
     }
@@ -136,19 +149,16 @@ fn update_run_cache_and_run<T: Clone>(
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let provider = Arc::new(Mutex::new(RunProvider::new(&config.read().unwrap())?));
-    let arc_provider = Arc::clone(&provider) as ArcProvider<()>;
-
-    let selection_result = gui::show(config, arc_provider, None, None, ExpandMode::Verbatim, None);
-    match selection_result {
-        Ok(s) => {
-            let prov = provider.lock().unwrap();
-            update_run_cache_and_run(&prov.cache_path, &mut prov.cache.clone(), s.menu)?;
-        }
-        Err(_) => {
-            log::error!("No item selected");
-        }
-    }
-
-    Ok(())
+    let provider: ArcProvider<()> = Arc::new(Mutex::new(RunProvider::new(&config.read().unwrap())));
+
+    let selection = gui::show(
+        &config.read().unwrap(),
+        provider,
+        None,
+        None,
+        ExpandMode::Verbatim,
+        None,
+    )?;
+
+    update_history_and_run(selection.menu, &config.read().unwrap())
 }
@@ -1,18 +1,64 @@
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
 
+use regex::Regex;
 use urlencoding::encode;
 
 use crate::{
     Error,
-    config::Config,
-    desktop::spawn_fork,
+    config::{Config, SortOrder},
+    desktop::launch,
     gui::{self, ArcFactory, DefaultItemFactory, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    history::{self, History},
 };
 
+const LABEL_PREFIX: &str = "Search ";
+const OPEN_PREFIX: &str = "Open ";
+
+/// Top level domains a query without scheme has to end with to count as url,
+/// so i.e. `org.gnome.Nautilus` is still searched for.
+const KNOWN_TLDS: &[&str] = &[
+    "app", "at", "be", "biz", "ca", "ch", "cn", "co", "com", "de", "dev", "edu", "es", "eu", "fr",
+    "gov", "info", "io", "it", "jp", "me", "net", "nl", "org", "pl", "ru", "se", "uk", "us",
+];
+
+/// The query as url to open, `None` if it is not an url or domain.
+/// Queries without scheme are opened with https, local hosts with http.
+pub(crate) fn url_of(query: &str) -> Option<String> {
+    static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^(?:(https?)://)?((?:[a-z0-9-]+\.)*[a-z0-9-]+)(:\d+)?([/?#]\S*)?$")
+            .unwrap()
+    });
+    let caps = URL_RE.captures(query.trim())?;
+    let host = caps[2].to_lowercase();
+    let rest = format!(
+        "{}{}",
+        caps.get(3).map_or("", |m| m.as_str()),
+        caps.get(4).map_or("", |m| m.as_str())
+    );
+
+    if let Some(scheme) = caps.get(1) {
+        return Some(format!("{}://{host}{rest}", scheme.as_str().to_lowercase()));
+    }
+
+    let is_ipv4 =
+        host.split('.').count() == 4 && host.split('.').all(|part| part.parse::<u8>().is_ok());
+    if host == "localhost" || is_ipv4 {
+        return Some(format!("http://{host}{rest}"));
+    }
+    let (_, tld) = host.rsplit_once('.')?;
+    KNOWN_TLDS
+        .contains(&tld)
+        .then(|| format!("https://{host}{rest}"))
+}
+
 #[derive(Clone)]
 pub(crate) struct SearchProvider<T: Clone> {
     search_query: String,
     data: T,
+    /// Previous searches, offered below the current query if set.
+    history: Option<History>,
+    /// Offer to open queries which are urls, see `url_of`
+    open_urls: bool,
 }
 
 impl<T: Clone> SearchProvider<T> {
@@ -20,34 +66,82 @@ impl<T: Clone> SearchProvider<T> {
         Self {
             search_query,
             data: data.clone(),
+            history: None,
+            open_urls: false,
         }
     }
+
+    /// Offers to open the query directly if it is an url, above the search.
+    pub(crate) fn with_open_urls(mut self, open_urls: bool) -> Self {
+        self.open_urls = open_urls;
+        self
+    }
+
+    /// Offers previous searches containing the query, ranked by frecency.
+    pub(crate) fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    fn search_item(&self, query: &str, sort_score: f64) -> MenuItem<T> {
+        let url = format!("{}{}", self.search_query, encode(query));
+        MenuItem::new(
+            format!("{LABEL_PREFIX}{query}"),
+            None,
+            Some(format!("xdg-open {url}")),
+            vec![],
+            None,
+            sort_score,
+            Some(self.data.clone()),
+        )
+    }
 }
 
 impl<T: Clone> ItemProvider<T> for SearchProvider<T> {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<T> {
         if let Some(query) = query {
-            let url = format!("{}{}", self.search_query, encode(query));
-            let run_search = MenuItem::new(
-                format!("Search {query}"),
-                None,
-                Some(format!("xdg-open {url}")),
-                vec![],
-                None,
-                0.0,
-                Some(self.data.clone()),
-            );
+            let lower_query = query.to_lowercase();
+            let previous: Vec<_> = self
+                .history
+                .iter()
+                .flat_map(History::ranked)
+                .filter(|(previous, _)| {
+                    previous != query && previous.to_lowercase().contains(&lower_query)
+                })
+                .collect();
 
-            ProviderData {
-                items: Some(vec![run_search]),
+            // the current query stays on top of all previous searches
+            let top_score = previous.first().map_or(0.0, |(_, score)| score + 1.0);
+            let mut items = vec![self.search_item(query, top_score)];
+            if self.open_urls
+                && let Some(url) = url_of(query)
+            {
+                items.insert(
+                    0,
+                    MenuItem::new(
+                        format!("{OPEN_PREFIX}{url}"),
+                        None,
+                        Some(format!("xdg-open {url}")),
+                        vec![],
+                        None,
+                        top_score + 1.0,
+                        Some(self.data.clone()),
+                    ),
+                );
             }
+            items.extend(
+                previous
+                    .iter()
+                    .map(|(previous, score)| self.search_item(previous, *score)),
+            );
+            ProviderData::Items(items)
         } else {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<T>) -> ProviderData<T> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
     }
 }
 
@@ -58,13 +152,17 @@ impl<T: Clone> ItemProvider<T> for SearchProvider<T> {
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let provider = Arc::new(Mutex::new(SearchProvider::new(
-        (),
-        config.read().unwrap().search_query(),
-    )));
+    let cfg = config.read().unwrap();
+    let mut provider =
+        SearchProvider::new((), cfg.search_query()).with_open_urls(cfg.search_open_urls());
+    if cfg.sort_order() == SortOrder::Frecency {
+        provider = provider.with_history(History::load("websearch", &cfg));
+    }
+    drop(cfg);
+    let provider = Arc::new(Mutex::new(provider));
     let factory: ArcFactory<()> = Arc::new(Mutex::new(DefaultItemFactory::new()));
     let selection_result = gui::show(
-        config,
+        &config.read().unwrap(),
         provider,
         Some(factory),
         None,
@@ -73,6 +171,39 @@ pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
     )?;
     match selection_result.menu.action {
         None => Err(Error::MissingAction),
-        Some(action) => spawn_fork(&action, None),
+        Some(action) => {
+            let config = config.read().unwrap();
+            if let Some(query) = selection_result.menu.label.strip_prefix(LABEL_PREFIX) {
+                history::record_usage("websearch", query, &config);
+            }
+            launch(&action, None, &[], &config)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_of_normalizes_scheme() {
+        assert_eq!(url_of("github.com"), Some("https://github.com".to_owned()));
+        assert_eq!(
+            url_of("HTTP://Example.org/a?b=c"),
+            Some("http://example.org/a?b=c".to_owned())
+        );
+        assert_eq!(
+            url_of("localhost:8080/api"),
+            Some("http://localhost:8080/api".to_owned())
+        );
+        assert_eq!(url_of("192.168.0.1"), Some("http://192.168.0.1".to_owned()));
+    }
+
+    #[test]
+    fn test_url_of_ignores_searches() {
+        assert_eq!(url_of("rust borrow checker"), None);
+        assert_eq!(url_of("org.gnome.Nautilus"), None);
+        assert_eq!(url_of("firefox"), None);
+        assert_eq!(url_of("ftp://example.com"), None);
     }
 }
@@ -1,6 +1,10 @@
 use std::{
+    collections::HashMap,
     fs,
+    net::{TcpStream, ToSocketAddrs},
     sync::{Arc, LazyLock, Mutex, RwLock},
+    thread,
+    time::Duration,
 };
 
 use regex::Regex;
@@ -8,23 +12,39 @@ use regex::Regex;
 use crate::{
     Error,
     config::{Config, SortOrder},
-    desktop::spawn_fork,
+    desktop::launch,
     gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+    history::{self, History},
 };
 
+/// Time a single connection attempt of the reachability probe may take.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Interval in which finished probes are shown.
+const PROBE_REFRESH: Duration = Duration::from_millis(250);
+
+/// Subtracted from the sort score of unreachable hosts, so they are shown last.
+const UNREACHABLE_PENALTY: f64 = 1000.0;
+
+type ProbeResults = Arc<Mutex<HashMap<String, bool>>>;
+
 #[derive(Clone)]
 pub(crate) struct SshProvider<T: Clone> {
     items: Vec<MenuItem<T>>,
+    /// Reachability of the probed hosts, `None` if probing is disabled.
+    probes: Option<ProbeResults>,
+    /// Number of probe results already applied to the items.
+    applied_probes: usize,
+    order: SortOrder,
 }
 
 impl<T: Clone> SshProvider<T> {
-    pub(crate) fn new(menu_item_data: T, order: &SortOrder) -> Self {
+    pub(crate) fn new(menu_item_data: T, config: &Config) -> Self {
         static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^\s*Host\s+(.+)$").unwrap());
         let re = &*RE;
-        let mut items: Vec<_> = dirs::home_dir()
-            .map(|home| home.join(".ssh").join("config"))
-            .filter(|path| path.exists())
-            .map(|path| fs::read_to_string(&path).unwrap_or_default())
+        let order = config.sort_order();
+        let history = (order == SortOrder::Frecency).then(|| History::load("ssh", config));
+        let mut items: Vec<_> = read_ssh_config()
             .into_iter()
             .flat_map(|content| {
                 re.captures_iter(&content)
@@ -39,7 +59,7 @@ impl<T: Clone> SshProvider<T> {
                                     Some(format!("ssh {host}")),
                                     vec![],
                                     None,
-                                    0.0,
+                                    history.as_ref().map_or(0.0, |h| h.score(host)),
                                     Some(menu_item_data.clone()),
                                 )
                             })
@@ -49,28 +69,154 @@ impl<T: Clone> SshProvider<T> {
             })
             .collect();
 
-        gui::apply_sort(&mut items, order);
-        Self { items }
+        gui::apply_sort(&mut items, &order);
+        Self {
+            items,
+            probes: None,
+            applied_probes: 0,
+            order,
+        }
+    }
+
+    /// Starts probing the ssh port of all hosts in the background.
+    /// The results are shown as badge once they are available.
+    pub(crate) fn with_probes(mut self) -> Self {
+        let targets = read_ssh_config()
+            .map(|content| probe_targets(&content))
+            .unwrap_or_default();
+        let results = ProbeResults::default();
+
+        for item in &mut self.items {
+            let Some((host, port)) = targets.get(&item.label).cloned() else {
+                continue;
+            };
+            item.badge = Some("…".to_owned());
+            item.badge_class = Some("probing".to_owned());
+
+            let alias = item.label.clone();
+            let results = Arc::clone(&results);
+            thread::spawn(move || {
+                let reachable = probe(&host, port);
+                log::debug!("ssh host {alias} reachable: {reachable}");
+                results.lock().unwrap().insert(alias, reachable);
+            });
+        }
+
+        self.probes = Some(results);
+        self
+    }
+
+    fn apply_probe_results(&mut self, results: &HashMap<String, bool>) {
+        for item in &mut self.items {
+            let Some(&reachable) = results.get(&item.label) else {
+                continue;
+            };
+            if item.badge_class.as_deref() != Some("probing") {
+                continue;
+            }
+
+            let (badge, class) = if reachable {
+                ("online", "reachable")
+            } else {
+                ("offline", "unreachable")
+            };
+            item.badge = Some(badge.to_owned());
+            item.badge_class = Some(class.to_owned());
+            if !reachable && self.order != SortOrder::MostUsed {
+                item.initial_sort_score -= UNREACHABLE_PENALTY;
+            }
+        }
     }
 }
 
 impl<T: Clone> ItemProvider<T> for SshProvider<T> {
     fn get_elements(&mut self, query: Option<&str>) -> ProviderData<T> {
+        if let Some(probes) = &self.probes {
+            let results = probes.lock().unwrap().clone();
+            if results.len() != self.applied_probes {
+                self.applied_probes = results.len();
+                self.apply_probe_results(&results);
+                return ProviderData::Items(self.items.clone());
+            }
+        }
+
         if query.is_some() {
-            ProviderData { items: None }
+            ProviderData::Unchanged
         } else {
-            ProviderData {
-                items: Some(self.items.clone()),
-            }
+            ProviderData::Items(self.items.clone())
         }
     }
 
     fn get_sub_elements(&mut self, _: &MenuItem<T>) -> ProviderData<T> {
-        ProviderData { items: None }
+        ProviderData::Unchanged
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.probes.as_ref().map(|_| PROBE_REFRESH)
+    }
+}
+
+fn read_ssh_config() -> Option<String> {
+    dirs::home_dir()
+        .map(|home| home.join(".ssh").join("config"))
+        .filter(|path| path.exists())
+        .map(|path| fs::read_to_string(&path).unwrap_or_default())
+}
+
+/// Address and port to probe for every host alias of the ssh config.
+/// Patterns like `*.example.com` cannot be probed and are skipped.
+fn probe_targets(content: &str) -> HashMap<String, (String, u16)> {
+    let mut targets: HashMap<String, (String, u16)> = HashMap::new();
+    let mut current: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        match key.to_lowercase().as_str() {
+            "host" => {
+                current = line
+                    .split_whitespace()
+                    .skip(1)
+                    .filter(|host| !host.contains(['*', '?', '!']))
+                    .map(ToOwned::to_owned)
+                    .collect();
+                for host in &current {
+                    targets.insert(host.clone(), (host.clone(), 22));
+                }
+            }
+            "hostname" => {
+                for host in &current {
+                    if let Some(target) = targets.get_mut(host) {
+                        target.0 = value.to_owned();
+                    }
+                }
+            }
+            "port" => {
+                for host in &current {
+                    if let Some(target) = targets.get_mut(host)
+                        && let Ok(port) = value.parse()
+                    {
+                        target.1 = port;
+                    }
+                }
+            }
+            "match" => current.clear(),
+            _ => {}
+        }
     }
+    targets
+}
+
+fn probe(host: &str, port: u16) -> bool {
+    (host, port).to_socket_addrs().is_ok_and(|mut addrs| {
+        addrs.any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok())
+    })
 }
 
 pub(crate) fn launch<T: Clone>(menu_item: &MenuItem<T>, config: &Config) -> Result<(), Error> {
+    history::record_usage("ssh", &menu_item.label, config);
     let ssh_cmd = if let Some(action) = &menu_item.action {
         action.clone()
     } else {
@@ -88,7 +234,7 @@ pub(crate) fn launch<T: Clone>(menu_item: &MenuItem<T>, config: &Config) -> Resu
         "{} bash -c \"source ~/.bashrc; {ssh_cmd}\"",
         config.term().unwrap_or_default()
     );
-    spawn_fork(&cmd, menu_item.working_dir.as_ref())
+    launch(&cmd, menu_item.working_dir.as_ref(), &menu_item.env, config)
 }
 
 /// Shows the ssh mode
@@ -100,15 +246,35 @@ pub(crate) fn launch<T: Clone>(menu_item: &MenuItem<T>, config: &Config) -> Resu
 /// # Panics
 /// When failing to unwrap the arc lock
 pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
-    let provider = Arc::new(Mutex::new(SshProvider::new(
-        0,
-        &config.read().unwrap().sort_order(),
-    )));
-    let selection_result = gui::show(config, provider, None, None, ExpandMode::Verbatim, None);
-    if let Ok(mi) = selection_result {
-        launch(&mi.menu, &config.read().unwrap())?;
-    } else {
-        log::error!("No item selected");
+    let mut provider = SshProvider::new(0, &config.read().unwrap());
+    if config.read().unwrap().ssh_probe() {
+        provider = provider.with_probes();
+    }
+    let provider = Arc::new(Mutex::new(provider));
+    let selection = gui::show(
+        &config.read().unwrap(),
+        provider,
+        None,
+        None,
+        ExpandMode::Verbatim,
+        None,
+    )?;
+    launch(&selection.menu, &config.read().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_targets() {
+        let config = "Host web www\n  HostName example.com\n  Port 2222\n\n\
+                      Host *.internal\n  User admin\n\n\
+                      Host db\n  User root\n";
+        let targets = probe_targets(config);
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets["web"], ("example.com".to_owned(), 2222));
+        assert_eq!(targets["www"], ("example.com".to_owned(), 2222));
+        assert_eq!(targets["db"], ("db".to_owned(), 22));
     }
-    Ok(())
 }
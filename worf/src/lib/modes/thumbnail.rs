@@ -0,0 +1,267 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::LazyLock,
+    time::UNIX_EPOCH,
+};
+
+use gtk4::{
+    gdk_pixbuf::Pixbuf,
+    glib::{self, ChecksumType},
+};
+
+/// Size of thumbnails in the `normal` folder of the cache.
+const THUMBNAIL_SIZE: i32 = 128;
+
+/// Thumbnailer installed on the system, see `/usr/share/thumbnailers`.
+#[derive(Debug, PartialEq)]
+struct Thumbnailer {
+    exec: String,
+    mime_types: Vec<String>,
+}
+
+/// The thumbnail of the file, created if there is no up to date one in
+/// `$XDG_CACHE_HOME/thumbnails`. Images are scaled directly, other files
+/// need a thumbnailer for their mime type, i.e. for pdfs or videos.
+pub(crate) fn thumbnail(path: &Path, mime: &str) -> Option<PathBuf> {
+    let uri = file_uri(path)?;
+    let mtime = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let name = md5_hex(uri.as_bytes())?;
+    let thumbnail = dirs::cache_dir()?
+        .join("thumbnails")
+        .join("normal")
+        .join(format!("{name}.png"));
+
+    if is_up_to_date(&thumbnail, mtime) {
+        return Some(thumbnail);
+    }
+
+    let dir = thumbnail.parent()?;
+    fs::create_dir_all(dir).ok()?;
+    // written next to the thumbnail and renamed, so nobody reads half a file
+    let tmp = dir.join(format!("worf-{name}-{}.png", std::process::id()));
+    let created = if let Some(thumbnailer) = THUMBNAILERS
+        .iter()
+        .find(|thumbnailer| thumbnailer.mime_types.iter().any(|m| m == mime))
+    {
+        run_thumbnailer(thumbnailer, path, &uri, &tmp)
+    } else if mime.starts_with("image") {
+        scale_image(path, &uri, mtime, &tmp)
+    } else {
+        false
+    };
+
+    if created && fs::rename(&tmp, &thumbnail).is_ok() {
+        Some(thumbnail)
+    } else {
+        let _ = fs::remove_file(&tmp);
+        None
+    }
+}
+
+/// Thumbnails written by us or file managers contain the modification time of the file,
+/// the ones of thumbnailers only have the time they were written.
+fn is_up_to_date(thumbnail: &Path, mtime: u64) -> bool {
+    let Ok(content) = fs::read(thumbnail) else {
+        return false;
+    };
+    match png_text(&content, "Thumb::MTime") {
+        Some(thumb_mtime) => thumb_mtime.parse() == Ok(mtime),
+        None => fs::metadata(thumbnail)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .is_some_and(|written| written.as_secs() >= mtime),
+    }
+}
+
+fn scale_image(path: &Path, uri: &str, mtime: u64, output: &Path) -> bool {
+    let pixbuf = match Pixbuf::from_file_at_scale(path, THUMBNAIL_SIZE, THUMBNAIL_SIZE, true) {
+        Ok(pixbuf) => pixbuf,
+        Err(e) => {
+            log::debug!("cannot scale {}: {e}", path.display());
+            return false;
+        }
+    };
+    pixbuf
+        .savev(
+            output,
+            "png",
+            &[
+                ("tEXt::Thumb::URI", uri),
+                ("tEXt::Thumb::MTime", &mtime.to_string()),
+            ],
+        )
+        .inspect_err(|e| log::debug!("cannot save thumbnail of {}: {e}", path.display()))
+        .is_ok()
+}
+
+fn run_thumbnailer(thumbnailer: &Thumbnailer, path: &Path, uri: &str, output: &Path) -> bool {
+    let mut args = thumbnailer.exec.split_whitespace().map(|arg| {
+        arg.replace("%i", &path.to_string_lossy())
+            .replace("%u", uri)
+            .replace("%o", &output.to_string_lossy())
+            .replace("%s", &THUMBNAIL_SIZE.to_string())
+            .replace("%%", "%")
+    });
+    let Some(program) = args.next() else {
+        return false;
+    };
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+        && output.exists()
+}
+
+static THUMBNAILERS: LazyLock<Vec<Thumbnailer>> = LazyLock::new(load_thumbnailers);
+
+fn load_thumbnailers() -> Vec<Thumbnailer> {
+    let data_dirs =
+        env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_owned());
+    dirs::data_dir()
+        .into_iter()
+        .chain(env::split_paths(&data_dirs))
+        .filter_map(|dir| fs::read_dir(dir.join("thumbnailers")).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "thumbnailer")
+        })
+        .filter_map(|entry| parse_thumbnailer(&fs::read_to_string(entry.path()).ok()?))
+        .filter(|thumbnailer| {
+            thumbnailer
+                .exec
+                .split_whitespace()
+                .next()
+                .is_some_and(|program| which::which(program).is_ok())
+        })
+        .collect()
+}
+
+fn parse_thumbnailer(content: &str) -> Option<Thumbnailer> {
+    let mut exec = None;
+    let mut mime_types = Vec::new();
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("Exec=") {
+            exec = Some(value.trim().to_owned());
+        } else if let Some(value) = line.strip_prefix("MimeType=") {
+            mime_types = value
+                .split(';')
+                .filter(|mime| !mime.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+    }
+    Some(Thumbnailer {
+        exec: exec?,
+        mime_types,
+    })
+}
+
+/// Uri of the file as used for the name of its thumbnail, `None` for relative paths.
+fn file_uri(path: &Path) -> Option<String> {
+    glib::filename_to_uri(path, None)
+        .inspect_err(|e| log::debug!("no uri for {}: {e}", path.display()))
+        .ok()
+        .map(String::from)
+}
+
+/// Value of a `tEXt` chunk of the png, thumbnails store the uri and mtime of the file in them.
+fn png_text(png: &[u8], key: &str) -> Option<String> {
+    let mut pos = 8;
+    while pos + 8 <= png.len() {
+        let length = u32::from_be_bytes(png[pos..pos + 4].try_into().ok()?) as usize;
+        let chunk_type = &png[pos + 4..pos + 8];
+        let data = png.get(pos + 8..pos + 8 + length)?;
+        if chunk_type == b"tEXt"
+            && let Some(separator) = data.iter().position(|b| *b == 0)
+            && &data[..separator] == key.as_bytes()
+        {
+            return Some(String::from_utf8_lossy(&data[separator + 1..]).into_owned());
+        }
+        if chunk_type == b"IDAT" {
+            // text chunks of thumbnails are written before the image data
+            return None;
+        }
+        pos += 12 + length;
+    }
+    None
+}
+
+/// Md5 of the data as hex string, the thumbnail spec names the files by the md5 of their uri.
+fn md5_hex(data: &[u8]) -> Option<String> {
+    glib::compute_checksum_for_bytes(ChecksumType::Md5, &glib::Bytes::from(data)).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_md5_hex() {
+        // spec example, the thumbnail name of file:///home/jens/photos/me.png
+        assert_eq!(
+            file_uri(Path::new("/home/jens/photos/me.png"))
+                .and_then(|uri| md5_hex(uri.as_bytes()))
+                .as_deref(),
+            Some("c6ee772d9e49320e97ec29a7eb5b1697")
+        );
+    }
+
+    #[test]
+    fn test_file_uri_escapes_reserved_characters() {
+        assert_eq!(
+            file_uri(Path::new("/home/user/my file#1.png")).as_deref(),
+            Some("file:///home/user/my%20file%231.png")
+        );
+        assert_eq!(file_uri(Path::new("relative.png")), None);
+    }
+
+    #[test]
+    fn test_png_text() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |kind: &[u8], data: &[u8]| {
+            png.extend_from_slice(&u32::try_from(data.len()).unwrap().to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]);
+        };
+        chunk(b"IHDR", &[0; 13]);
+        chunk(b"tEXt", b"Thumb::MTime\x001700000000");
+        chunk(b"IDAT", &[]);
+        assert_eq!(
+            png_text(&png, "Thumb::MTime").as_deref(),
+            Some("1700000000")
+        );
+        assert_eq!(png_text(&png, "Thumb::URI"), None);
+    }
+
+    #[test]
+    fn test_parse_thumbnailer() {
+        let content = "[Thumbnailer Entry]\nTryExec=evince-thumbnailer\n\
+            Exec=evince-thumbnailer -s %s %u %o\nMimeType=application/pdf;application/x-bzpdf;\n";
+        assert_eq!(
+            parse_thumbnailer(content),
+            Some(Thumbnailer {
+                exec: "evince-thumbnailer -s %s %u %o".to_owned(),
+                mime_types: vec![
+                    "application/pdf".to_owned(),
+                    "application/x-bzpdf".to_owned()
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    num::ParseIntError,
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use notify_rust::Notification;
+use regex::Regex;
+
+use crate::{
+    Error,
+    config::Config,
+    desktop::spawn_fork,
+    gui::{self, ArcProvider, ExpandMode, ItemProvider, MenuItem, ProviderData},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum TimerAction {
+    /// Start a new timer with the given duration and label
+    Start(Duration, String),
+    /// Cancel the running timer
+    Cancel(Timer),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Timer {
+    /// Unix timestamp in seconds when the timer expires
+    expiry: u64,
+    /// Process id of the background process waiting for the timer
+    pid: i32,
+    label: String,
+}
+
+#[derive(Clone)]
+struct TimerProvider {
+    path: PathBuf,
+}
+
+impl TimerProvider {
+    fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl ItemProvider<TimerAction> for TimerProvider {
+    fn get_elements(&mut self, query: Option<&str>) -> ProviderData<TimerAction> {
+        let now = now_secs();
+        let mut items: Vec<_> = load_timers(&self.path)
+            .into_iter()
+            .filter(|timer| timer.expiry > now)
+            .map(|timer| {
+                // acceptable, timers won't expire far enough in the future for this to matter
+                #[allow(clippy::cast_precision_loss)]
+                let score = -(timer.expiry as f64);
+                MenuItem::new(
+                    format!("{} — {}", format_remaining(timer.expiry - now), timer.label),
+                    Some("alarm".to_owned()),
+                    Some(format!("timer {} {}", timer.expiry, timer.pid)),
+                    vec![],
+                    None,
+                    score,
+                    Some(TimerAction::Cancel(timer)),
+                )
+            })
+            .collect();
+
+        if let Some((duration, label)) = query.and_then(parse_timer) {
+            items.push(MenuItem::new(
+                format!("Start {} — {label}", format_remaining(duration.as_secs())),
+                Some("alarm".to_owned()),
+                None,
+                vec![],
+                None,
+                1.0,
+                Some(TimerAction::Start(duration, label)),
+            ));
+        }
+
+        ProviderData::Items(items)
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<TimerAction>) -> ProviderData<TimerAction> {
+        ProviderData::Unchanged
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+}
+
+/// Parses timer queries like `10m tea`, `1h30m`, `90s pizza`.
+/// A plain number is interpreted as minutes.
+fn parse_timer(query: &str) -> Option<(Duration, String)> {
+    static RE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(?:(\d+)h)?(?:(\d+)m)?(?:(\d+)s)?$").unwrap());
+
+    let mut parts = query.trim().splitn(2, char::is_whitespace);
+    let spec = parts.next()?;
+    let label = parts
+        .next()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .unwrap_or("Timer");
+
+    let secs = if let Ok(minutes) = spec.parse::<u64>() {
+        minutes.saturating_mul(60)
+    } else {
+        let caps = RE.captures(spec)?;
+        let part = |idx: usize, factor: u64| {
+            caps.get(idx)
+                .and_then(|m| m.as_str().parse::<u64>().ok())
+                .map_or(0, |v| v.saturating_mul(factor))
+        };
+        part(1, 3600) + part(2, 60) + part(3, 1)
+    };
+
+    if secs == 0 {
+        None
+    } else {
+        Some((Duration::from_secs(secs), label.to_owned()))
+    }
+}
+
+fn format_remaining(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn timer_file_path() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("worf-timers")
+}
+
+/// Loads the timers stored as lines of `expiry<TAB>pid<TAB>label`
+fn load_timers(path: &Path) -> Vec<Timer> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            Some(Timer {
+                expiry: parts.next()?.parse().ok()?,
+                pid: parts.next()?.parse().ok()?,
+                label: parts.next()?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+fn save_timers(path: &Path, timers: &[Timer]) -> Result<(), Error> {
+    let content: String = timers
+        .iter()
+        .map(|t| format!("{}\t{}\t{}\n", t.expiry, t.pid, t.label))
+        .collect();
+    fs::write(path, content).map_err(|e| Error::Io(e.to_string()))
+}
+
+fn remove_timer(path: &Path, timer: &Timer) -> Result<(), Error> {
+    let now = now_secs();
+    let timers: Vec<_> = load_timers(path)
+        .into_iter()
+        .filter(|t| t != timer && t.expiry > now)
+        .collect();
+    save_timers(path, &timers)
+}
+
+/// Argument the helper process waiting for a timer is started with,
+/// followed by the expiry and the label of the timer, see `wait`.
+const TIMER_WAIT_ARG: &str = "--timer-wait";
+
+/// Starts a helper process which waits for the timer to expire
+/// and sends a notification, unless the timer got cancelled meanwhile.
+fn start_timer(
+    path: &Path,
+    duration: Duration,
+    label: String,
+    sound: Option<String>,
+) -> Result<(), Error> {
+    let expiry = now_secs() + duration.as_secs();
+
+    let exe = env::current_exe().map_err(|e| Error::RunFailed(e.to_string()))?;
+    let mut command = Command::new(exe);
+    command
+        .args([TIMER_WAIT_ARG, &expiry.to_string(), &label])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0);
+    if let Some(sound) = sound {
+        command.args(["--timer-sound", &sound]);
+    }
+    let mut helper = command
+        .spawn()
+        .map_err(|e| Error::RunFailed(e.to_string()))?;
+
+    let pid = i32::try_from(helper.id()).map_err(|e| Error::RunFailed(e.to_string()))?;
+    let mut timers = load_timers(path);
+    timers.push(Timer { expiry, pid, label });
+    let saved = save_timers(path, &timers);
+
+    // the helper starts waiting once stdin is closed, so the timer is always stored before
+    drop(helper.stdin.take());
+    // reaps the helper if this process outlives it, i.e. in the daemon
+    thread::spawn(move || helper.wait());
+    saved
+}
+
+/// Runs the helper process of a timer started by the timer mode.
+/// Waits until the timer expires and notifies about it, unless it got cancelled meanwhile.
+/// # Errors
+///
+/// Will return `Err` if the expiry is not a unix timestamp.
+pub fn wait(expiry: &str, label: &str, sound: Option<String>) -> Result<(), Error> {
+    let expiry: u64 = expiry
+        .parse()
+        .map_err(|e: ParseIntError| Error::ParsingError(e.to_string()))?;
+
+    // closed by the timer mode after storing the timer
+    if let Err(e) = io::stdin().read_to_end(&mut Vec::new()) {
+        log::warn!("failed to wait for the timer to be stored {e}");
+    }
+    thread::sleep(Duration::from_secs(expiry.saturating_sub(now_secs())));
+
+    let path = timer_file_path();
+    let pid = i32::try_from(std::process::id()).unwrap_or_default();
+    let timer = Timer {
+        expiry,
+        pid,
+        label: label.to_owned(),
+    };
+    if !load_timers(&path).contains(&timer) {
+        return Ok(());
+    }
+
+    if let Err(e) = Notification::new()
+        .summary("Timer expired")
+        .body(&timer.label)
+        .icon("alarm")
+        .show()
+    {
+        log::error!("failed to show timer notification {e}");
+    }
+    if let Some(sound) = sound
+        && let Err(e) = spawn_fork(&sound, None, &[])
+    {
+        log::error!("failed to play timer sound {e}");
+    }
+    if let Err(e) = remove_timer(&path, &timer) {
+        log::warn!("failed to remove expired timer {e}");
+    }
+    Ok(())
+}
+
+fn cancel_timer(path: &Path, timer: &Timer) -> Result<(), Error> {
+    // SAFETY: only sends a signal to the timer process we started.
+    unsafe {
+        libc::kill(timer.pid, libc::SIGTERM);
+    }
+    remove_timer(path, timer)
+}
+
+/// Shows the timer mode.
+/// Queries like `10m tea` start a timer, selecting a running timer cancels it.
+/// # Errors
+///
+/// Will return `Err` if the timer cannot be started or the timer state cannot be stored.
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let path = timer_file_path();
+    let provider = Arc::new(Mutex::new(TimerProvider::new(path.clone())));
+
+    loop {
+        let selection = gui::show(
+            &config.read().unwrap(),
+            Arc::clone(&provider) as ArcProvider<TimerAction>,
+            None,
+            None,
+            ExpandMode::Verbatim,
+            None,
+        )?;
+
+        match selection.menu.data {
+            Some(TimerAction::Start(duration, label)) => {
+                let sound = config.read().unwrap().timer_sound();
+                start_timer(&path, duration, label, sound)?;
+                break;
+            }
+            Some(TimerAction::Cancel(timer)) => cancel_timer(&path, &timer)?,
+            None => return Err(Error::MissingAction),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timer() {
+        assert_eq!(
+            parse_timer("10m tea"),
+            Some((Duration::from_secs(600), "tea".to_owned()))
+        );
+        assert_eq!(
+            parse_timer("1h30m"),
+            Some((Duration::from_secs(5400), "Timer".to_owned()))
+        );
+        assert_eq!(
+            parse_timer("5 pizza oven"),
+            Some((Duration::from_secs(300), "pizza oven".to_owned()))
+        );
+        assert_eq!(parse_timer("tea"), None);
+        assert_eq!(parse_timer("0m"), None);
+    }
+}
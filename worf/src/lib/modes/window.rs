@@ -0,0 +1,572 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use wayland_client::{
+    Connection, Dispatch, EventQueue, QueueHandle,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+};
+
+use crate::{
+    Error,
+    config::{Config, MatchMethod},
+    desktop::{self, EntryType, IconResolver},
+    gui::{self, ExpandMode, ItemProvider, MenuItem, ProviderData},
+};
+
+use protocol::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    non_snake_case,
+    unused_imports,
+    missing_docs,
+    clippy::all,
+    clippy::pedantic
+)]
+mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!(
+            "protocols/wlr-foreign-toplevel-management-unstable-v1.xml"
+        );
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!(
+        "protocols/wlr-foreign-toplevel-management-unstable-v1.xml"
+    );
+}
+
+/// Value of the activated state in the state array of a toplevel.
+const STATE_ACTIVATED: u32 = 2;
+
+/// Weights of the fields matched by the search, a match of the class
+/// ranks above a similar match of the title.
+const CLASS_WEIGHT: f64 = 3.0;
+const TITLE_WEIGHT: f64 = 2.0;
+const WORKSPACE_WEIGHT: f64 = 1.0;
+
+/// An open window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Window {
+    title: String,
+    app_id: String,
+    workspace: Option<String>,
+    active: bool,
+    /// Number of the toplevel or address of the Hyprland client
+    id: String,
+}
+
+#[derive(Default)]
+struct ToplevelState {
+    toplevels: Vec<(ZwlrForeignToplevelHandleV1, Window)>,
+    /// Id of the next announced toplevel
+    next_id: usize,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for ToplevelState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+wayland_client::delegate_noop!(ToplevelState: ignore WlSeat);
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            let window = Window {
+                id: state.next_id.to_string(),
+                ..Window::default()
+            };
+            state.next_id += 1;
+            state.toplevels.push((toplevel, window));
+        }
+    }
+
+    wayland_client::event_created_child!(ToplevelState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(index) = state.toplevels.iter().position(|(h, _)| h == handle) else {
+            return;
+        };
+        let window = &mut state.toplevels[index].1;
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => window.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => window.app_id = app_id,
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: states } => {
+                window.active = states
+                    .chunks_exact(4)
+                    .any(|s| u32::from_ne_bytes([s[0], s[1], s[2], s[3]]) == STATE_ACTIVATED);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels[index].0.destroy();
+                state.toplevels.remove(index);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Source of the open windows, the foreign toplevel protocol if the compositor
+/// supports it, Hyprland's IPC otherwise.
+enum WindowBackend {
+    Toplevel {
+        queue: EventQueue<ToplevelState>,
+        state: ToplevelState,
+        seat: WlSeat,
+    },
+    Hyprland,
+}
+
+impl WindowBackend {
+    fn connect() -> Result<Self, Error> {
+        match Self::connect_toplevel() {
+            Ok(backend) => Ok(backend),
+            Err(e) if desktop::hyprland_socket().is_some() => {
+                log::debug!("foreign toplevel protocol not usable, using hyprland: {e}");
+                Ok(WindowBackend::Hyprland)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn connect_toplevel() -> Result<Self, Error> {
+        let connection =
+            Connection::connect_to_env().map_err(|e| Error::Graphics(e.to_string()))?;
+        let (globals, mut queue) = registry_queue_init::<ToplevelState>(&connection)
+            .map_err(|e| Error::Graphics(e.to_string()))?;
+        let qh = queue.handle();
+        let _manager: ZwlrForeignToplevelManagerV1 = globals
+            .bind(&qh, 1..=3, ())
+            .map_err(|e| Error::Graphics(format!("no foreign toplevel manager: {e}")))?;
+        let seat: WlSeat = globals
+            .bind(&qh, 1..=1, ())
+            .map_err(|e| Error::Graphics(format!("no seat: {e}")))?;
+
+        let mut state = ToplevelState::default();
+        // the first roundtrip announces the toplevels, the second their details
+        for _ in 0..2 {
+            queue
+                .roundtrip(&mut state)
+                .map_err(|e| Error::Graphics(e.to_string()))?;
+        }
+        Ok(WindowBackend::Toplevel { queue, state, seat })
+    }
+
+    fn windows(&self) -> Result<Vec<Window>, Error> {
+        match self {
+            WindowBackend::Toplevel { state, .. } => Ok(state
+                .toplevels
+                .iter()
+                .map(|(_, window)| window.clone())
+                .collect()),
+            WindowBackend::Hyprland => hyprland_windows(),
+        }
+    }
+
+    /// The open windows, including the changes since they were last read.
+    fn current_windows(&mut self) -> Result<Vec<Window>, Error> {
+        if let WindowBackend::Toplevel { queue, state, .. } = self {
+            queue
+                .roundtrip(state)
+                .map_err(|e| Error::Graphics(e.to_string()))?;
+        }
+        self.windows()
+    }
+
+    fn focus(&mut self, window: &Window) -> Result<(), Error> {
+        match self {
+            WindowBackend::Toplevel { queue, state, seat } => {
+                let (handle, _) = state
+                    .toplevels
+                    .iter()
+                    .find(|(_, w)| w.id == window.id)
+                    .ok_or(Error::InvalidSelection)?;
+                handle.activate(seat);
+                queue
+                    .roundtrip(state)
+                    .map(|_| ())
+                    .map_err(|e| Error::Graphics(e.to_string()))
+            }
+            WindowBackend::Hyprland => {
+                let reply = desktop::hyprland_request(&format!(
+                    "dispatch focuswindow address:{}",
+                    window.id
+                ))?;
+                if reply.trim() == "ok" {
+                    Ok(())
+                } else {
+                    Err(Error::RunFailed(reply))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct HyprlandWorkspace {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct HyprlandClient {
+    address: String,
+    title: String,
+    class: String,
+    workspace: HyprlandWorkspace,
+    /// 0 for the focused window, counting up the longer a window was not focused
+    #[serde(rename = "focusHistoryID")]
+    focus_history_id: i64,
+    mapped: bool,
+}
+
+fn hyprland_windows() -> Result<Vec<Window>, Error> {
+    let reply = desktop::hyprland_request("j/clients")?;
+    let mut clients: Vec<HyprlandClient> =
+        serde_json::from_str(&reply).map_err(|e| Error::ParsingError(e.to_string()))?;
+    clients.sort_by_key(|client| client.focus_history_id);
+    Ok(clients
+        .into_iter()
+        .filter(|client| client.mapped)
+        .map(|client| Window {
+            title: client.title,
+            app_id: client.class,
+            workspace: Some(client.workspace.name),
+            active: client.focus_history_id == 0,
+            id: client.address,
+        })
+        .collect())
+}
+
+/// Icon of the desktop file matching the app id, the app id itself if there is none.
+fn window_icon(app_id: &str, desktop_files: &[(PathBuf, desktop::DesktopFile)]) -> String {
+    let app_id_lower = app_id.to_lowercase();
+    desktop_files
+        .iter()
+        .find_map(|(path, file)| {
+            let stem_matches = path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().to_lowercase() == app_id_lower);
+            let class_matches = match &file.entry.entry_type {
+                EntryType::Application(app) => app
+                    .startup_wm_class
+                    .as_ref()
+                    .is_some_and(|class| class.to_lowercase() == app_id_lower),
+                _ => false,
+            };
+            (stem_matches || class_matches)
+                .then(|| file.entry.icon.as_ref().map(|icon| icon.content.clone()))
+                .flatten()
+        })
+        .unwrap_or(app_id_lower)
+}
+
+/// Time until the listed windows are read again while the menu is shown,
+/// so windows opened or closed meanwhile show up or disappear.
+const WINDOWS_VALID_FOR: Duration = Duration::from_secs(2);
+
+struct WindowProvider {
+    /// Shared with `show`, which focuses the selected window through it
+    backend: Arc<Mutex<WindowBackend>>,
+    desktop_files: Vec<(PathBuf, desktop::DesktopFile)>,
+    icons: IconResolver,
+    items: Vec<MenuItem<Window>>,
+    loaded_at: Instant,
+}
+
+impl WindowProvider {
+    fn new(backend: Arc<Mutex<WindowBackend>>, icons: IconResolver) -> Result<Self, Error> {
+        let windows = backend.lock().unwrap().windows()?;
+        let desktop_files = desktop::find_desktop_files_with_path();
+        let items = window_items(windows, &desktop_files, &icons);
+        Ok(Self {
+            backend,
+            desktop_files,
+            icons,
+            items,
+            loaded_at: Instant::now(),
+        })
+    }
+
+    /// Reads the open windows again, once the last read ones are outdated.
+    fn reload_if_expired(&mut self) {
+        if self.loaded_at.elapsed() < WINDOWS_VALID_FOR {
+            return;
+        }
+        self.loaded_at = Instant::now();
+        match self.backend.lock().unwrap().current_windows() {
+            Ok(windows) => self.items = window_items(windows, &self.desktop_files, &self.icons),
+            Err(e) => log::warn!("cannot read the open windows {e}"),
+        }
+    }
+}
+
+fn window_items(
+    windows: Vec<Window>,
+    desktop_files: &[(PathBuf, desktop::DesktopFile)],
+    icons: &IconResolver,
+) -> Vec<MenuItem<Window>> {
+    let count = windows.len();
+    let mut items: Vec<_> = windows
+        .into_iter()
+        .enumerate()
+        .map(|(i, window)| {
+            // keep the order of the backend, the active window goes last
+            // as switching to it would change nothing
+            #[allow(clippy::cast_precision_loss)] // there are only a few windows
+            let score = if window.active {
+                -1.0
+            } else {
+                (count - i) as f64
+            };
+            let label = if window.title.is_empty() {
+                window.app_id.clone()
+            } else {
+                window.title.clone()
+            };
+            let mut item = MenuItem::new(
+                label,
+                Some({
+                    let icon = window_icon(&window.app_id, desktop_files);
+                    icons.resolve(&icon).unwrap_or(icon)
+                }),
+                None,
+                vec![],
+                None,
+                score,
+                None,
+            )
+            .with_id(window.id.clone())
+            .with_search_terms(vec![window.app_id.clone()]);
+            if let Some(workspace) = &window.workspace {
+                item = item.with_badge(workspace.clone());
+            }
+            item.data = Some(window);
+            item
+        })
+        .collect();
+    items.sort_by(|l, r| r.initial_sort_score.total_cmp(&l.initial_sort_score));
+    if let Err(e) = icons.save() {
+        log::warn!("cannot save icon cache {e}");
+    }
+    items
+}
+
+impl ItemProvider<Window> for WindowProvider {
+    /// Matches class, title and workspace separately, the gui shows all returned items.
+    fn get_elements(&mut self, query: Option<&str>) -> ProviderData<Window> {
+        self.reload_if_expired();
+        let query = normalize(query.unwrap_or_default());
+        if query.is_empty() {
+            return ProviderData::Items(self.items.clone());
+        }
+
+        // the match ranks above the order of the backend, which breaks ties
+        #[allow(clippy::cast_precision_loss)] // there are only a few windows
+        let scale = 10.0 * (self.items.len() + 2) as f64;
+        let mut items: Vec<_> = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                let score = window_score(item.data.as_ref()?, &query);
+                (score > 0.0).then(|| {
+                    let mut item = item.clone();
+                    item.initial_sort_score += score * scale;
+                    item
+                })
+            })
+            .collect();
+        items.sort_by(|l, r| r.initial_sort_score.total_cmp(&l.initial_sort_score));
+        ProviderData::Items(items)
+    }
+
+    fn get_sub_elements(&mut self, _: &MenuItem<Window>) -> ProviderData<Window> {
+        ProviderData::Unchanged
+    }
+
+    fn expires_in(&self) -> Option<Duration> {
+        Some(WINDOWS_VALID_FOR.saturating_sub(self.loaded_at.elapsed()))
+    }
+}
+
+/// Lowercase text without the decorative characters Hyprland setups often put
+/// into titles and workspace names, i.e. nerd font icons, bullets and emoji.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c.is_ascii_punctuation() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// How well a normalized field matches the normalized query, 0 if it does not.
+/// Whole matches rank above prefixes, word prefixes, substrings and
+/// finally the characters of the query appearing in order.
+fn field_score(field: &str, query: &str) -> f64 {
+    if field.is_empty() {
+        0.0
+    } else if field == query {
+        1.0
+    } else if field.starts_with(query) {
+        0.8
+    } else if field
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        0.6
+    } else if field.contains(query) {
+        0.4
+    } else if is_subsequence(query, field) {
+        0.2
+    } else {
+        0.0
+    }
+}
+
+fn is_subsequence(query: &str, field: &str) -> bool {
+    let mut chars = field.chars();
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|q| chars.any(|c| c == q))
+}
+
+/// Best weighted score of the fields of the window for the normalized query.
+fn window_score(window: &Window, query: &str) -> f64 {
+    [
+        (normalize(&window.app_id), CLASS_WEIGHT),
+        (normalize(&window.title), TITLE_WEIGHT),
+        (
+            normalize(window.workspace.as_deref().unwrap_or_default()),
+            WORKSPACE_WEIGHT,
+        ),
+    ]
+    .iter()
+    .map(|(field, weight)| field_score(field, query) * weight)
+    .fold(0.0, f64::max)
+}
+
+/// Shows the open windows and focuses the selected one.
+/// Uses the foreign toplevel protocol and falls back to Hyprland's IPC.
+/// # Errors
+///
+/// Will return `Err`
+/// * if neither the protocol nor Hyprland are available
+/// * if the window cannot be focused
+/// # Panics
+/// When failing to unwrap the arc lock
+pub fn show(config: &Arc<RwLock<Config>>) -> Result<(), Error> {
+    let backend = Arc::new(Mutex::new(WindowBackend::connect()?));
+    let icons = IconResolver::load(&config.read().unwrap());
+    let provider = WindowProvider::new(Arc::clone(&backend), icons)?;
+    // the provider matches the windows itself, see `window_score`
+    let mut window_config = config.read().unwrap().clone();
+    window_config.set_match_method(MatchMethod::None);
+    let selection = gui::show(
+        &window_config,
+        Arc::new(Mutex::new(provider)),
+        None,
+        None,
+        ExpandMode::Verbatim,
+        None,
+    )?;
+
+    let window = selection.menu.data.ok_or(Error::InvalidSelection)?;
+    let mut backend = backend.lock().unwrap();
+    // the window might have been closed while the menu was shown
+    backend.current_windows()?;
+    backend.focus(&window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_expire_after_validity() {
+        let mut provider = WindowProvider {
+            backend: Arc::new(Mutex::new(WindowBackend::Hyprland)),
+            desktop_files: vec![],
+            icons: IconResolver::load(&Config::default()),
+            items: vec![],
+            loaded_at: Instant::now(),
+        };
+        assert!(provider.expires_in().unwrap() > Duration::from_secs(1));
+
+        provider.loaded_at = Instant::now() - WINDOWS_VALID_FOR;
+        assert_eq!(provider.expires_in(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_normalize_strips_decorations() {
+        assert_eq!(normalize("\u{f269}  Firefox • GitHub"), "firefox github");
+        assert_eq!(normalize("🎵 Spotify — Song"), "spotify song");
+        assert_eq!(normalize("~/src/worf: vim"), "~/src/worf: vim");
+    }
+
+    #[test]
+    fn test_class_match_ranks_above_title_substring() {
+        let browser = Window {
+            title: "Mozilla Firefox".to_owned(),
+            app_id: "firefox".to_owned(),
+            ..Window::default()
+        };
+        let terminal = Window {
+            title: "notes about firefox".to_owned(),
+            app_id: "foot".to_owned(),
+            ..Window::default()
+        };
+        assert!(window_score(&browser, "fire") > window_score(&terminal, "fire"));
+        assert!(window_score(&browser, "ffx") > 0.0);
+        assert!(window_score(&terminal, "xyz").abs() < f64::EPSILON);
+    }
+}
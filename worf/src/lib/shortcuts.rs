@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use wayland_client::{
+    Connection, Dispatch, QueueHandle,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::wl_registry::{self, WlRegistry},
+};
+
+use crate::{Error, daemon, gui};
+
+use protocol::{
+    hyprland_global_shortcut_v1::{self, HyprlandGlobalShortcutV1},
+    hyprland_global_shortcuts_manager_v1::HyprlandGlobalShortcutsManagerV1,
+};
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    non_snake_case,
+    unused_imports,
+    missing_docs,
+    clippy::all,
+    clippy::pedantic
+)]
+mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/hyprland-global-shortcuts-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/hyprland-global-shortcuts-v1.xml");
+}
+
+/// App id the shortcuts are registered with, bound in hyprland as `global, worf:<mode>`.
+const APP_ID: &str = "worf";
+
+struct ShortcutState {
+    /// Set while a menu opened by a shortcut is shown.
+    shown: Arc<AtomicBool>,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for ShortcutState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+wayland_client::delegate_noop!(ShortcutState: HyprlandGlobalShortcutsManagerV1);
+
+impl Dispatch<HyprlandGlobalShortcutV1, String> for ShortcutState {
+    fn event(
+        state: &mut Self,
+        _: &HyprlandGlobalShortcutV1,
+        event: hyprland_global_shortcut_v1::Event,
+        mode: &String,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let hyprland_global_shortcut_v1::Event::Pressed { .. } = event {
+            toggle(mode, &state.shown);
+        }
+    }
+}
+
+/// Shows the menu of the mode or closes it, if a shortcut opened the shown one.
+fn toggle(mode: &str, shown: &Arc<AtomicBool>) {
+    if shown.swap(true, Ordering::SeqCst) {
+        gui::close_shown();
+        return;
+    }
+
+    let shown = Arc::clone(shown);
+    let args = ["worf", "--show", mode].map(str::to_owned);
+    thread::spawn(move || {
+        if daemon::forward(&args).is_none() {
+            log::error!("failed to show {} from shortcut", args[2]);
+        }
+        shown.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Registers a global shortcut per mode with the compositor, a press shows the menu
+/// of the mode through the daemon, another press closes it again.
+/// `shortcuts` maps the modes to the description of their trigger, the keys are bound
+/// by the compositor config, i.e. `bind = SUPER, space, global, worf:drun`.
+/// The shortcuts stay registered until the process exits.
+/// # Errors
+/// `Error::Graphics` if there is no wayland connection, the compositor does not support
+/// hyprland global shortcuts or another process registered them already.
+pub fn register(shortcuts: &HashMap<String, String>) -> Result<(), Error> {
+    let conn = Connection::connect_to_env().map_err(|e| Error::Graphics(e.to_string()))?;
+    let (globals, mut queue) =
+        registry_queue_init::<ShortcutState>(&conn).map_err(|e| Error::Graphics(e.to_string()))?;
+    let qh = queue.handle();
+    let manager: HyprlandGlobalShortcutsManagerV1 = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|e| Error::Graphics(format!("global shortcuts not supported: {e}")))?;
+
+    let registered: Vec<HyprlandGlobalShortcutV1> = shortcuts
+        .iter()
+        .map(|(mode, trigger)| {
+            manager.register_shortcut(
+                mode.clone(),
+                APP_ID.to_owned(),
+                format!("Show worf {mode}"),
+                trigger.clone(),
+                &qh,
+                mode.clone(),
+            )
+        })
+        .collect();
+
+    let mut state = ShortcutState {
+        shown: Arc::new(AtomicBool::new(false)),
+    };
+    queue
+        .roundtrip(&mut state)
+        .map_err(|e| Error::Graphics(format!("cannot register shortcuts: {e}")))?;
+
+    thread::spawn(move || {
+        // the shortcuts are unregistered once dropped
+        let _registered = (manager, registered);
+        loop {
+            if let Err(e) = queue.blocking_dispatch(&mut state) {
+                log::error!("global shortcuts stopped: {e}");
+                return;
+            }
+        }
+    });
+    Ok(())
+}
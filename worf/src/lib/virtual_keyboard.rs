@@ -0,0 +1,260 @@
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    io::Write as _,
+    os::fd::AsFd,
+    thread,
+    time::{Duration, Instant},
+};
+
+use wayland_client::{
+    Connection, Dispatch, EventQueue, QueueHandle,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{
+        wl_keyboard::{KeyState, KeymapFormat},
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+};
+
+use crate::Error;
+
+use protocol::{
+    zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+    zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+};
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    non_snake_case,
+    unused_imports,
+    missing_docs,
+    clippy::all,
+    clippy::pedantic
+)]
+mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/virtual-keyboard-unstable-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/virtual-keyboard-unstable-v1.xml");
+}
+
+/// Highest number of distinct characters a single keymap holds.
+/// Longer texts are typed in several chunks with a new keymap each.
+const MAX_KEYS: usize = 200;
+
+/// Offset between the evdev key codes sent to the compositor and the xkb keycodes.
+const XKB_KEYCODE_OFFSET: u32 = 8;
+
+/// Delay between two key strokes, some applications drop keys if they arrive too fast.
+const KEY_DELAY: Duration = Duration::from_millis(2);
+
+struct TypingState;
+
+impl Dispatch<WlRegistry, GlobalListContents> for TypingState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+wayland_client::delegate_noop!(TypingState: ignore WlSeat);
+wayland_client::delegate_noop!(TypingState: ZwpVirtualKeyboardManagerV1);
+wayland_client::delegate_noop!(TypingState: ZwpVirtualKeyboardV1);
+
+/// Characters that are typed with one keymap.
+#[derive(Debug, Default, PartialEq)]
+struct KeymapChunk {
+    /// Distinct characters, the index is the key code in the keymap.
+    keys: Vec<char>,
+    /// Indices into `keys` in the order they have to be pressed.
+    presses: Vec<usize>,
+}
+
+/// Type the given text via the `zwp_virtual_keyboard_v1` protocol.
+/// # Errors
+/// * Not running on wayland or the connection failed
+/// * The compositor does not support or does not allow virtual keyboards
+/// * The keymap could not be written
+pub(crate) fn type_text(text: &str) -> Result<(), Error> {
+    let conn = Connection::connect_to_env().map_err(|e| Error::Graphics(e.to_string()))?;
+    let (globals, mut queue) =
+        registry_queue_init::<TypingState>(&conn).map_err(|e| Error::Graphics(e.to_string()))?;
+    let qh = queue.handle();
+
+    let seat: WlSeat = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|e| Error::Graphics(format!("no seat available: {e}")))?;
+    let manager: ZwpVirtualKeyboardManagerV1 = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|e| Error::Graphics(format!("virtual keyboard not supported: {e}")))?;
+    let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+
+    let start = Instant::now();
+    let result = keymap_chunks(text)
+        .iter()
+        .try_for_each(|chunk| type_chunk(&keyboard, &mut queue, chunk, start));
+
+    keyboard.destroy();
+    roundtrip(&mut queue)?;
+    result
+}
+
+fn type_chunk(
+    keyboard: &ZwpVirtualKeyboardV1,
+    queue: &mut EventQueue<TypingState>,
+    chunk: &KeymapChunk,
+    start: Instant,
+) -> Result<(), Error> {
+    let keymap = build_keymap(&chunk.keys);
+    let keymap_file = keymap_file(&keymap)?;
+    let size = u32::try_from(keymap.len() + 1).map_err(|e| Error::Io(e.to_string()))?;
+    keyboard.keymap(KeymapFormat::XkbV1.into(), keymap_file.as_fd(), size);
+    roundtrip(queue)?;
+
+    for &index in &chunk.presses {
+        let key = u32::try_from(index).map_err(|e| Error::Io(e.to_string()))? + 1;
+        for state in [KeyState::Pressed, KeyState::Released] {
+            let time = u32::try_from(start.elapsed().as_millis()).unwrap_or(u32::MAX);
+            keyboard.key(time, key, state.into());
+        }
+        roundtrip(queue)?;
+        thread::sleep(KEY_DELAY);
+    }
+
+    Ok(())
+}
+
+fn roundtrip(queue: &mut EventQueue<TypingState>) -> Result<(), Error> {
+    queue
+        .roundtrip(&mut TypingState)
+        .map(|_| ())
+        .map_err(|e| Error::Graphics(e.to_string()))
+}
+
+/// Writes the keymap into a file which is unlinked right away,
+/// the compositor only needs the file descriptor.
+fn keymap_file(keymap: &str) -> Result<fs::File, Error> {
+    let dir = dirs::runtime_dir().unwrap_or_else(env::temp_dir);
+    let path = dir.join(format!("worf-keymap-{}", std::process::id()));
+
+    let mut file = fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .map_err(|e| Error::Io(e.to_string()))?;
+    let result = file
+        .write_all(keymap.as_bytes())
+        .and_then(|()| file.write_all(&[0]))
+        .and_then(|()| file.flush());
+    fs::remove_file(&path).map_err(|e| Error::Io(e.to_string()))?;
+    result.map_err(|e| Error::Io(e.to_string()))?;
+    Ok(file)
+}
+
+fn keymap_chunks(text: &str) -> Vec<KeymapChunk> {
+    let mut chunks = Vec::new();
+    let mut current = KeymapChunk::default();
+
+    for c in text.chars() {
+        let index = if let Some(index) = current.keys.iter().position(|k| *k == c) {
+            index
+        } else {
+            if current.keys.len() == MAX_KEYS {
+                chunks.push(std::mem::take(&mut current));
+            }
+            current.keys.push(c);
+            current.keys.len() - 1
+        };
+        current.presses.push(index);
+    }
+
+    if !current.presses.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn keysym_name(c: char) -> String {
+    match c {
+        '\n' => "Return".to_owned(),
+        '\t' => "Tab".to_owned(),
+        ' ' => "space".to_owned(),
+        _ => format!("U{:04X}", u32::from(c)),
+    }
+}
+
+/// Builds a keymap which maps every key to exactly one of the given characters.
+#[allow(clippy::cast_possible_truncation)] // at most `MAX_KEYS` keys
+fn build_keymap(keys: &[char]) -> String {
+    let mut keycodes = String::new();
+    let mut symbols = String::new();
+    for (i, c) in keys.iter().enumerate() {
+        let code = i as u32 + XKB_KEYCODE_OFFSET + 1;
+        let _ = writeln!(keycodes, "    <K{i}> = {code};");
+        let _ = writeln!(symbols, "    key <K{i}> {{ [ {} ] }};", keysym_name(*c));
+    }
+
+    format!(
+        "xkb_keymap {{\n\
+         xkb_keycodes \"worf\" {{\n    minimum = {XKB_KEYCODE_OFFSET};\n    maximum = {};\n{keycodes}}};\n\
+         xkb_types \"worf\" {{ include \"complete\" }};\n\
+         xkb_compatibility \"worf\" {{ include \"complete\" }};\n\
+         xkb_symbols \"worf\" {{\n{symbols}}};\n\
+         }};\n",
+        keys.len() as u32 + XKB_KEYCODE_OFFSET + 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_chunks_reuse_keys() {
+        let chunks = keymap_chunks("abba\n");
+        assert_eq!(
+            chunks,
+            vec![KeymapChunk {
+                keys: vec!['a', 'b', '\n'],
+                presses: vec![0, 1, 1, 0, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keymap_chunks_split_large_texts() {
+        let text: String = (0..=MAX_KEYS as u32)
+            .filter_map(|i| char::from_u32(0x4e00 + i))
+            .collect();
+        let chunks = keymap_chunks(&text);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].keys.len(), MAX_KEYS);
+        assert_eq!(chunks[1].keys.len(), 1);
+    }
+
+    #[test]
+    fn test_build_keymap_uses_unicode_keysyms() {
+        let keymap = build_keymap(&['€', '\n']);
+        assert!(keymap.contains("<K0> = 9;"));
+        assert!(keymap.contains("key <K0> { [ U20AC ] };"));
+        assert!(keymap.contains("key <K1> { [ Return ] };"));
+        assert!(keymap.contains("maximum = 11;"));
+    }
+}
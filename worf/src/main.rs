@@ -1,12 +1,18 @@
 use std::{
     env,
     fmt::Display,
+    fs,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, RwLock},
 };
 
 use clap::Parser;
-use worf::{Error, config, desktop::fork_if_configured, modes};
+use worf::{
+    Error, config, daemon,
+    desktop::{self, fork_if_configured},
+    dialogs, gui, modes, shortcuts,
+};
 
 #[derive(Clone, Debug)]
 pub enum Mode {
@@ -36,16 +42,57 @@ pub enum Mode {
 
     /// Open search engine.
     WebSearch,
+
+    /// Quick capture notes into a file
+    Notes,
+
+    /// Countdown timers with a notification on expiry
+    Timer,
+
+    /// Choose the application to open `--open-uri` with, i.e. as handler for `xdg-open`
+    Open,
+
+    /// Shows TOTP codes of the accounts in `--otp-file`
+    Otp,
+
+    /// Restores the mode used last, defaults to drun
+    Last,
+
+    /// Lists worf's own modes and settings
+    Palette,
+
+    /// Asks the prompt as yes/no question, exits with `no_selection_exit_code` on no
+    Confirm,
+
+    /// Lists the open windows and focuses the selected one
+    Window,
+
+    /// Searches the history of `--clipboard-backend` and copies the selected entry again
+    Clipboard,
 }
 
-#[derive(Debug, Parser)]
+#[derive(Clone, Debug, Parser)]
 #[clap(
     about = "Worf is a wofi like launcher, written in rust, it aims to be a drop-in replacement"
 )]
 struct MainConfig {
     /// Defines the mode worf is running in
-    #[clap(long = "show", alias = "mode")]
-    show: Mode,
+    #[clap(
+        long = "show",
+        alias = "mode",
+        required_unless_present_any = ["daemon", "stats", "check_config", "timer_wait"]
+    )]
+    show: Option<Mode>,
+
+    /// Waits for a timer started by the timer mode, see `modes::timer::wait`
+    #[clap(
+        long = "timer-wait",
+        hide = true,
+        num_args = 2,
+        allow_hyphen_values = true,
+        value_names = ["EXPIRY", "LABEL"]
+    )]
+    timer_wait: Option<Vec<String>>,
 
     #[command(flatten)]
     worf: config::Config,
@@ -63,6 +110,15 @@ impl Display for Mode {
             Mode::Ssh => write!(f, "ssh"),
             Mode::Emoji => write!(f, "emoji"),
             Mode::WebSearch => write!(f, "websearch"),
+            Mode::Notes => write!(f, "notes"),
+            Mode::Timer => write!(f, "timer"),
+            Mode::Open => write!(f, "open"),
+            Mode::Otp => write!(f, "otp"),
+            Mode::Last => write!(f, "last"),
+            Mode::Palette => write!(f, "palette"),
+            Mode::Confirm => write!(f, "confirm"),
+            Mode::Window => write!(f, "window"),
+            Mode::Clipboard => write!(f, "clipboard"),
         }
     }
 }
@@ -81,6 +137,15 @@ impl FromStr for Mode {
             "emoji" => Ok(Mode::Emoji),
             "websearch" => Ok(Mode::WebSearch),
             "auto" => Ok(Mode::Auto),
+            "notes" => Ok(Mode::Notes),
+            "timer" => Ok(Mode::Timer),
+            "open" => Ok(Mode::Open),
+            "otp" => Ok(Mode::Otp),
+            "last" => Ok(Mode::Last),
+            "palette" => Ok(Mode::Palette),
+            "confirm" => Ok(Mode::Confirm),
+            "window" => Ok(Mode::Window),
+            "clipboard" => Ok(Mode::Clipboard),
             _ => Err(Error::InvalidArgument(
                 format!("{s} is not a valid argument, see help for details").to_owned(),
             )),
@@ -88,16 +153,228 @@ impl FromStr for Mode {
     }
 }
 
+/// The last used mode is stored in the state dir, or the cache dir if there is none.
+fn last_mode_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("worf-last-mode"))
+}
+
+fn load_last_mode() -> Mode {
+    last_mode_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|mode| Mode::from_str(mode.trim()).ok())
+        .filter(|mode| !matches!(mode, Mode::Last))
+        .unwrap_or(Mode::Drun)
+}
+
+fn save_last_mode(mode: &Mode) {
+    // dmenu, open and confirm are used by scripts and cannot be restored without their input
+    if matches!(mode, Mode::Dmenu | Mode::Open | Mode::Confirm | Mode::Last) {
+        return;
+    }
+
+    let Some(path) = last_mode_path() else {
+        log::warn!("no state dir, cannot store last mode");
+        return;
+    };
+    if let Err(e) = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(&path, mode.to_string()))
+    {
+        log::warn!("failed to store last mode in {}: {e}", path.display());
+    }
+}
+
+/// Prints problems of the loaded config, returns the exit code.
+fn check_config(loaded: &Result<config::Config, Error>) -> i32 {
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("failed to load config: {e}");
+            return config::EXIT_CODE_ERROR;
+        }
+    };
+
+    let mut conflicts: Vec<String> = gui::key_conflicts(config, None)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    // the keys some modes add on top, i.e. the `[[custom_key]]` entries of dmenu
+    let mode_keys = [
+        ("drun", Some(modes::drun::custom_keys(config))),
+        ("dmenu", modes::dmenu::custom_keys(config)),
+        ("clipboard", Some(modes::clipboard::custom_keys())),
+        #[cfg(feature = "mode-math")]
+        ("math", Some(modes::math::custom_keys())),
+    ];
+    for (mode, custom_keys) in mode_keys {
+        for conflict in gui::key_conflicts(config, custom_keys.as_ref()) {
+            let conflict = conflict.to_string();
+            // conflicts of the built-in keys are reported once
+            if !conflicts.contains(&conflict) {
+                conflicts.push(format!("{conflict} in {mode} mode"));
+            }
+        }
+    }
+
+    for conflict in &conflicts {
+        eprintln!("key binding conflict: {conflict}");
+    }
+    if conflicts.is_empty() {
+        println!("config ok");
+        0
+    } else {
+        config::EXIT_CODE_ERROR
+    }
+}
+
+/// Prints statistics of worf, returns the exit code.
+fn print_stats() -> i32 {
+    match desktop::last_launch_log() {
+        Some(path) => println!("last launch log: {}", path.display()),
+        None => println!("last launch log: none"),
+    }
+    0
+}
+
+/// Modes whose menu can be shown by a running daemon.
+/// Modes printing to stdout or reading stdin have to run in the calling process
+/// and run mode replaces the process with the selected program.
+fn served_by_daemon(mode: &Mode) -> bool {
+    matches!(
+        mode,
+        Mode::Drun | Mode::Auto | Mode::Ssh | Mode::File | Mode::WebSearch
+    )
+}
+
+/// Whether the options write to stdout or a file descriptor of the calling process,
+/// which the daemon cannot do.
+fn writes_to_caller(args: &config::Config) -> bool {
+    let config = config::load_worf_config(Some(args)).unwrap_or_else(|_| args.clone());
+    config.dry_run()
+        || config.print_window_info()
+        || config.debug_style()
+        || config.output_fd().is_some()
+}
+
+fn parse_args(args: Vec<String>) -> Result<MainConfig, clap::Error> {
+    let invoked_as_dmenu = args
+        .first()
+        .and_then(|cmd| Path::new(cmd).file_name())
+        .is_some_and(|name| name == "dmenu");
+    if invoked_as_dmenu {
+        MainConfig::try_parse_from(modes::dmenu::compat_args(&args))
+    } else {
+        MainConfig::try_parse_from(args)
+    }
+}
+
 fn main() {
     env_logger::Builder::new()
         .parse_filters(&env::var("RUST_LOG").unwrap_or_else(|_| "error".to_owned()))
         .format_timestamp_micros()
         .init();
 
-    let mut config = MainConfig::parse();
-    config.worf = if let Ok(config) =
-        config::load_worf_config(Some(&config.worf)).map_err(|e| e.to_string())
+    let args: Vec<String> = env::args().collect();
+    let config = parse_args(args.clone()).unwrap_or_else(|e| e.exit());
+
+    if let Some([expiry, label]) = config.timer_wait.as_deref() {
+        if let Err(e) = modes::timer::wait(expiry, label, config.worf.timer_sound()) {
+            log::error!("timer failed: {e}");
+            std::process::exit(config::EXIT_CODE_ERROR);
+        }
+        return;
+    }
+
+    if config.worf.daemon() {
+        desktop::keep_desktop_files_in_memory();
+        gui::watch_config_reload();
+        let mut shortcuts = config::load_worf_config(Some(&config.worf))
+            .unwrap_or_else(|_| config.worf.clone())
+            .daemon_shortcuts();
+        shortcuts.retain(|mode, _| {
+            let known = Mode::from_str(mode).is_ok();
+            if !known {
+                log::error!("daemon_shortcuts: {mode} is not a mode");
+            }
+            known
+        });
+        if !shortcuts.is_empty()
+            && let Err(e) = shortcuts::register(&shortcuts)
+        {
+            log::error!("failed to register shortcuts: {e}");
+        }
+        let served = daemon::serve(|request| match parse_args(request.args.clone()) {
+            Ok(config) => {
+                request.apply_environment();
+                run(config, true)
+            }
+            Err(e) => {
+                log::error!("invalid arguments: {e}");
+                config::EXIT_CODE_ERROR
+            }
+        });
+        if let Err(e) = served {
+            log::error!("daemon failed: {e}");
+            std::process::exit(config::EXIT_CODE_ERROR);
+        }
+        return;
+    }
+
+    let mode = match &config.show {
+        Some(Mode::Last) => Some(load_last_mode()),
+        mode => mode.clone(),
+    };
+    if mode.as_ref().is_some_and(served_by_daemon)
+        && !config.worf.version()
+        && !config.worf.check_config()
+        && !config.worf.stats()
+        && !writes_to_caller(&config.worf)
+        && let Some(code) = daemon::forward(&args)
     {
+        std::process::exit(code);
+    }
+
+    std::process::exit(run(config, false));
+}
+
+/// Result of showing a single mode
+enum Shown {
+    /// Worf is done, with the given exit code
+    Exit(i32),
+    /// `key_switch_mode` was pressed, the arguments show the next mode
+    SwitchMode(Box<MainConfig>),
+}
+
+/// Shows the menu of the configured mode, and the following ones of `switch_modes`
+/// while `key_switch_mode` is pressed. Returns the exit code.
+/// Within the daemon the process is never forked.
+fn run(mut config: MainConfig, in_daemon: bool) -> i32 {
+    let mut no_fork = in_daemon;
+    loop {
+        match show(config, no_fork) {
+            Shown::Exit(code) => return code,
+            Shown::SwitchMode(next) => {
+                config = *next;
+                // the process was forked for the first mode already
+                no_fork = true;
+            }
+        }
+    }
+}
+
+fn show(mut config: MainConfig, no_fork: bool) -> Shown {
+    let args = config.clone();
+    let loaded = config::load_worf_config(Some(&config.worf));
+    if config.worf.check_config() {
+        return Shown::Exit(check_config(&loaded));
+    }
+    if config.worf.stats() {
+        return Shown::Exit(print_stats());
+    }
+    config.worf = if let Ok(config) = loaded {
         config
     } else {
         log::warn!(
@@ -107,36 +384,120 @@ fn main() {
         config::Config::default()
     };
 
+    let Some(mut mode) = config.show else {
+        log::error!("no mode given");
+        return Shown::Exit(config::EXIT_CODE_ERROR);
+    };
+    if matches!(mode, Mode::Last) {
+        mode = load_last_mode();
+    }
+    save_last_mode(&mode);
+
+    let mode_config = config.worf.mode(&mode.to_string());
     if config.worf.prompt().is_none() {
-        config.worf.set_prompt(config.show.to_string());
+        config
+            .worf
+            .set_prompt(mode_config.placeholder.unwrap_or(mode.to_string()));
+    }
+    if config.worf.prompt_icon().is_none()
+        && let Some(icon) = mode_config.icon
+    {
+        config.worf.set_prompt_icon(icon);
+    }
+    if let Some(length) = mode_config.min_query_length {
+        config.worf.set_min_query_length(length);
+    }
+    let next_mode = next_cycle_mode(&config.worf.switch_modes(), &mode);
+    if next_mode.is_none() && !matches!(mode, Mode::Auto) {
+        config.worf.set_key_switch_mode(None);
     }
 
     if config.worf.version() {
         println!("worf version {}", env!("CARGO_PKG_VERSION"));
-        return;
+        return Shown::Exit(0);
     }
 
-    fork_if_configured(&config.worf); // may exit the program
+    if !no_fork {
+        fork_if_configured(&config.worf); // may exit the program
+    }
 
+    let no_selection_exit_code = config.worf.no_selection_exit_code();
     let cfg_arc = Arc::new(RwLock::new(config.worf));
-    let result = match config.show {
+    let result = match mode {
         Mode::Run => modes::run::show(&cfg_arc),
         Mode::Drun => modes::drun::show(&cfg_arc),
         Mode::Dmenu => modes::dmenu::show(&cfg_arc),
+        #[cfg(feature = "mode-file")]
         Mode::File => modes::file::show(&cfg_arc),
+        #[cfg(feature = "mode-math")]
         Mode::Math => modes::math::show(&cfg_arc),
         Mode::Ssh => modes::ssh::show(&cfg_arc),
+        #[cfg(feature = "mode-emoji")]
         Mode::Emoji => modes::emoji::show(&cfg_arc),
+        #[cfg(feature = "mode-auto")]
         Mode::Auto => modes::auto::show(&cfg_arc),
+        #[cfg(feature = "mode-search")]
         Mode::WebSearch => modes::search::show(&cfg_arc),
+        Mode::Notes => modes::notes::show(&cfg_arc),
+        Mode::Timer => modes::timer::show(&cfg_arc),
+        Mode::Open => modes::open::show(&cfg_arc),
+        Mode::Otp => modes::otp::show(&cfg_arc),
+        Mode::Palette => modes::palette::show(&cfg_arc),
+        Mode::Window => modes::window::show(&cfg_arc),
+        Mode::Clipboard => modes::clipboard::show(&cfg_arc),
+        Mode::Confirm => {
+            let config = cfg_arc.read().unwrap();
+            let question = config.prompt().unwrap_or_default();
+            match dialogs::confirm(&config, &question) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(Error::NoSelection),
+                Err(e) => Err(e),
+            }
+        }
+        Mode::Last => unreachable!("last mode is resolved before"),
+        #[allow(unreachable_patterns)]
+        mode => Err(Error::InvalidArgument(format!(
+            "worf was built without support for {mode} mode"
+        ))),
     };
 
-    if let Err(err) = result {
-        if err == Error::NoSelection {
+    let code = match result {
+        Ok(()) => 0,
+        Err(Error::NoSelection) => {
             log::info!("no selection made");
-        } else {
+            no_selection_exit_code
+        }
+        Err(Error::CustomKey(code)) => code,
+        Err(Error::WindowInfoPrinted) => 0,
+        Err(Error::SwitchMode(query)) => {
+            let Some(next_mode) = next_mode else {
+                return Shown::Exit(config::EXIT_CODE_ERROR);
+            };
+            log::debug!("switching to mode {next_mode}");
+            let mut config = args;
+            config.show = Some(next_mode);
+            if !query.is_empty() {
+                config.worf.set_search(query);
+            }
+            return Shown::SwitchMode(Box::new(config));
+        }
+        Err(err) => {
             log::error!("Error occurred {err:?}");
-            std::process::exit(1);
+            config::EXIT_CODE_ERROR
         }
-    }
+    };
+    Shown::Exit(code)
+}
+
+/// Returns the mode following `mode` in `cycle`, wrapping around at the end.
+/// `None` if `mode` is not part of the cycle.
+fn next_cycle_mode(cycle: &[String], mode: &Mode) -> Option<Mode> {
+    let mode = mode.to_string();
+    let pos = cycle.iter().position(|m| m.trim() == mode)?;
+    cycle
+        .iter()
+        .cycle()
+        .skip(pos + 1)
+        .take(cycle.len())
+        .find_map(|m| Mode::from_str(m.trim()).ok())
 }
@@ -5,15 +5,29 @@ use thiserror::Error;
 /// Configuration and command line parsing
 #[path = "lib/config.rs"]
 pub mod config;
+/// Long-running server keeping worf warm, later calls forward their arguments to it
+#[path = "lib/daemon.rs"]
+pub mod daemon;
 /// Desktop action like parsing desktop files and launching programs
 #[path = "lib/desktop.rs"]
 pub mod desktop;
+/// Small dialogs built on the gui, like confirmations
+#[path = "lib/dialogs.rs"]
+pub mod dialogs;
 #[path = "lib/gui.rs"]
 /// All things related to the user interface
 pub mod gui;
+/// Frecency history of used entries, shared across modes
+#[path = "lib/history.rs"]
+pub mod history;
 /// Out of the box supported modes, like drun, dmenu, etc...
 #[path = "lib/modes/mod.rs"]
 pub mod modes;
+/// Global shortcuts of the daemon, registered with the compositor
+#[path = "lib/shortcuts.rs"]
+pub mod shortcuts;
+#[path = "lib/virtual_keyboard.rs"]
+mod virtual_keyboard;
 
 /// Defines error the lib can encounter
 #[derive(Debug, PartialEq, Error)]
@@ -45,6 +59,14 @@ pub enum Error {
     NoSelection,
     /// Invalid argument
     InvalidArgument(String),
+    /// The selection was made with a custom key of dmenu mode,
+    /// worf exits with the given code instead of 0.
+    CustomKey(i32),
+    /// The window info was printed instead of showing the menu, see `print_window_info`.
+    WindowInfoPrinted,
+    /// `key_switch_mode` was pressed in a mode of `switch_modes`, the next one is to be shown.
+    /// Contains the search text, which is kept for the next mode.
+    SwitchMode(String),
 }
 
 impl fmt::Display for Error {
@@ -79,6 +101,9 @@ impl fmt::Display for Error {
                 write!(f, "NoSelection")
             }
             Error::InvalidArgument(s) => write!(f, "Invalid argument {s}"),
+            Error::CustomKey(code) => write!(f, "CustomKey {code}"),
+            Error::WindowInfoPrinted => write!(f, "WindowInfoPrinted"),
+            Error::SwitchMode(query) => write!(f, "SwitchMode: {query}"),
         }
     }
 }